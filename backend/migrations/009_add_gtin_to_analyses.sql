@@ -0,0 +1,4 @@
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS gtin VARCHAR(14);
+
+CREATE INDEX IF NOT EXISTS idx_analyses_gtin ON analyses(gtin);
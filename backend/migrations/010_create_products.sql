@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS products (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    gtin VARCHAR(14) NOT NULL UNIQUE,
+    brand TEXT,
+    name TEXT NOT NULL,
+    category TEXT,
+    ingredient_text TEXT,
+    canonical_analysis_id UUID REFERENCES analyses(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_products_name ON products (LOWER(name));
+
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS product_id UUID REFERENCES products(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_analyses_product_id ON analyses(product_id);
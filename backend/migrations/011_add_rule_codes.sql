@@ -0,0 +1,4 @@
+ALTER TABLE rules
+    ADD COLUMN IF NOT EXISTS e_numbers TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS ins_numbers TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS cns_codes TEXT[] NOT NULL DEFAULT '{}';
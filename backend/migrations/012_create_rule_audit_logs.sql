@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS rule_audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    rule_id TEXT NOT NULL,
+    action VARCHAR(20) NOT NULL,
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    before_data JSONB,
+    after_data JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_audit_logs_rule_id ON rule_audit_logs(rule_id, created_at DESC);
+
+-- Every committed change to the rules table tells running instances to reload.
+CREATE OR REPLACE FUNCTION notify_rules_changed() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('rules_changed', '');
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS rules_changed_notify ON rules;
+CREATE TRIGGER rules_changed_notify
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON rules
+    FOR EACH STATEMENT EXECUTE FUNCTION notify_rules_changed();
@@ -0,0 +1,10 @@
+-- Snapshot of the enabled rule set each time its content changes.
+CREATE TABLE IF NOT EXISTS rule_set_versions (
+    version BIGSERIAL PRIMARY KEY,
+    content_hash CHAR(64) NOT NULL,
+    rule_count INTEGER NOT NULL,
+    rules JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rule_set_versions_content_hash ON rule_set_versions(content_hash);
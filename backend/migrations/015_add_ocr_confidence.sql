@@ -0,0 +1,2 @@
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS ocr_confidence REAL;
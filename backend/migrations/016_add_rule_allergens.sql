@@ -0,0 +1,13 @@
+ALTER TABLE rules
+    ADD COLUMN IF NOT EXISTS allergens TEXT[] NOT NULL DEFAULT '{}';
+
+UPDATE rules SET allergens = '{peanut}' WHERE id = 'allergen-peanut' AND allergens = '{}';
+UPDATE rules SET allergens = '{milk}' WHERE id = 'allergen-milk' AND allergens = '{}';
+UPDATE rules SET allergens = '{egg}' WHERE id = 'allergen-egg' AND allergens = '{}';
+UPDATE rules SET allergens = '{gluten}' WHERE id = 'allergen-gluten' AND allergens = '{}';
+UPDATE rules SET allergens = '{soy}' WHERE id IN ('allergen-soy', 'additive-lecithin') AND allergens = '{}';
+UPDATE rules SET allergens = '{shellfish}' WHERE id = 'allergen-shellfish' AND allergens = '{}';
+UPDATE rules SET allergens = '{fish}' WHERE id = 'allergen-fish' AND allergens = '{}';
+UPDATE rules SET allergens = '{tree_nuts}' WHERE id = 'allergen-tree-nuts' AND allergens = '{}';
+UPDATE rules SET allergens = '{sesame}' WHERE id = 'allergen-sesame' AND allergens = '{}';
+UPDATE rules SET allergens = '{sulfites}' WHERE id = 'additive-sulfites' AND allergens = '{}';
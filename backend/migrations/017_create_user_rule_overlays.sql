@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS user_rule_overlays (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    ingredient TEXT NOT NULL,
+    rule_id TEXT,
+    risk_level VARCHAR(10) NOT NULL CHECK (risk_level IN ('low', 'medium', 'high')),
+    note TEXT,
+    trusted BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_rule_overlays_user_id ON user_rule_overlays(user_id, created_at);
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS family_profiles (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    preference VARCHAR(32),
+    allergens TEXT[] NOT NULL DEFAULT '{}',
+    avoid_ingredients TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_family_profiles_user_id ON family_profiles(user_id, created_at);
+
+-- The name is copied so history keeps saying who an analysis was scored for after the
+-- profile is renamed or deleted.
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS profile_id UUID REFERENCES family_profiles(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS profile_name TEXT;
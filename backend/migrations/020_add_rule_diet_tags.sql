@@ -0,0 +1,9 @@
+-- Diet tags on existing rules. The diet-only rules (category 'diet') come from the rules
+-- file through `rules_import import`.
+UPDATE rules SET groups = groups || '{not_vegan}' WHERE id IN ('allergen-milk', 'allergen-egg') AND NOT groups && '{not_vegan}';
+UPDATE rules SET groups = groups || '{not_gluten_free,not_keto}' WHERE id = 'allergen-gluten' AND NOT groups && '{not_gluten_free,not_keto}';
+UPDATE rules SET groups = groups || '{maybe_not_vegetarian}' WHERE id = 'additive-disodium-inosinate' AND NOT groups && '{maybe_not_vegetarian}';
+UPDATE rules SET groups = groups || '{not_keto}' WHERE id = 'other-high-fructose' AND NOT groups && '{not_keto}';
+UPDATE rules SET groups = groups || '{not_vegetarian,maybe_not_halal}' WHERE id = 'allergen-shellfish' AND NOT groups && '{not_vegetarian,maybe_not_halal}';
+UPDATE rules SET groups = groups || '{not_vegetarian}' WHERE id = 'allergen-fish' AND NOT groups && '{not_vegetarian}';
+UPDATE rules SET groups = groups || '{not_halal}' WHERE id = 'other-alcohol' AND NOT groups && '{not_halal}';
@@ -0,0 +1,20 @@
+-- History search. Chinese has no word boundaries for the built-in text search parsers, so
+-- substring matches go through a trigram index; the 'simple' text search index serves
+-- whole-word queries such as additive codes and English names.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS search_text TEXT GENERATED ALWAYS AS (
+        COALESCE(confirmed_text, '') || ' ' ||
+        COALESCE(result->>'summary', '') || ' ' ||
+        COALESCE(jsonb_path_query_array(result, '$.ingredients[*].name')::text, '')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_analyses_search_text_trgm
+    ON analyses USING GIN (search_text gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS idx_analyses_search_text_fts
+    ON analyses USING GIN (to_tsvector('simple', search_text));
+
+-- Primary analysis focus chosen when the analysis was confirmed; NULL for older analyses.
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS preference VARCHAR(32);
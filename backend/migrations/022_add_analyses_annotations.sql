@@ -0,0 +1,10 @@
+-- Favorites, tags and notes users keep on their own analyses.
+ALTER TABLE analyses
+    ADD COLUMN IF NOT EXISTS is_favorite BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS note TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_analyses_user_favorite
+    ON analyses(user_id, created_at DESC) WHERE is_favorite;
+CREATE INDEX IF NOT EXISTS idx_analyses_tags
+    ON analyses USING GIN (tags);
@@ -0,0 +1,61 @@
+use anyhow::{anyhow, bail, Result};
+use sqlx::PgPool;
+
+use backend::config::AppConfig;
+use shared::UserRole;
+
+const USAGE: &str = "usage: promote_user <username> [--role admin|moderator|user] [--force]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let mut username = None;
+    let mut role = UserRole::Admin;
+    let mut force = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--force" => force = true,
+            "--role" => {
+                let value = args.next().ok_or_else(|| anyhow!(USAGE))?;
+                role = UserRole::parse(&value).ok_or_else(|| anyhow!("unknown role: {}", value))?;
+            }
+            _ if username.is_none() && !arg.starts_with("--") => username = Some(arg),
+            _ => bail!(USAGE),
+        }
+    }
+    let username = username.ok_or_else(|| anyhow!(USAGE))?;
+
+    let config = AppConfig::from_env()?;
+    let pool = PgPool::connect(&config.database_url).await?;
+
+    // Once an admin exists, roles are managed through the admin API instead.
+    let admins: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = 'admin'")
+        .fetch_one(&pool)
+        .await?;
+    if admins > 0 && !force {
+        bail!(
+            "{} admin(s) already exist; use PUT /api/v1/admin/users/{{id}}/role or pass --force",
+            admins
+        );
+    }
+
+    let updated = sqlx::query(
+        "UPDATE users SET role = $2, updated_at = NOW() WHERE username_normalized = $1",
+    )
+    .bind(username.trim().to_lowercase())
+    .bind(role.as_str())
+    .execute(&pool)
+    .await?;
+    if updated.rows_affected() == 0 {
+        bail!("user not found: {}", username);
+    }
+
+    println!(
+        "Set role of {} to {}; the user must log in again or refresh their token",
+        username,
+        role.as_str()
+    );
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, bail, Result};
+use sqlx::PgPool;
+use std::path::Path;
+
+use backend::cli::rule_eval::{evaluate_corpus, find_regressions, CorpusCase, EvalReport};
+use backend::config::AppConfig;
+use backend::services::rules::{load_enabled_items, load_items_from_path, RuleEngine};
+
+const USAGE: &str = "usage: rules_eval [--corpus PATH] [--rules PATH | --db] \
+                     [--baseline PATH] [--save-baseline PATH]";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut corpus_path = manifest_dir.join("corpus/rules_golden.json");
+    let mut baseline_path = Some(manifest_dir.join("corpus/rules_baseline.json"));
+    let mut rules_path = std::env::var("RULES_PATH").ok();
+    let mut save_path = None;
+    let mut from_db = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!(USAGE));
+        match arg.as_str() {
+            "--corpus" => corpus_path = value()?.into(),
+            "--rules" => rules_path = Some(value()?),
+            "--baseline" => baseline_path = Some(value()?.into()),
+            "--save-baseline" => save_path = Some(value()?),
+            "--db" => from_db = true,
+            _ => bail!(USAGE),
+        }
+    }
+
+    let items = if from_db {
+        let config = AppConfig::from_env()?;
+        let pool = PgPool::connect(&config.database_url).await?;
+        load_enabled_items(&pool).await?
+    } else {
+        let path = rules_path.unwrap_or_else(|| {
+            manifest_dir
+                .join("rules.json")
+                .to_string_lossy()
+                .to_string()
+        });
+        load_items_from_path(&path)?
+    };
+    let engine = RuleEngine::from_items(items);
+
+    let cases: Vec<CorpusCase> = serde_json::from_str(&std::fs::read_to_string(&corpus_path)?)?;
+    let report = evaluate_corpus(&engine, &cases);
+    print_report(&report, cases.len());
+
+    if let Some(path) = save_path {
+        std::fs::write(&path, serde_json::to_string_pretty(&report)? + "\n")?;
+        println!("Saved baseline to {}", path);
+        return Ok(());
+    }
+
+    let Some(baseline_path) = baseline_path.filter(|path| path.exists()) else {
+        println!("No baseline to compare against");
+        return Ok(());
+    };
+    let baseline: EvalReport = serde_json::from_str(&std::fs::read_to_string(&baseline_path)?)?;
+    println!(
+        "Baseline {}: precision {:.3}, recall {:.3}",
+        baseline_path.display(),
+        baseline.precision,
+        baseline.recall
+    );
+    let regressions = find_regressions(&baseline, &report);
+    if regressions.is_empty() {
+        println!("No regressions");
+        return Ok(());
+    }
+    for regression in &regressions {
+        for rule in &regression.newly_missed {
+            println!("REGRESSION {}: no longer hits {}", regression.case_id, rule);
+        }
+        for rule in &regression.newly_unexpected {
+            println!("REGRESSION {}: now hits {}", regression.case_id, rule);
+        }
+    }
+    bail!("{} case(s) regressed", regressions.len())
+}
+
+fn print_report(report: &EvalReport, case_count: usize) {
+    println!(
+        "{} cases: precision {:.3}, recall {:.3} (tp {}, fp {}, fn {})",
+        case_count,
+        report.precision,
+        report.recall,
+        report.true_positives,
+        report.false_positives,
+        report.false_negatives
+    );
+    for (rule, stats) in &report.rules {
+        if stats.false_negatives > 0 || stats.false_positives > 0 {
+            println!(
+                "  {}: {} hit, {} missed, {} unexpected",
+                rule, stats.true_positives, stats.false_negatives, stats.false_positives
+            );
+        }
+    }
+    for case in &report.cases {
+        if !case.missed.is_empty() {
+            println!("  case {} missed {}", case.id, case.missed.join(", "));
+        }
+        if !case.unexpected.is_empty() {
+            println!(
+                "  case {} unexpected {}",
+                case.id,
+                case.unexpected.join(", ")
+            );
+        }
+    }
+}
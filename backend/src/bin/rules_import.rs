@@ -1,49 +1,224 @@
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use sqlx::PgPool;
+use std::collections::HashSet;
 
-use backend::config::AppConfig;
-use backend::services::rules::load_items_from_path;
+use backend::cli::rule_export::{self, ExportFormat};
+use backend::config::{rules_path_from_env, AppConfig};
+use backend::db;
+use backend::services::rule_validation::validate_rule_set;
+use backend::services::rule_versions::{diff_rules, RuleDiff};
+use backend::services::rules::{load_enabled_items, load_items_from_path, RuleItem};
+
+const USAGE: &str = "usage: rules_import [validate|diff|import|export] [--file PATH] \
+                     [--dry-run] [--prune] [--format json|csv] [--output PATH]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Validate,
+    Diff,
+    Import,
+    Export,
+}
+
+struct Options {
+    command: Command,
+    file: Option<String>,
+    dry_run: bool,
+    prune: bool,
+    format: ExportFormat,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Options> {
+    let mut options = Options {
+        command: Command::Import,
+        file: None,
+        dry_run: false,
+        prune: false,
+        format: ExportFormat::Json,
+        output: None,
+    };
+    let mut args = std::env::args().skip(1).peekable();
+    if let Some(first) = args.peek() {
+        let command = match first.as_str() {
+            "validate" => Some(Command::Validate),
+            "diff" => Some(Command::Diff),
+            "import" => Some(Command::Import),
+            "export" => Some(Command::Export),
+            _ => None,
+        };
+        if let Some(command) = command {
+            options.command = command;
+            args.next();
+        }
+    }
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => options.dry_run = true,
+            "--prune" => options.prune = true,
+            "--file" => options.file = Some(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            "--output" => options.output = Some(args.next().ok_or_else(|| anyhow!(USAGE))?),
+            "--format" => {
+                let value = args.next().ok_or_else(|| anyhow!(USAGE))?;
+                options.format = ExportFormat::parse(&value)
+                    .ok_or_else(|| anyhow!("unknown export format: {}", value))?;
+            }
+            _ => bail!(USAGE),
+        }
+    }
+    Ok(options)
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
+    let options = parse_args()?;
+    let path = options.file.clone().unwrap_or_else(rules_path_from_env);
+
+    match options.command {
+        Command::Validate => {
+            let items = load_valid_items(&path)?;
+            println!("{}: {} rules, no problems found", path, items.len());
+        }
+        Command::Diff => {
+            let pool = connect().await?;
+            let items = load_items_from_path(&path)?;
+            let current = load_enabled_items(&pool).await?;
+            print_diff(&diff_rules(&current, &items), options.prune);
+        }
+        Command::Import => {
+            let pool = connect().await?;
+            import(&pool, &path, options.dry_run, options.prune).await?;
+        }
+        Command::Export => {
+            let pool = connect().await?;
+            let items = load_enabled_items(&pool).await?;
+            let content = rule_export::export_rules(&items, options.format)?;
+            match &options.output {
+                Some(output) => {
+                    std::fs::write(output, content)?;
+                    println!("Exported {} rules to {}", items.len(), output);
+                }
+                None => print!("{}", content),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Connect to the database; only commands that need it load the full configuration
+async fn connect() -> Result<PgPool> {
     let config = AppConfig::from_env()?;
-    let pool = PgPool::connect(&config.database_url).await?;
+    Ok(PgPool::connect(&config.database_url).await?)
+}
 
-    let items = load_items_from_path(&config.rules_path)?;
-    let total = items.len();
-    let mut tx = pool.begin().await?;
+/// Load the rule file, refusing files with invalid rules or colliding names
+fn load_valid_items(path: &str) -> Result<Vec<RuleItem>> {
+    let items = load_items_from_path(path)?;
+    let problems = validate_rule_set(&items);
+    if !problems.is_empty() {
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        bail!("{}: {} problem(s) found", path, problems.len());
+    }
+    Ok(items)
+}
+
+async fn import(pool: &PgPool, path: &str, dry_run: bool, prune: bool) -> Result<()> {
+    let items = load_valid_items(path)?;
+    let current = load_enabled_items(pool).await?;
+    let diff = diff_rules(&current, &items);
+    print_diff(&diff, prune);
+    if dry_run {
+        println!("Dry run, no changes written");
+        return Ok(());
+    }
+
+    // Every write goes through the audited rule functions the admin API uses, inside one
+    // transaction; unchanged rules are skipped so they get no audit noise
+    let enabled_ids: HashSet<&str> = current.iter().map(|item| item.id.as_str()).collect();
+    let changed_ids: HashSet<&str> = diff
+        .changed
+        .iter()
+        .map(|change| change.id.as_str())
+        .collect();
+    let (mut created, mut updated, mut enabled, mut disabled) = (0, 0, 0, 0);
 
+    let mut tx = pool.begin().await?;
     for item in &items {
-        sqlx::query(
-            "INSERT INTO rules (id, name, aliases, category, risk_level, groups, description, evidence, source, enabled)\
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, TRUE)\
-             ON CONFLICT (id) DO UPDATE SET \
-               name = EXCLUDED.name,\
-               aliases = EXCLUDED.aliases,\
-               category = EXCLUDED.category,\
-               risk_level = EXCLUDED.risk_level,\
-               groups = EXCLUDED.groups,\
-               description = EXCLUDED.description,\
-               evidence = EXCLUDED.evidence,\
-               source = EXCLUDED.source,\
-               enabled = TRUE,\
-               updated_at = NOW()",
-        )
-        .bind(&item.id)
-        .bind(&item.name)
-        .bind(&item.aliases)
-        .bind(&item.category)
-        .bind(&item.risk_level)
-        .bind(&item.groups)
-        .bind(&item.description)
-        .bind(&item.evidence)
-        .bind(&item.source)
-        .execute(&mut *tx)
-        .await?;
+        if db::insert_rule(&mut *tx, item, None).await?.is_some() {
+            created += 1;
+            continue;
+        }
+        let was_enabled = enabled_ids.contains(item.id.as_str());
+        if !was_enabled || changed_ids.contains(item.id.as_str()) {
+            db::update_rule(&mut *tx, item, "import", None).await?;
+            updated += 1;
+        }
+        if !was_enabled {
+            db::set_rule_enabled(&mut *tx, &item.id, true, None).await?;
+            enabled += 1;
+        }
+    }
+
+    if prune {
+        for rule in &diff.removed {
+            db::set_rule_enabled(&mut *tx, &rule.id, false, None).await?;
+            disabled += 1;
+        }
     }
 
     tx.commit().await?;
-    println!("Imported {} rules", total);
+    println!(
+        "Imported {} rules: {} created, {} updated, {} re-enabled, {} disabled",
+        items.len(),
+        created,
+        updated,
+        enabled,
+        disabled
+    );
     Ok(())
 }
+
+/// Print changes from the enabled database rules to the file
+fn print_diff(diff: &RuleDiff, prune: bool) {
+    if diff.added.is_empty() && diff.changed.is_empty() && diff.removed.is_empty() {
+        println!("No differences");
+        return;
+    }
+    for rule in &diff.added {
+        println!("+ {} ({})", rule.id, rule.name);
+    }
+    for change in &diff.changed {
+        let mut details = change.changed_fields.clone();
+        details.extend(
+            change
+                .added_aliases
+                .iter()
+                .map(|alias| format!("+alias {}", alias)),
+        );
+        details.extend(
+            change
+                .removed_aliases
+                .iter()
+                .map(|alias| format!("-alias {}", alias)),
+        );
+        println!("~ {} ({}): {}", change.id, change.name, details.join(", "));
+    }
+    let removed_note = if prune {
+        "disabled"
+    } else {
+        "kept, pass --prune to disable"
+    };
+    for rule in &diff.removed {
+        println!("- {} ({}) [{}]", rule.id, rule.name, removed_note);
+    }
+    println!(
+        "{} added, {} changed, {} missing from file",
+        diff.added.len(),
+        diff.changed.len(),
+        diff.removed.len()
+    );
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use sqlx::PgPool;
+
+use backend::config::AppConfig;
+use backend::services::retention;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    dotenvy::dotenv().ok();
+    let config = AppConfig::from_env()?;
+    let pool = PgPool::connect(&config.database_url).await?;
+
+    let dry_run = std::env::args().any(|arg| arg == "--dry-run");
+    let report = retention::run_once(&pool, &config.upload_dir, &config.retention, dry_run).await?;
+
+    let verb = if dry_run { "Would delete" } else { "Deleted" };
+    println!(
+        "{} {} unconfirmed analyses and {} of {} files ({} referenced), reclaiming {:.1} MB",
+        verb,
+        report.purged_analyses,
+        report.deleted_files,
+        report.scanned_files,
+        report.referenced_files,
+        report.reclaimed_bytes as f64 / (1024.0 * 1024.0)
+    );
+    Ok(())
+}
@@ -0,0 +1,6 @@
+//! Library code used only by the command-line binaries
+//!
+//! Declared in `lib.rs` but not in `main.rs`, so the server binary does not compile it.
+
+pub mod rule_eval;
+pub mod rule_export;
@@ -0,0 +1,257 @@
+//! Golden-corpus evaluation of the rule engine
+//!
+//! A corpus is a JSON list of ingredient texts with the rule IDs they should hit. Running
+//! the engine over it yields precision and recall, and comparing two reports lists the
+//! cases a rule edit broke.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::services::llm::PreferenceBlend;
+use crate::services::rules::RuleEngine;
+
+/// One labelled ingredient text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorpusCase {
+    /// Stable case ID used to compare runs
+    pub id: String,
+    /// Ingredient text as read from a label
+    pub text: String,
+    /// Rule IDs the text should hit
+    #[serde(default)]
+    pub expected: Vec<String>,
+    /// Preference to evaluate with (defaults to none)
+    #[serde(default)]
+    pub preference: Option<String>,
+}
+
+/// Outcome of a single case
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub id: String,
+    /// Expected rules the engine found
+    pub matched: Vec<String>,
+    /// Expected rules the engine did not find
+    pub missed: Vec<String>,
+    /// Rules the engine found that were not expected
+    pub unexpected: Vec<String>,
+}
+
+/// Hit counts for one rule across the corpus
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleStats {
+    pub true_positives: u32,
+    pub false_positives: u32,
+    pub false_negatives: u32,
+}
+
+/// Result of running the engine over a corpus; saved as the baseline for later runs
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub true_positives: u32,
+    pub false_positives: u32,
+    pub false_negatives: u32,
+    pub precision: f64,
+    pub recall: f64,
+    pub rules: BTreeMap<String, RuleStats>,
+    pub cases: Vec<CaseResult>,
+}
+
+/// A case that got worse compared to the baseline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Regression {
+    pub case_id: String,
+    /// Expected rules the baseline found but the current run misses
+    pub newly_missed: Vec<String>,
+    /// Unexpected rules the current run finds but the baseline did not
+    pub newly_unexpected: Vec<String>,
+}
+
+/// Evaluate every case and aggregate precision, recall and per-rule counts
+pub fn evaluate_corpus(engine: &RuleEngine, cases: &[CorpusCase]) -> EvalReport {
+    let mut report = EvalReport::default();
+    for case in cases {
+        let preference = PreferenceBlend::from_request(case.preference.as_deref(), &[]);
+        let found: BTreeSet<String> = engine
+            .evaluate(&case.text, &preference)
+            .hits
+            .into_iter()
+            .filter_map(|hit| hit.rule_id)
+            .collect();
+        let expected: BTreeSet<String> = case.expected.iter().cloned().collect();
+
+        let result = CaseResult {
+            id: case.id.clone(),
+            matched: expected.intersection(&found).cloned().collect(),
+            missed: expected.difference(&found).cloned().collect(),
+            unexpected: found.difference(&expected).cloned().collect(),
+        };
+        for rule in &result.matched {
+            report.rules.entry(rule.clone()).or_default().true_positives += 1;
+        }
+        for rule in &result.unexpected {
+            report
+                .rules
+                .entry(rule.clone())
+                .or_default()
+                .false_positives += 1;
+        }
+        for rule in &result.missed {
+            report
+                .rules
+                .entry(rule.clone())
+                .or_default()
+                .false_negatives += 1;
+        }
+        report.true_positives += result.matched.len() as u32;
+        report.false_positives += result.unexpected.len() as u32;
+        report.false_negatives += result.missed.len() as u32;
+        report.cases.push(result);
+    }
+
+    report.precision = ratio(
+        report.true_positives,
+        report.true_positives + report.false_positives,
+    );
+    report.recall = ratio(
+        report.true_positives,
+        report.true_positives + report.false_negatives,
+    );
+    report
+}
+
+/// Cases where the current run misses a rule the baseline matched, or finds an unexpected
+/// rule the baseline did not. Cases missing from the baseline are not compared.
+pub fn find_regressions(baseline: &EvalReport, current: &EvalReport) -> Vec<Regression> {
+    let previous: BTreeMap<&str, &CaseResult> = baseline
+        .cases
+        .iter()
+        .map(|case| (case.id.as_str(), case))
+        .collect();
+
+    current
+        .cases
+        .iter()
+        .filter_map(|case| {
+            let before = previous.get(case.id.as_str())?;
+            let regression = Regression {
+                case_id: case.id.clone(),
+                newly_missed: case
+                    .missed
+                    .iter()
+                    .filter(|rule| before.matched.contains(rule))
+                    .cloned()
+                    .collect(),
+                newly_unexpected: case
+                    .unexpected
+                    .iter()
+                    .filter(|rule| !before.unexpected.contains(rule))
+                    .cloned()
+                    .collect(),
+            };
+            (!regression.newly_missed.is_empty() || !regression.newly_unexpected.is_empty())
+                .then_some(regression)
+        })
+        .collect()
+}
+
+fn ratio(numerator: u32, denominator: u32) -> f64 {
+    if denominator == 0 {
+        1.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::rules::RuleItem;
+
+    fn item(id: &str, name: &str, aliases: &[&str]) -> RuleItem {
+        RuleItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: "additive".to_string(),
+            risk_level: "low".to_string(),
+            groups: Vec::new(),
+            description: String::new(),
+            evidence: None,
+            source: None,
+            e_numbers: Vec::new(),
+            ins_numbers: Vec::new(),
+            cns_codes: Vec::new(),
+            allergens: Vec::new(),
+        }
+    }
+
+    fn case(id: &str, text: &str, expected: &[&str]) -> CorpusCase {
+        CorpusCase {
+            id: id.to_string(),
+            text: text.to_string(),
+            expected: expected.iter().map(|rule| rule.to_string()).collect(),
+            preference: None,
+        }
+    }
+
+    fn corpus() -> Vec<CorpusCase> {
+        vec![
+            case("drink", "配料：水、白砂糖、山梨酸钾", &["sugar", "sorbate"]),
+            case("bread", "配料：小麦粉、水、食用盐", &["sugar"]),
+        ]
+    }
+
+    #[test]
+    fn computes_precision_recall_and_per_rule_misses() {
+        let engine = RuleEngine::from_items(vec![
+            item("sugar", "白砂糖", &[]),
+            item("sorbate", "山梨酸钾", &[]),
+            item("salt", "食用盐", &[]),
+        ]);
+        let report = evaluate_corpus(&engine, &corpus());
+
+        assert_eq!(report.true_positives, 2);
+        assert_eq!(report.false_positives, 1);
+        assert_eq!(report.false_negatives, 1);
+        assert!((report.precision - 2.0 / 3.0).abs() < 1e-9);
+        assert!((report.recall - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(report.rules["sugar"].false_negatives, 1);
+        assert_eq!(report.cases[1].unexpected, vec!["salt".to_string()]);
+    }
+
+    #[test]
+    fn reports_rules_lost_since_baseline() {
+        let baseline = evaluate_corpus(
+            &RuleEngine::from_items(vec![
+                item("sugar", "白砂糖", &[]),
+                item("sorbate", "山梨酸钾", &[]),
+            ]),
+            &corpus(),
+        );
+        let current = evaluate_corpus(
+            &RuleEngine::from_items(vec![
+                item("sugar", "蔗糖", &[]),
+                item("sorbate", "山梨酸钾", &[]),
+                item("salt", "食用盐", &[]),
+            ]),
+            &corpus(),
+        );
+
+        assert_eq!(
+            find_regressions(&baseline, &current),
+            vec![
+                Regression {
+                    case_id: "drink".to_string(),
+                    newly_missed: vec!["sugar".to_string()],
+                    newly_unexpected: Vec::new(),
+                },
+                Regression {
+                    case_id: "bread".to_string(),
+                    newly_missed: Vec::new(),
+                    newly_unexpected: vec!["salt".to_string()],
+                },
+            ]
+        );
+    }
+}
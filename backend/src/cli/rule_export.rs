@@ -0,0 +1,115 @@
+//! Rule library export formats
+
+use crate::services::rules::RuleItem;
+
+/// Separator for list fields in CSV cells
+const CSV_LIST_SEPARATOR: &str = "|";
+
+const CSV_HEADER: &[&str] = &[
+    "id",
+    "name",
+    "aliases",
+    "category",
+    "risk_level",
+    "groups",
+    "description",
+    "evidence",
+    "source",
+    "e_numbers",
+    "ins_numbers",
+    "cns_codes",
+    "allergens",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "json" => Some(Self::Json),
+            "csv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Serialize rules; JSON uses the `RULES_PATH` file layout, CSV joins list fields with `|`
+pub fn export_rules(items: &[RuleItem], format: ExportFormat) -> anyhow::Result<String> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(items)? + "\n"),
+        ExportFormat::Csv => export_csv(items),
+    }
+}
+
+fn export_csv(items: &[RuleItem]) -> anyhow::Result<String> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADER)?;
+    for item in items {
+        writer.write_record([
+            item.id.as_str(),
+            item.name.as_str(),
+            &item.aliases.join(CSV_LIST_SEPARATOR),
+            item.category.as_str(),
+            item.risk_level.as_str(),
+            &item.groups.join(CSV_LIST_SEPARATOR),
+            item.description.as_str(),
+            item.evidence.as_deref().unwrap_or_default(),
+            item.source.as_deref().unwrap_or_default(),
+            &item.e_numbers.join(CSV_LIST_SEPARATOR),
+            &item.ins_numbers.join(CSV_LIST_SEPARATOR),
+            &item.cns_codes.join(CSV_LIST_SEPARATOR),
+            &item.allergens.join(CSV_LIST_SEPARATOR),
+        ])?;
+    }
+    Ok(String::from_utf8(writer.into_inner()?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> RuleItem {
+        RuleItem {
+            id: "additive-sodium-benzoate".to_string(),
+            name: "苯甲酸钠".to_string(),
+            aliases: vec!["苯甲酸".to_string(), "安息香酸钠".to_string()],
+            category: "additive".to_string(),
+            risk_level: "medium".to_string(),
+            groups: vec!["kids".to_string()],
+            description: "防腐剂，\"过量\"摄入需注意, 儿童慎用".to_string(),
+            evidence: None,
+            source: None,
+            e_numbers: vec!["E211".to_string()],
+            ins_numbers: Vec::new(),
+            cns_codes: vec!["17.001".to_string()],
+            allergens: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn csv_joins_lists_and_quotes_text() {
+        let csv = export_rules(&[item()], ExportFormat::Csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER.join(",").as_str()));
+        assert_eq!(
+            lines.next(),
+            Some(
+                "additive-sodium-benzoate,苯甲酸钠,苯甲酸|安息香酸钠,additive,medium,kids,\
+                 \"防腐剂，\"\"过量\"\"摄入需注意, 儿童慎用\",,,E211,,17.001,"
+            )
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let items = vec![item()];
+        let json = export_rules(&items, ExportFormat::Json).unwrap();
+        let from_json: Vec<RuleItem> = serde_json::from_str(&json).unwrap();
+        assert_eq!(from_json[0].aliases, items[0].aliases);
+        assert_eq!(from_json[0].description, items[0].description);
+    }
+}
@@ -10,7 +10,7 @@ pub struct AppConfig {
     pub ocr: OcrConfig,
     pub auth: AuthConfig,
     pub rules_path: String,
-    pub rules_refresh_seconds: u64,
+    pub retention: RetentionConfig,
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +34,14 @@ pub struct LlmConfig {
     pub timeout: Duration,
 }
 
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub interval: Duration,
+    pub unconfirmed_ttl_hours: i64,
+    pub orphan_grace: Duration,
+}
+
 #[derive(Debug, Clone)]
 pub struct AuthConfig {
     pub redis_url: String,
@@ -44,6 +52,8 @@ pub struct AuthConfig {
     pub login_hash_key: String,
     pub login_max_attempts: u32,
     pub login_lock_seconds: u64,
+    /// Users granted the admin role at startup (`ADMIN_USER_IDS`)
+    pub admin_user_ids: Vec<uuid::Uuid>,
 }
 
 impl AppConfig {
@@ -113,19 +123,33 @@ impl AppConfig {
                 .ok()
                 .and_then(|value| value.parse::<u64>().ok())
                 .unwrap_or(900),
+            admin_user_ids: parse_uuid_list(&env::var("ADMIN_USER_IDS").unwrap_or_default())?,
         };
 
-        let rules_path = env::var("RULES_PATH").unwrap_or_else(|_| {
-            std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
-                .join("rules.json")
-                .to_string_lossy()
-                .to_string()
-        });
+        let rules_path = rules_path_from_env();
 
-        let rules_refresh_seconds = env::var("RULES_REFRESH_SECONDS")
-            .ok()
-            .and_then(|value| value.parse::<u64>().ok())
-            .unwrap_or(300);
+        let retention = RetentionConfig {
+            enabled: env::var("UPLOAD_GC_ENABLED")
+                .ok()
+                .and_then(|value| value.parse::<bool>().ok())
+                .unwrap_or(true),
+            interval: Duration::from_secs(
+                env::var("UPLOAD_GC_INTERVAL_SECONDS")
+                    .ok()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(3600),
+            ),
+            unconfirmed_ttl_hours: env::var("UNCONFIRMED_ANALYSIS_TTL_HOURS")
+                .ok()
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or(72),
+            orphan_grace: Duration::from_secs(
+                env::var("UPLOAD_ORPHAN_GRACE_SECONDS")
+                    .ok()
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(3600),
+            ),
+        };
 
         Ok(Self {
             database_url,
@@ -134,11 +158,33 @@ impl AppConfig {
             ocr,
             auth,
             rules_path,
-            rules_refresh_seconds,
+            retention,
         })
     }
 }
 
+/// `RULES_PATH`, or the bundled `rules.json`; needs none of the other settings
+pub fn rules_path_from_env() -> String {
+    env::var("RULES_PATH").unwrap_or_else(|_| {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("rules.json")
+            .to_string_lossy()
+            .to_string()
+    })
+}
+
+fn parse_uuid_list(value: &str) -> anyhow::Result<Vec<uuid::Uuid>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            uuid::Uuid::parse_str(item)
+                .map_err(|_| anyhow::anyhow!("invalid UUID in ADMIN_USER_IDS: {}", item))
+        })
+        .collect()
+}
+
 fn parse_llm_provider(value: String) -> anyhow::Result<LlmProvider> {
     match value.trim().to_lowercase().as_str() {
         "deepseek" => Ok(LlmProvider::DeepSeek),
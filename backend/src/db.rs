@@ -2,9 +2,13 @@
 
 use chrono::{DateTime, Utc};
 use serde_json::Value;
-use sqlx::{FromRow, PgPool, Row};
+use sqlx::postgres::PgArguments;
+use sqlx::query::QueryAs;
+use sqlx::{Acquire, FromRow, PgConnection, PgPool, Postgres, Row};
 use uuid::Uuid;
 
+use crate::services::rules::RuleItem;
+
 /// Create database connection pool
 pub async fn create_pool(database_url: &str) -> sqlx::Result<PgPool> {
     PgPool::connect(database_url).await
@@ -21,6 +25,7 @@ pub struct AnalysisRow {
     pub id: Uuid,
     pub image_url: String,
     pub ocr_text: Option<String>,
+    pub ocr_confidence: Option<f32>,
     pub confirmed_text: Option<String>,
     pub ocr_status: String,
     pub llm_status: String,
@@ -29,6 +34,14 @@ pub struct AnalysisRow {
     pub result: Option<Value>,
     pub status: String,
     pub error_message: Option<String>,
+    pub gtin: Option<String>,
+    pub user_id: Option<Uuid>,
+    pub product_id: Option<Uuid>,
+    pub profile_id: Option<Uuid>,
+    pub profile_name: Option<String>,
+    pub is_favorite: bool,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -69,6 +82,29 @@ pub async fn attach_user_to_analysis(pool: &PgPool, id: Uuid, user_id: Uuid) ->
     Ok(())
 }
 
+/// Record the family profile an analysis is scored for; `None` clears it
+pub async fn set_analysis_profile(
+    pool: &PgPool,
+    id: Uuid,
+    profile: Option<&FamilyProfileRow>,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE analyses
+        SET profile_id = $2,
+            profile_name = $3,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(profile.map(|profile| profile.id))
+    .bind(profile.map(|profile| profile.name.as_str()))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 pub async fn update_ocr_status(
     pool: &PgPool,
     id: Uuid,
@@ -99,12 +135,14 @@ pub async fn save_ocr_result(
     pool: &PgPool,
     id: Uuid,
     text: &str,
+    confidence: Option<f32>,
     status: &str,
 ) -> sqlx::Result<()> {
     sqlx::query(
         r#"
         UPDATE analyses
         SET ocr_text = $2,
+            ocr_confidence = $4,
             ocr_status = 'completed',
             status = $3,
             ocr_completed_at = NOW(),
@@ -116,6 +154,25 @@ pub async fn save_ocr_result(
     .bind(id)
     .bind(text)
     .bind(status)
+    .bind(confidence)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store the decoded GTIN and link the analysis to a known product with that GTIN
+pub async fn save_analysis_gtin(pool: &PgPool, id: Uuid, gtin: &str) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE analyses
+        SET gtin = $2,
+            product_id = COALESCE(product_id, (SELECT id FROM products WHERE gtin = $2)),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(gtin)
     .execute(pool)
     .await?;
     Ok(())
@@ -212,6 +269,7 @@ pub async fn get_analysis(pool: &PgPool, id: Uuid) -> sqlx::Result<Option<Analys
         SELECT id,
                image_url,
                ocr_text,
+               ocr_confidence,
                confirmed_text,
                ocr_status,
                llm_status,
@@ -220,6 +278,14 @@ pub async fn get_analysis(pool: &PgPool, id: Uuid) -> sqlx::Result<Option<Analys
                result,
                status,
                error_message,
+               gtin,
+               user_id,
+               product_id,
+               profile_id,
+               profile_name,
+               is_favorite,
+               tags,
+               note,
                created_at,
                updated_at
         FROM analyses
@@ -252,6 +318,7 @@ pub async fn list_history(
         SELECT id,
                image_url,
                ocr_text,
+               ocr_confidence,
                confirmed_text,
                ocr_status,
                llm_status,
@@ -260,6 +327,14 @@ pub async fn list_history(
                result,
                status,
                error_message,
+               gtin,
+               user_id,
+               product_id,
+               profile_id,
+               profile_name,
+               is_favorite,
+               tags,
+               note,
                created_at,
                updated_at
         FROM analyses
@@ -276,28 +351,127 @@ pub async fn list_history(
     Ok((total, rows))
 }
 
+/// Record the primary focus an analysis was confirmed with
+pub async fn set_analysis_preference(
+    pool: &PgPool,
+    id: Uuid,
+    preference: &str,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE analyses
+        SET preference = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(preference)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Conditions on a user's history; unset fields do not filter
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    /// Words that must all appear in the confirmed text, summary or ingredient names
+    pub query: Option<String>,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    pub created_from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound
+    pub created_until: Option<DateTime<Utc>>,
+    pub preference: Option<String>,
+    pub warning_type: Option<String>,
+    /// Ingredient name fragment
+    pub ingredient: Option<String>,
+    /// Only favorites
+    pub favorite: bool,
+    /// Tag, compared case-insensitively
+    pub tag: Option<String>,
+}
+
+/// `$1` is the user; `$2`-`$12` the filter fields, see [`bind_history_filter`]
+const USER_HISTORY_CONDITIONS: &str = r#"
+        user_id = $1 AND status = 'completed'
+        AND ($2::text IS NULL
+             OR to_tsvector('simple', search_text) @@ plainto_tsquery('simple', $2)
+             OR search_text ILIKE ALL($3::text[]))
+        AND ($4::int IS NULL OR health_score >= $4)
+        AND ($5::int IS NULL OR health_score <= $5)
+        AND ($6::timestamptz IS NULL OR created_at >= $6)
+        AND ($7::timestamptz IS NULL OR created_at < $7)
+        AND ($8::text IS NULL OR preference = $8)
+        AND ($9::text IS NULL
+             OR result->'warnings' @> jsonb_build_array(jsonb_build_object('warning_type', $9::text)))
+        AND ($10::text IS NULL
+             OR confirmed_text ILIKE $10
+             OR EXISTS (
+                 SELECT 1
+                 FROM jsonb_array_elements(
+                     CASE WHEN jsonb_typeof(result->'ingredients') = 'array'
+                          THEN result->'ingredients' ELSE '[]'::jsonb END
+                 ) AS item
+                 WHERE item->>'name' ILIKE $10
+             ))
+        AND (NOT $11::bool OR is_favorite)
+        AND ($12::text IS NULL
+             OR EXISTS (SELECT 1 FROM unnest(tags) AS tag WHERE lower(tag) = lower($12)))
+"#;
+
+fn bind_history_filter<'q, O>(
+    query: QueryAs<'q, Postgres, O, PgArguments>,
+    user_id: Uuid,
+    filter: &'q HistoryFilter,
+) -> QueryAs<'q, Postgres, O, PgArguments> {
+    let patterns: Vec<String> = filter
+        .query
+        .iter()
+        .flat_map(|query| query.split_whitespace())
+        .map(|term| format!("%{}%", escape_like(term)))
+        .collect();
+    query
+        .bind(user_id)
+        .bind(filter.query.as_deref())
+        .bind(patterns)
+        .bind(filter.min_score)
+        .bind(filter.max_score)
+        .bind(filter.created_from)
+        .bind(filter.created_until)
+        .bind(filter.preference.as_deref())
+        .bind(filter.warning_type.as_deref())
+        .bind(
+            filter
+                .ingredient
+                .as_deref()
+                .map(|name| format!("%{}%", escape_like(name))),
+        )
+        .bind(filter.favorite)
+        .bind(filter.tag.as_deref())
+}
+
 pub async fn list_user_history(
     pool: &PgPool,
     user_id: Uuid,
+    filter: &HistoryFilter,
     limit: i64,
     offset: i64,
 ) -> sqlx::Result<(i64, Vec<AnalysisRow>)> {
-    let total: i64 = sqlx::query_scalar::<_, i64>(
-        r#"
-        SELECT COUNT(*) as count
-        FROM analyses
-        WHERE user_id = $1 AND status = 'completed'
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await?;
+    let count_sql = format!(
+        "SELECT COUNT(*) FROM analyses WHERE {}",
+        USER_HISTORY_CONDITIONS
+    );
+    let (total,): (i64,) = bind_history_filter(sqlx::query_as(&count_sql), user_id, filter)
+        .fetch_one(pool)
+        .await?;
 
-    let rows = sqlx::query_as::<_, AnalysisRow>(
+    let sql = format!(
         r#"
         SELECT id,
                image_url,
                ocr_text,
+               ocr_confidence,
                confirmed_text,
                ocr_status,
                llm_status,
@@ -306,19 +480,28 @@ pub async fn list_user_history(
                result,
                status,
                error_message,
+               gtin,
+               user_id,
+               product_id,
+               profile_id,
+               profile_name,
+               is_favorite,
+               tags,
+               note,
                created_at,
                updated_at
         FROM analyses
-        WHERE user_id = $1 AND status = 'completed'
+        WHERE {}
         ORDER BY created_at DESC
-        LIMIT $2 OFFSET $3
+        LIMIT $13 OFFSET $14
         "#,
-    )
-    .bind(user_id)
-    .bind(limit)
-    .bind(offset)
-    .fetch_all(pool)
-    .await?;
+        USER_HISTORY_CONDITIONS
+    );
+    let rows = bind_history_filter(sqlx::query_as::<_, AnalysisRow>(&sql), user_id, filter)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await?;
 
     Ok((total, rows))
 }
@@ -357,26 +540,112 @@ pub async fn delete_user_histories(
     Ok(result.rows_affected())
 }
 
+/// Returns the ids of the analyses that were migrated
 pub async fn migrate_user_histories(
     pool: &PgPool,
     user_id: Uuid,
     ids: &[Uuid],
-) -> sqlx::Result<u64> {
-    let result = sqlx::query(
+) -> sqlx::Result<Vec<Uuid>> {
+    sqlx::query_scalar::<_, Uuid>(
         r#"
         UPDATE analyses
         SET user_id = $1,
             updated_at = NOW()
         WHERE user_id IS NULL
           AND id = ANY($2)
+        RETURNING id
         "#,
     )
     .bind(user_id)
     .bind(ids)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AnnotationsRow {
+    pub id: Uuid,
+    pub is_favorite: bool,
+    pub tags: Vec<String>,
+    pub note: Option<String>,
+}
+
+/// `None` when the analysis does not belong to the user
+pub async fn set_history_favorite(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    is_favorite: bool,
+) -> sqlx::Result<Option<AnnotationsRow>> {
+    sqlx::query_as::<_, AnnotationsRow>(
+        r#"
+        UPDATE analyses
+        SET is_favorite = $3,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, is_favorite, tags, note
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(is_favorite)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Set all annotations at once, e.g. those kept locally before the analysis was migrated
+pub async fn set_history_annotations(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    is_favorite: bool,
+    tags: &[String],
+    note: Option<&str>,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE analyses
+        SET is_favorite = $3,
+            tags = $4,
+            note = $5,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(is_favorite)
+    .bind(tags)
+    .bind(note)
     .execute(pool)
     .await?;
+    Ok(())
+}
 
-    Ok(result.rows_affected())
+/// Replace the tags and note; `None` when the analysis does not belong to the user
+pub async fn set_history_notes(
+    pool: &PgPool,
+    user_id: Uuid,
+    id: Uuid,
+    tags: &[String],
+    note: Option<&str>,
+) -> sqlx::Result<Option<AnnotationsRow>> {
+    sqlx::query_as::<_, AnnotationsRow>(
+        r#"
+        UPDATE analyses
+        SET tags = $3,
+            note = $4,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, is_favorite, tags, note
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(tags)
+    .bind(note)
+    .fetch_optional(pool)
+    .await
 }
 
 pub async fn prune_user_history(
@@ -408,6 +677,245 @@ pub async fn prune_user_history(
     Ok(result.rows_affected())
 }
 
+#[derive(Debug, Clone, FromRow)]
+pub struct ProductRow {
+    pub id: Uuid,
+    pub gtin: String,
+    pub brand: Option<String>,
+    pub name: String,
+    pub category: Option<String>,
+    pub ingredient_text: Option<String>,
+    pub canonical_analysis_id: Option<Uuid>,
+    pub health_score: Option<i32>,
+    pub result: Option<Value>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub async fn get_product_by_gtin(pool: &PgPool, gtin: &str) -> sqlx::Result<Option<ProductRow>> {
+    let row = sqlx::query_as::<_, ProductRow>(
+        r#"
+        SELECT p.id,
+               p.gtin,
+               p.brand,
+               p.name,
+               p.category,
+               p.ingredient_text,
+               p.canonical_analysis_id,
+               a.health_score,
+               a.result,
+               p.created_at,
+               p.updated_at
+        FROM products p
+        LEFT JOIN analyses a ON a.id = p.canonical_analysis_id
+        WHERE p.gtin = $1
+        "#,
+    )
+    .bind(gtin)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+pub async fn search_products(
+    pool: &PgPool,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> sqlx::Result<(i64, Vec<ProductRow>)> {
+    let pattern = format!("%{}%", escape_like(query));
+    let total: i64 = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) as count
+        FROM products
+        WHERE name ILIKE $1 OR brand ILIKE $1
+        "#,
+    )
+    .bind(&pattern)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, ProductRow>(
+        r#"
+        SELECT p.id,
+               p.gtin,
+               p.brand,
+               p.name,
+               p.category,
+               p.ingredient_text,
+               p.canonical_analysis_id,
+               a.health_score,
+               NULL::jsonb AS result,
+               p.created_at,
+               p.updated_at
+        FROM products p
+        LEFT JOIN analyses a ON a.id = p.canonical_analysis_id
+        WHERE p.name ILIKE $1 OR p.brand ILIKE $1
+        ORDER BY p.updated_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(&pattern)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((total, rows))
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AlternativeProductRow {
+    pub id: Uuid,
+    pub brand: Option<String>,
+    pub name: String,
+    pub category: Option<String>,
+    pub ingredient_text: Option<String>,
+    pub health_score: i32,
+    pub result: Option<Value>,
+}
+
+/// Catalog products whose canonical analysis scores above `min_score`, best first
+pub async fn list_alternative_products(
+    pool: &PgPool,
+    min_score: i32,
+    exclude_analysis_id: Uuid,
+    limit: i64,
+) -> sqlx::Result<Vec<AlternativeProductRow>> {
+    sqlx::query_as::<_, AlternativeProductRow>(
+        r#"
+        SELECT p.id,
+               p.brand,
+               p.name,
+               p.category,
+               COALESCE(p.ingredient_text, a.confirmed_text, a.ocr_text) AS ingredient_text,
+               a.health_score,
+               a.result
+        FROM products p
+        JOIN analyses a ON a.id = p.canonical_analysis_id
+        WHERE a.status = 'completed'
+          AND a.health_score > $1
+          AND a.id <> $2
+        ORDER BY a.health_score DESC, p.updated_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(min_score)
+    .bind(exclude_analysis_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct AlternativePostRow {
+    pub id: Uuid,
+    pub summary_text: String,
+    pub health_score: i32,
+    pub ingredients_raw: String,
+    pub card_payload: Value,
+}
+
+/// Community posts scoring above `min_score`, best first, other than shares of the
+/// excluded analysis
+pub async fn list_alternative_posts(
+    pool: &PgPool,
+    min_score: i32,
+    exclude_analysis_id: Uuid,
+    limit: i64,
+) -> sqlx::Result<Vec<AlternativePostRow>> {
+    sqlx::query_as::<_, AlternativePostRow>(
+        r#"
+        SELECT id,
+               summary_text,
+               health_score,
+               ingredients_raw,
+               card_payload
+        FROM community_posts
+        WHERE health_score > $1
+          AND source_analysis_id IS DISTINCT FROM $2
+        ORDER BY health_score DESC, created_at DESC
+        LIMIT $3
+        "#,
+    )
+    .bind(min_score)
+    .bind(exclude_analysis_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Create the product or attach the analysis to it. Without `curator`, an existing product
+/// keeps its name and details, and the analysis only becomes canonical if there is none.
+pub async fn upsert_product_with_analysis(
+    pool: &PgPool,
+    gtin: &str,
+    brand: Option<&str>,
+    name: &str,
+    category: Option<&str>,
+    analysis_id: Uuid,
+    curator: bool,
+) -> sqlx::Result<Uuid> {
+    let mut tx = pool.begin().await?;
+
+    let product_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO products (gtin, brand, name, category, ingredient_text, canonical_analysis_id)
+        SELECT $1, $2, $3, $4, COALESCE(confirmed_text, ocr_text), id
+        FROM analyses
+        WHERE id = $5
+        ON CONFLICT (gtin) DO UPDATE SET
+            brand = CASE WHEN $6 THEN COALESCE(EXCLUDED.brand, products.brand)
+                         ELSE COALESCE(products.brand, EXCLUDED.brand) END,
+            name = CASE WHEN $6 THEN EXCLUDED.name ELSE products.name END,
+            category = CASE WHEN $6 THEN COALESCE(EXCLUDED.category, products.category)
+                            ELSE COALESCE(products.category, EXCLUDED.category) END,
+            ingredient_text = CASE WHEN $6 OR products.canonical_analysis_id IS NULL
+                                   THEN EXCLUDED.ingredient_text
+                                   ELSE products.ingredient_text END,
+            canonical_analysis_id = CASE WHEN $6 OR products.canonical_analysis_id IS NULL
+                                         THEN EXCLUDED.canonical_analysis_id
+                                         ELSE products.canonical_analysis_id END,
+            updated_at = NOW()
+        RETURNING id
+        "#,
+    )
+    .bind(gtin)
+    .bind(brand)
+    .bind(name)
+    .bind(category)
+    .bind(analysis_id)
+    .bind(curator)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE analyses
+        SET product_id = $2,
+            gtin = COALESCE(gtin, $3),
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+    )
+    .bind(analysis_id)
+    .bind(product_id)
+    .bind(gtin)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(product_id)
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
 #[derive(Debug, Clone, FromRow)]
 pub struct CommunityPostListRow {
     pub id: Uuid,
@@ -563,6 +1071,20 @@ pub async fn delete_community_post_by_user(
     Ok(result.rows_affected())
 }
 
+/// Delete a post regardless of author, for moderators
+pub async fn delete_community_post(pool: &PgPool, id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM community_posts
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn delete_community_post_by_token(
     pool: &PgPool,
     id: Uuid,
@@ -591,6 +1113,14 @@ pub struct UserRow {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login_at: Option<DateTime<Utc>>,
+    pub role: String,
+}
+
+impl UserRow {
+    /// Stored role; unknown values fall back to a regular user
+    pub fn user_role(&self) -> shared::UserRole {
+        shared::UserRole::parse(&self.role).unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -649,7 +1179,8 @@ pub async fn get_user_by_id(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Option
                password_hash,
                created_at,
                updated_at,
-               last_login_at
+               last_login_at,
+               role
         FROM users
         WHERE id = $1
         "#,
@@ -673,7 +1204,8 @@ pub async fn get_user_by_username(
                password_hash,
                created_at,
                updated_at,
-               last_login_at
+               last_login_at,
+               role
         FROM users
         WHERE username_normalized = $1
         "#,
@@ -701,7 +1233,8 @@ pub async fn create_user_with_password(
                   password_hash,
                   created_at,
                   updated_at,
-                  last_login_at
+                  last_login_at,
+                  role
         "#,
     )
     .bind(username)
@@ -713,13 +1246,82 @@ pub async fn create_user_with_password(
     Ok(row)
 }
 
-pub async fn update_user_last_login(pool: &PgPool, user_id: Uuid) -> sqlx::Result<()> {
-    sqlx::query(
+/// Users holding a role above `user`, oldest first
+pub async fn list_staff_users(pool: &PgPool) -> sqlx::Result<Vec<UserRow>> {
+    let rows = sqlx::query_as::<_, UserRow>(
         r#"
-        UPDATE users
-        SET last_login_at = NOW(),
-            updated_at = NOW()
-        WHERE id = $1
+        SELECT id,
+               username,
+               username_normalized,
+               password_hash,
+               created_at,
+               updated_at,
+               last_login_at,
+               role
+        FROM users
+        WHERE role <> 'user'
+        ORDER BY created_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn update_user_role(
+    pool: &PgPool,
+    user_id: Uuid,
+    role: &str,
+) -> sqlx::Result<Option<UserRow>> {
+    let row = sqlx::query_as::<_, UserRow>(
+        r#"
+        UPDATE users
+        SET role = $2,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING id,
+                  username,
+                  username_normalized,
+                  password_hash,
+                  created_at,
+                  updated_at,
+                  last_login_at,
+                  role
+        "#,
+    )
+    .bind(user_id)
+    .bind(role)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Give the listed users the admin role; returns how many rows changed
+pub async fn grant_admin_roles(pool: &PgPool, user_ids: &[Uuid]) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        UPDATE users
+        SET role = 'admin',
+            updated_at = NOW()
+        WHERE id = ANY($1) AND role <> 'admin'
+        "#,
+    )
+    .bind(user_ids)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn update_user_last_login(pool: &PgPool, user_id: Uuid) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        UPDATE users
+        SET last_login_at = NOW(),
+            updated_at = NOW()
+        WHERE id = $1
         "#,
     )
     .bind(user_id)
@@ -743,6 +1345,159 @@ pub async fn count_user_analyses(pool: &PgPool, user_id: Uuid) -> sqlx::Result<i
     Ok(total)
 }
 
+/// Totals over a user's completed analyses, with this month (UTC) so far and the
+/// previous month
+#[derive(Debug, Clone, FromRow)]
+pub struct InsightsOverviewRow {
+    pub current_month: String,
+    pub total_analyses: i64,
+    pub average_score: Option<f64>,
+    pub warning_analyses: i64,
+    pub current_analyses: i64,
+    pub current_average: Option<f64>,
+    pub previous_analyses: i64,
+    pub previous_average: Option<f64>,
+}
+
+/// Analyses with a `warning_type` warning naming at least one ingredient are counted in
+/// `warning_analyses`; results stored before the allergens-unavailable notice had its own
+/// type carry it as an allergen warning without ingredients.
+pub async fn get_insights_overview(
+    pool: &PgPool,
+    user_id: Uuid,
+    warning_type: &str,
+) -> sqlx::Result<InsightsOverviewRow> {
+    sqlx::query_as::<_, InsightsOverviewRow>(
+        r#"
+        SELECT to_char(date_trunc('month', now() AT TIME ZONE 'UTC'), 'YYYY-MM') AS current_month,
+               COUNT(*) AS total_analyses,
+               AVG(health_score)::float8 AS average_score,
+               COUNT(*) FILTER (
+                   WHERE jsonb_path_exists(
+                       result,
+                       '$.warnings[*] ? (@.warning_type == $type && @.ingredients.size() > 0)',
+                       jsonb_build_object('type', $2::text)
+                   )
+               ) AS warning_analyses,
+               COUNT(*) FILTER (WHERE created_at >= month_start) AS current_analyses,
+               (AVG(health_score) FILTER (WHERE created_at >= month_start))::float8
+                   AS current_average,
+               COUNT(*) FILTER (
+                   WHERE created_at >= previous_start AND created_at < month_start
+               ) AS previous_analyses,
+               (AVG(health_score) FILTER (
+                   WHERE created_at >= previous_start AND created_at < month_start
+               ))::float8 AS previous_average
+        FROM analyses
+        CROSS JOIN (
+            SELECT date_trunc('month', now() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+                       AS month_start,
+                   (date_trunc('month', now() AT TIME ZONE 'UTC') - interval '1 month')
+                       AT TIME ZONE 'UTC' AS previous_start
+        ) AS bounds
+        WHERE user_id = $1 AND status = 'completed'
+        "#,
+    )
+    .bind(user_id)
+    .bind(warning_type)
+    .fetch_one(pool)
+    .await
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct WeeklyInsightRow {
+    pub week_start: String,
+    pub analyses: i64,
+    pub average_score: Option<f64>,
+    /// Slope of the weekly averages over all returned weeks, repeated on every row
+    pub trend: Option<f64>,
+}
+
+/// The last `weeks` weeks (UTC, starting Monday) up to the current one, oldest first,
+/// including weeks without analyses
+pub async fn list_weekly_insights(
+    pool: &PgPool,
+    user_id: Uuid,
+    weeks: i32,
+) -> sqlx::Result<Vec<WeeklyInsightRow>> {
+    sqlx::query_as::<_, WeeklyInsightRow>(
+        r#"
+        WITH weeks AS (
+            SELECT generate_series(
+                       date_trunc('week', now() AT TIME ZONE 'UTC')
+                           - make_interval(weeks => $2 - 1),
+                       date_trunc('week', now() AT TIME ZONE 'UTC'),
+                       interval '1 week'
+                   )::date AS week_start
+        ),
+        weekly AS (
+            SELECT weeks.week_start,
+                   COUNT(analyses.id) AS analyses,
+                   AVG(analyses.health_score)::float8 AS average_score
+            FROM weeks
+            LEFT JOIN analyses
+                   ON analyses.user_id = $1
+                  AND analyses.status = 'completed'
+                  AND analyses.created_at >= weeks.week_start::timestamp AT TIME ZONE 'UTC'
+                  AND analyses.created_at < (weeks.week_start + 7)::timestamp AT TIME ZONE 'UTC'
+            GROUP BY weeks.week_start
+        )
+        SELECT to_char(week_start, 'YYYY-MM-DD') AS week_start,
+               analyses,
+               average_score,
+               regr_slope(average_score, ((week_start - DATE '2000-01-03') / 7)::float8)
+                   OVER () AS trend
+        FROM weekly
+        ORDER BY weekly.week_start
+        "#,
+    )
+    .bind(user_id)
+    .bind(weeks)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct IngredientCountRow {
+    pub name: String,
+    pub analyses: i64,
+}
+
+/// Ingredient names most often found in a user's completed analyses, each counted once
+/// per analysis. `risk_level` and `category` restrict the ingredients (lowercase keys).
+pub async fn list_frequent_ingredients(
+    pool: &PgPool,
+    user_id: Uuid,
+    risk_level: Option<&str>,
+    category: Option<&str>,
+    limit: i64,
+) -> sqlx::Result<Vec<IngredientCountRow>> {
+    sqlx::query_as::<_, IngredientCountRow>(
+        r#"
+        SELECT ingredient.name, COUNT(DISTINCT analyses.id) AS analyses
+        FROM analyses
+        CROSS JOIN LATERAL jsonb_array_elements(
+            CASE WHEN jsonb_typeof(result->'ingredients') = 'array'
+                 THEN result->'ingredients' ELSE '[]'::jsonb END
+        ) AS item
+        CROSS JOIN LATERAL (SELECT btrim(item->>'name') AS name) AS ingredient
+        WHERE user_id = $1 AND status = 'completed'
+          AND ingredient.name <> ''
+          AND ($2::text IS NULL OR lower(item->>'risk_level') = $2)
+          AND ($3::text IS NULL OR lower(item->>'category') = $3)
+        GROUP BY ingredient.name
+        ORDER BY analyses DESC, ingredient.name
+        LIMIT $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(risk_level)
+    .bind(category)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn ensure_user_preferences(pool: &PgPool, user_id: Uuid) -> sqlx::Result<()> {
     sqlx::query(
         r#"
@@ -769,3 +1524,579 @@ pub async fn delete_user(pool: &PgPool, user_id: Uuid) -> sqlx::Result<()> {
     .await?;
     Ok(())
 }
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RuleRecordRow {
+    pub id: String,
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub category: String,
+    pub risk_level: String,
+    pub groups: Vec<String>,
+    pub description: String,
+    pub evidence: Option<String>,
+    pub source: Option<String>,
+    pub e_numbers: Vec<String>,
+    pub ins_numbers: Vec<String>,
+    pub cns_codes: Vec<String>,
+    pub allergens: Vec<String>,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RuleAuditRow {
+    pub id: Uuid,
+    pub rule_id: String,
+    pub action: String,
+    pub actor_id: Option<Uuid>,
+    pub before_data: Option<Value>,
+    pub after_data: Option<Value>,
+    pub created_at: DateTime<Utc>,
+}
+
+const RULE_COLUMNS: &str = "id, name, aliases, category, risk_level, groups, description, \
+     evidence, source, e_numbers, ins_numbers, cns_codes, allergens, enabled, created_at, \
+     updated_at";
+
+pub async fn list_rules(pool: &PgPool) -> sqlx::Result<Vec<RuleRecordRow>> {
+    let rows = sqlx::query_as::<_, RuleRecordRow>(&format!(
+        "SELECT {} FROM rules ORDER BY id",
+        RULE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_rule(pool: &PgPool, id: &str) -> sqlx::Result<Option<RuleRecordRow>> {
+    let row = sqlx::query_as::<_, RuleRecordRow>(&format!(
+        "SELECT {} FROM rules WHERE id = $1",
+        RULE_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(row)
+}
+
+/// Insert a new rule and its audit record; returns `None` if the ID is taken.
+///
+/// The rule writes below take a pool or an open transaction, so a batch (such as a
+/// `rules_import` run) commits together. `actor_id` is `None` for CLI changes.
+pub async fn insert_rule<'c>(
+    conn: impl Acquire<'c, Database = Postgres>,
+    item: &RuleItem,
+    actor_id: Option<Uuid>,
+) -> sqlx::Result<Option<RuleRecordRow>> {
+    let mut tx = conn.begin().await?;
+
+    let row = sqlx::query_as::<_, RuleRecordRow>(&format!(
+        r#"
+        INSERT INTO rules (id, name, aliases, category, risk_level, groups, description,
+                           evidence, source, e_numbers, ins_numbers, cns_codes, allergens,
+                           enabled)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, TRUE)
+        ON CONFLICT (id) DO NOTHING
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    ))
+    .bind(&item.id)
+    .bind(&item.name)
+    .bind(&item.aliases)
+    .bind(&item.category)
+    .bind(&item.risk_level)
+    .bind(&item.groups)
+    .bind(&item.description)
+    .bind(&item.evidence)
+    .bind(&item.source)
+    .bind(&item.e_numbers)
+    .bind(&item.ins_numbers)
+    .bind(&item.cns_codes)
+    .bind(&item.allergens)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let after = rule_snapshot(&mut tx, &item.id).await?;
+    insert_rule_audit(&mut tx, &item.id, "create", actor_id, None, after).await?;
+
+    tx.commit().await?;
+    Ok(Some(row))
+}
+
+/// Replace a rule's editable fields and record `action` in the audit log
+pub async fn update_rule<'c>(
+    conn: impl Acquire<'c, Database = Postgres>,
+    item: &RuleItem,
+    action: &str,
+    actor_id: Option<Uuid>,
+) -> sqlx::Result<Option<RuleRecordRow>> {
+    let mut tx = conn.begin().await?;
+    let Some(before) = rule_snapshot(&mut tx, &item.id).await? else {
+        return Ok(None);
+    };
+
+    let row = sqlx::query_as::<_, RuleRecordRow>(&format!(
+        r#"
+        UPDATE rules
+        SET name = $2,
+            aliases = $3,
+            category = $4,
+            risk_level = $5,
+            groups = $6,
+            description = $7,
+            evidence = $8,
+            source = $9,
+            e_numbers = $10,
+            ins_numbers = $11,
+            cns_codes = $12,
+            allergens = $13,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    ))
+    .bind(&item.id)
+    .bind(&item.name)
+    .bind(&item.aliases)
+    .bind(&item.category)
+    .bind(&item.risk_level)
+    .bind(&item.groups)
+    .bind(&item.description)
+    .bind(&item.evidence)
+    .bind(&item.source)
+    .bind(&item.e_numbers)
+    .bind(&item.ins_numbers)
+    .bind(&item.cns_codes)
+    .bind(&item.allergens)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let after = rule_snapshot(&mut tx, &item.id).await?;
+    insert_rule_audit(&mut tx, &item.id, action, actor_id, Some(before), after).await?;
+
+    tx.commit().await?;
+    Ok(Some(row))
+}
+
+pub async fn set_rule_enabled<'c>(
+    conn: impl Acquire<'c, Database = Postgres>,
+    id: &str,
+    enabled: bool,
+    actor_id: Option<Uuid>,
+) -> sqlx::Result<Option<RuleRecordRow>> {
+    let mut tx = conn.begin().await?;
+    let Some(before) = rule_snapshot(&mut tx, id).await? else {
+        return Ok(None);
+    };
+
+    let row = sqlx::query_as::<_, RuleRecordRow>(&format!(
+        "UPDATE rules SET enabled = $2, updated_at = NOW() WHERE id = $1 RETURNING {}",
+        RULE_COLUMNS
+    ))
+    .bind(id)
+    .bind(enabled)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let action = if enabled { "enable" } else { "disable" };
+    let after = rule_snapshot(&mut tx, id).await?;
+    insert_rule_audit(&mut tx, id, action, actor_id, Some(before), after).await?;
+
+    tx.commit().await?;
+    Ok(Some(row))
+}
+
+/// Delete a rule; returns `false` if it did not exist
+pub async fn delete_rule(pool: &PgPool, id: &str, actor_id: Uuid) -> sqlx::Result<bool> {
+    let mut tx = pool.begin().await?;
+    let Some(before) = rule_snapshot(&mut tx, id).await? else {
+        return Ok(false);
+    };
+
+    sqlx::query("DELETE FROM rules WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    insert_rule_audit(&mut tx, id, "delete", Some(actor_id), Some(before), None).await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+pub async fn list_rule_audit(
+    pool: &PgPool,
+    rule_id: &str,
+    limit: i64,
+    offset: i64,
+) -> sqlx::Result<(i64, Vec<RuleAuditRow>)> {
+    let total = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) as count
+        FROM rule_audit_logs
+        WHERE rule_id = $1
+        "#,
+    )
+    .bind(rule_id)
+    .fetch_one(pool)
+    .await?;
+
+    let rows = sqlx::query_as::<_, RuleAuditRow>(
+        r#"
+        SELECT id,
+               rule_id,
+               action,
+               actor_id,
+               before_data,
+               after_data,
+               created_at
+        FROM rule_audit_logs
+        WHERE rule_id = $1
+        ORDER BY created_at DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(rule_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((total, rows))
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RuleSetVersionRow {
+    pub version: i64,
+    pub content_hash: String,
+    pub rule_count: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+pub async fn list_rule_set_versions(
+    pool: &PgPool,
+    limit: i64,
+    offset: i64,
+) -> sqlx::Result<(i64, Vec<RuleSetVersionRow>)> {
+    let total = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) as count FROM rule_set_versions")
+        .fetch_one(pool)
+        .await?;
+
+    let rows = sqlx::query_as::<_, RuleSetVersionRow>(
+        r#"
+        SELECT version,
+               content_hash,
+               rule_count,
+               created_at
+        FROM rule_set_versions
+        ORDER BY version DESC
+        LIMIT $1 OFFSET $2
+        "#,
+    )
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((total, rows))
+}
+
+pub async fn latest_rule_set_version(pool: &PgPool) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM rule_set_versions")
+        .fetch_one(pool)
+        .await
+}
+
+/// Rules stored with a rule set version
+pub async fn get_rule_set_snapshot(
+    pool: &PgPool,
+    version: i64,
+) -> sqlx::Result<Option<Vec<RuleItem>>> {
+    let rules = sqlx::query_scalar::<_, sqlx::types::Json<Vec<RuleItem>>>(
+        "SELECT rules FROM rule_set_versions WHERE version = $1",
+    )
+    .bind(version)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(rules.map(|rules| rules.0))
+}
+
+/// Current rule row as JSON, locked for the rest of the transaction
+async fn rule_snapshot(conn: &mut PgConnection, id: &str) -> sqlx::Result<Option<Value>> {
+    sqlx::query_scalar::<_, Value>("SELECT to_jsonb(r) FROM rules r WHERE r.id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_optional(conn)
+        .await
+}
+
+async fn insert_rule_audit(
+    conn: &mut PgConnection,
+    rule_id: &str,
+    action: &str,
+    actor_id: Option<Uuid>,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> sqlx::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO rule_audit_logs (rule_id, action, actor_id, before_data, after_data)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(rule_id)
+    .bind(action)
+    .bind(actor_id)
+    .bind(before)
+    .bind(after)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RuleOverlayRow {
+    pub id: Uuid,
+    pub ingredient: String,
+    pub rule_id: Option<String>,
+    pub risk_level: String,
+    pub note: Option<String>,
+    pub trusted: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RuleOverlayRow {
+    pub fn into_overlay(self) -> shared::RuleOverlay {
+        shared::RuleOverlay {
+            id: self.id,
+            overlay: shared::RuleOverlayInput {
+                ingredient: self.ingredient,
+                rule_id: self.rule_id,
+                risk_level: self.risk_level,
+                note: self.note,
+                trusted: self.trusted,
+            },
+            created_at: self.created_at.to_rfc3339(),
+            updated_at: self.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+pub async fn list_rule_overlays(pool: &PgPool, user_id: Uuid) -> sqlx::Result<Vec<RuleOverlayRow>> {
+    let rows = sqlx::query_as::<_, RuleOverlayRow>(
+        r#"
+        SELECT id, ingredient, rule_id, risk_level, note, trusted, created_at, updated_at
+        FROM user_rule_overlays
+        WHERE user_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn insert_rule_overlay(
+    pool: &PgPool,
+    user_id: Uuid,
+    overlay: &shared::RuleOverlayInput,
+) -> sqlx::Result<RuleOverlayRow> {
+    sqlx::query_as::<_, RuleOverlayRow>(
+        r#"
+        INSERT INTO user_rule_overlays (user_id, ingredient, rule_id, risk_level, note, trusted)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, ingredient, rule_id, risk_level, note, trusted, created_at, updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(&overlay.ingredient)
+    .bind(&overlay.rule_id)
+    .bind(&overlay.risk_level)
+    .bind(&overlay.note)
+    .bind(overlay.trusted)
+    .fetch_one(pool)
+    .await
+}
+
+/// Update one of the user's overlays; `None` if it does not exist or belongs to someone else
+pub async fn update_rule_overlay(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+    overlay: &shared::RuleOverlayInput,
+) -> sqlx::Result<Option<RuleOverlayRow>> {
+    sqlx::query_as::<_, RuleOverlayRow>(
+        r#"
+        UPDATE user_rule_overlays
+        SET ingredient = $3,
+            rule_id = $4,
+            risk_level = $5,
+            note = $6,
+            trusted = $7,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, ingredient, rule_id, risk_level, note, trusted, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(&overlay.ingredient)
+    .bind(&overlay.rule_id)
+    .bind(&overlay.risk_level)
+    .bind(&overlay.note)
+    .bind(overlay.trusted)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_rule_overlay(pool: &PgPool, id: Uuid, user_id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM user_rule_overlays
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct FamilyProfileRow {
+    pub id: Uuid,
+    pub name: String,
+    pub preference: Option<String>,
+    pub allergens: Vec<String>,
+    pub avoid_ingredients: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FamilyProfileRow {
+    pub fn into_profile(self) -> shared::FamilyProfile {
+        shared::FamilyProfile {
+            id: self.id,
+            profile: shared::FamilyProfileInput {
+                name: self.name,
+                preference: self.preference,
+                allergens: self.allergens,
+                avoid_ingredients: self.avoid_ingredients,
+            },
+            created_at: self.created_at.to_rfc3339(),
+            updated_at: self.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+pub async fn list_family_profiles(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> sqlx::Result<Vec<FamilyProfileRow>> {
+    sqlx::query_as::<_, FamilyProfileRow>(
+        r#"
+        SELECT id, name, preference, allergens, avoid_ingredients, created_at, updated_at
+        FROM family_profiles
+        WHERE user_id = $1
+        ORDER BY created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// One of the user's profiles; `None` if it does not exist or belongs to someone else
+pub async fn get_family_profile(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+) -> sqlx::Result<Option<FamilyProfileRow>> {
+    sqlx::query_as::<_, FamilyProfileRow>(
+        r#"
+        SELECT id, name, preference, allergens, avoid_ingredients, created_at, updated_at
+        FROM family_profiles
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_family_profile(
+    pool: &PgPool,
+    user_id: Uuid,
+    profile: &shared::FamilyProfileInput,
+) -> sqlx::Result<FamilyProfileRow> {
+    sqlx::query_as::<_, FamilyProfileRow>(
+        r#"
+        INSERT INTO family_profiles (user_id, name, preference, allergens, avoid_ingredients)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id, name, preference, allergens, avoid_ingredients, created_at, updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(&profile.name)
+    .bind(&profile.preference)
+    .bind(&profile.allergens)
+    .bind(&profile.avoid_ingredients)
+    .fetch_one(pool)
+    .await
+}
+
+/// Update one of the user's profiles; `None` if it does not exist or belongs to someone else
+pub async fn update_family_profile(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+    profile: &shared::FamilyProfileInput,
+) -> sqlx::Result<Option<FamilyProfileRow>> {
+    sqlx::query_as::<_, FamilyProfileRow>(
+        r#"
+        UPDATE family_profiles
+        SET name = $3,
+            preference = $4,
+            allergens = $5,
+            avoid_ingredients = $6,
+            updated_at = NOW()
+        WHERE id = $1 AND user_id = $2
+        RETURNING id, name, preference, allergens, avoid_ingredients, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(&profile.name)
+    .bind(&profile.preference)
+    .bind(&profile.allergens)
+    .bind(&profile.avoid_ingredients)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_family_profile(pool: &PgPool, id: Uuid, user_id: Uuid) -> sqlx::Result<u64> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM family_profiles
+        WHERE id = $1 AND user_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
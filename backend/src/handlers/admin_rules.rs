@@ -0,0 +1,394 @@
+//! Rule library administration handlers
+//!
+//! All routes require the `manage_rules` permission. Every change is written to
+//! `rule_audit_logs`; a trigger on the rules table notifies all instances to reload their
+//! rule engine, and each reload of new content is snapshotted as a rule set version.
+
+use axum::{
+    extract::{Path, Query, State},
+    Json, Router,
+};
+use serde::Deserialize;
+use shared::{
+    CreateRuleRequest, RuleAliasRequest, RuleAuditEntry, RuleAuditResponse, RuleDefinition,
+    RuleInput, RuleListResponse, RuleSetDiff, RuleSetVersion, RuleSetVersionListResponse,
+};
+
+use crate::{
+    db,
+    errors::AppError,
+    middleware::{permissions::ManageRules, RequirePermission},
+    services::{
+        rule_validation, rule_versions,
+        rules::{normalize_token, RuleItem},
+    },
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    page: Option<i64>,
+    limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffQuery {
+    from: i64,
+    to: Option<i64>,
+}
+
+/// Create rule administration routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(list_rules).post(create_rule))
+        .route(
+            "/:id",
+            axum::routing::get(get_rule)
+                .put(update_rule)
+                .delete(delete_rule),
+        )
+        .route("/:id/enable", axum::routing::post(enable_rule))
+        .route("/:id/disable", axum::routing::post(disable_rule))
+        .route("/:id/aliases", axum::routing::post(add_alias))
+        .route("/:id/aliases/:alias", axum::routing::delete(remove_alias))
+        .route("/:id/audit", axum::routing::get(list_audit))
+}
+
+/// Create rule set version routes
+pub fn version_routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(list_versions))
+        .route("/diff", axum::routing::get(diff_versions))
+}
+
+async fn list_rules(
+    State(state): State<AppState>,
+    _admin: RequirePermission<ManageRules>,
+) -> Result<Json<RuleListResponse>, AppError> {
+    let rows = db::list_rules(&state.pool).await?;
+    Ok(Json(RuleListResponse {
+        total: rows.len() as i64,
+        items: rows.into_iter().map(to_definition).collect(),
+    }))
+}
+
+async fn get_rule(
+    State(state): State<AppState>,
+    _admin: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let row = load_rule(&state, &id).await?;
+    Ok(Json(to_definition(row)))
+}
+
+async fn create_rule(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Json(payload): Json<CreateRuleRequest>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let item = to_rule_item(payload.id.trim().to_string(), payload.rule);
+    validate(&state, &item, None).await?;
+
+    let row = db::insert_rule(&state.pool, &item, Some(user_id))
+        .await?
+        .ok_or_else(|| AppError::BadRequest("规则 ID 已存在".to_string()))?;
+    Ok(Json(to_definition(row)))
+}
+
+async fn update_rule(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+    Json(payload): Json<RuleInput>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let current = load_rule(&state, &id).await?;
+    let item = to_rule_item(id, payload);
+    validate(&state, &item, Some(&row_to_item(current))).await?;
+
+    save_update(&state, &item, "update", user_id).await
+}
+
+async fn delete_rule(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if !db::delete_rule(&state.pool, &id, user_id).await? {
+        return Err(AppError::NotFound("规则不存在".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+async fn enable_rule(
+    state: State<AppState>,
+    admin: RequirePermission<ManageRules>,
+    id: Path<String>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    set_enabled(state, admin, id, true).await
+}
+
+async fn disable_rule(
+    state: State<AppState>,
+    admin: RequirePermission<ManageRules>,
+    id: Path<String>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    set_enabled(state, admin, id, false).await
+}
+
+async fn set_enabled(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+    enabled: bool,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let current = load_rule(&state, &id).await?;
+    if enabled {
+        // Re-enabling must not bring back names another rule has taken over meanwhile.
+        let item = row_to_item(current);
+        validate(&state, &item, None).await?;
+    }
+
+    let row = db::set_rule_enabled(&state.pool, &id, enabled, Some(user_id))
+        .await?
+        .ok_or_else(|| AppError::NotFound("规则不存在".to_string()))?;
+    Ok(Json(to_definition(row)))
+}
+
+async fn add_alias(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+    Json(payload): Json<RuleAliasRequest>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let current = row_to_item(load_rule(&state, &id).await?);
+    let mut item = current.clone();
+    item.aliases.push(payload.alias.trim().to_string());
+    validate(&state, &item, Some(&current)).await?;
+
+    save_update(&state, &item, "add_alias", user_id).await
+}
+
+async fn remove_alias(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageRules>,
+    Path((id, alias)): Path<(String, String)>,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let mut item = row_to_item(load_rule(&state, &id).await?);
+    let target = normalize_token(&alias);
+    let before = item.aliases.len();
+    item.aliases
+        .retain(|value| normalize_token(value) != target);
+    if item.aliases.len() == before {
+        return Err(AppError::NotFound("别名不存在".to_string()));
+    }
+
+    save_update(&state, &item, "remove_alias", user_id).await
+}
+
+async fn list_audit(
+    State(state): State<AppState>,
+    _admin: RequirePermission<ManageRules>,
+    Path(id): Path<String>,
+    Query(params): Query<PageQuery>,
+) -> Result<Json<RuleAuditResponse>, AppError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+
+    let (total, rows) = db::list_rule_audit(&state.pool, &id, limit, offset).await?;
+    let items = rows
+        .into_iter()
+        .map(|row| RuleAuditEntry {
+            id: row.id,
+            rule_id: row.rule_id,
+            action: row.action,
+            actor_id: row.actor_id,
+            before: row.before_data,
+            after: row.after_data,
+            created_at: row.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(Json(RuleAuditResponse {
+        total,
+        page,
+        limit,
+        items,
+    }))
+}
+
+async fn list_versions(
+    State(state): State<AppState>,
+    _admin: RequirePermission<ManageRules>,
+    Query(params): Query<PageQuery>,
+) -> Result<Json<RuleSetVersionListResponse>, AppError> {
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+
+    let (total, rows) = db::list_rule_set_versions(&state.pool, limit, offset).await?;
+    let items = rows
+        .into_iter()
+        .map(|row| RuleSetVersion {
+            version: row.version,
+            content_hash: row.content_hash,
+            rule_count: row.rule_count,
+            created_at: row.created_at.to_rfc3339(),
+        })
+        .collect();
+
+    Ok(Json(RuleSetVersionListResponse {
+        total,
+        page,
+        limit,
+        items,
+    }))
+}
+
+/// Compare two rule set versions; `to` defaults to the latest version
+async fn diff_versions(
+    State(state): State<AppState>,
+    _admin: RequirePermission<ManageRules>,
+    Query(params): Query<DiffQuery>,
+) -> Result<Json<RuleSetDiff>, AppError> {
+    let to_version = match params.to {
+        Some(version) => version,
+        None => db::latest_rule_set_version(&state.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("规则版本不存在".to_string()))?,
+    };
+    let from = load_snapshot(&state, params.from).await?;
+    let to = load_snapshot(&state, to_version).await?;
+
+    Ok(Json(rule_versions::diff_rule_sets(
+        params.from,
+        &from,
+        to_version,
+        &to,
+    )))
+}
+
+async fn load_snapshot(state: &AppState, version: i64) -> Result<Vec<RuleItem>, AppError> {
+    db::get_rule_set_snapshot(&state.pool, version)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("规则版本 {} 不存在", version)))
+}
+
+async fn load_rule(state: &AppState, id: &str) -> Result<db::RuleRecordRow, AppError> {
+    db::get_rule(&state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("规则不存在".to_string()))
+}
+
+async fn save_update(
+    state: &AppState,
+    item: &RuleItem,
+    action: &str,
+    actor_id: uuid::Uuid,
+) -> Result<Json<RuleDefinition>, AppError> {
+    let row = db::update_rule(&state.pool, item, action, Some(actor_id))
+        .await?
+        .ok_or_else(|| AppError::NotFound("规则不存在".to_string()))?;
+    Ok(Json(to_definition(row)))
+}
+
+/// Reject invalid fields, and names or aliases that would newly collide with another
+/// enabled rule. Collisions the rule already had are tolerated so existing data stays
+/// editable.
+async fn validate(
+    state: &AppState,
+    item: &RuleItem,
+    previous: Option<&RuleItem>,
+) -> Result<(), AppError> {
+    let problems = rule_validation::validate_rule(item);
+    if !problems.is_empty() {
+        return Err(AppError::BadRequest(problems.join("；")));
+    }
+
+    let others: Vec<RuleItem> = db::list_rules(&state.pool)
+        .await?
+        .into_iter()
+        .filter(|row| row.enabled)
+        .map(row_to_item)
+        .collect();
+    let existing_keys = previous.map(rule_validation::rule_keys).unwrap_or_default();
+    let conflicts: Vec<String> = rule_validation::find_key_conflicts(item, &others)
+        .into_iter()
+        .filter(|conflict| !existing_keys.contains(&conflict.key))
+        .map(|conflict| format!("{} 已被规则 {} 使用", conflict.key, conflict.other_id))
+        .collect();
+    if !conflicts.is_empty() {
+        return Err(AppError::BadRequest(conflicts.join("；")));
+    }
+    Ok(())
+}
+
+fn to_rule_item(id: String, input: RuleInput) -> RuleItem {
+    RuleItem {
+        id,
+        name: input.name.trim().to_string(),
+        aliases: trimmed(input.aliases),
+        category: input.category.trim().to_lowercase(),
+        risk_level: input.risk_level.trim().to_lowercase(),
+        groups: trimmed(input.groups),
+        description: input.description.trim().to_string(),
+        evidence: input.evidence.filter(|value| !value.trim().is_empty()),
+        source: input.source.filter(|value| !value.trim().is_empty()),
+        e_numbers: trimmed(input.e_numbers),
+        ins_numbers: trimmed(input.ins_numbers),
+        cns_codes: trimmed(input.cns_codes),
+        allergens: input
+            .allergens
+            .into_iter()
+            .map(|value| value.trim().to_lowercase())
+            .collect(),
+    }
+}
+
+fn trimmed(values: Vec<String>) -> Vec<String> {
+    values
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .collect()
+}
+
+fn row_to_item(row: db::RuleRecordRow) -> RuleItem {
+    RuleItem {
+        id: row.id,
+        name: row.name,
+        aliases: row.aliases,
+        category: row.category,
+        risk_level: row.risk_level,
+        groups: row.groups,
+        description: row.description,
+        evidence: row.evidence,
+        source: row.source,
+        e_numbers: row.e_numbers,
+        ins_numbers: row.ins_numbers,
+        cns_codes: row.cns_codes,
+        allergens: row.allergens,
+    }
+}
+
+fn to_definition(row: db::RuleRecordRow) -> RuleDefinition {
+    RuleDefinition {
+        id: row.id,
+        rule: RuleInput {
+            name: row.name,
+            aliases: row.aliases,
+            category: row.category,
+            risk_level: row.risk_level,
+            groups: row.groups,
+            description: row.description,
+            evidence: row.evidence,
+            source: row.source,
+            e_numbers: row.e_numbers,
+            ins_numbers: row.ins_numbers,
+            cns_codes: row.cns_codes,
+            allergens: row.allergens,
+        },
+        enabled: row.enabled,
+        created_at: row.created_at.to_rfc3339(),
+        updated_at: row.updated_at.to_rfc3339(),
+    }
+}
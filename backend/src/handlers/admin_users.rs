@@ -0,0 +1,73 @@
+//! User role administration handlers
+
+use axum::{
+    extract::{Path, State},
+    Json, Router,
+};
+use shared::{UpdateUserRoleRequest, UserProfile, UserRole};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    errors::AppError,
+    handlers::users::resolve_login_id,
+    middleware::{permissions::ManageUsers, roles::Admin, RequirePermission, RequireRole},
+    state::AppState,
+};
+
+/// Create user administration routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(list_staff))
+        .route("/:id/role", axum::routing::put(update_role))
+}
+
+/// List moderators and admins
+async fn list_staff(
+    State(state): State<AppState>,
+    _admin: RequireRole<Admin>,
+) -> Result<Json<Vec<UserProfile>>, AppError> {
+    let rows = db::list_staff_users(&state.pool).await?;
+    let mut items = Vec::with_capacity(rows.len());
+    for row in rows {
+        items.push(to_profile(&state, row).await?);
+    }
+    Ok(Json(items))
+}
+
+/// Change a user's role; permission checks use it immediately, token claims and the
+/// profile shown to the user update on the next login or token refresh
+async fn update_role(
+    State(state): State<AppState>,
+    RequirePermission { user_id, .. }: RequirePermission<ManageUsers>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateUserRoleRequest>,
+) -> Result<Json<UserProfile>, AppError> {
+    if id == user_id && payload.role != UserRole::Admin {
+        return Err(AppError::BadRequest("不能降低自己的角色".to_string()));
+    }
+
+    let row = db::update_user_role(&state.pool, id, payload.role.as_str())
+        .await?
+        .ok_or_else(|| AppError::NotFound("user not found".to_string()))?;
+    tracing::info!(
+        actor_id = %user_id,
+        target_id = %id,
+        role = payload.role.as_str(),
+        "user role changed"
+    );
+    Ok(Json(to_profile(&state, row).await?))
+}
+
+async fn to_profile(state: &AppState, user: db::UserRow) -> Result<UserProfile, AppError> {
+    let analysis_count = db::count_user_analyses(&state.pool, user.id).await?;
+    let role = user.user_role();
+    Ok(UserProfile {
+        id: user.id,
+        login_id: resolve_login_id(&user),
+        created_at: user.created_at.to_rfc3339(),
+        analysis_count,
+        role,
+        permissions: role.permissions().to_vec(),
+    })
+}
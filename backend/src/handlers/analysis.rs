@@ -6,16 +6,26 @@ use axum::{
 };
 use serde::Deserialize;
 use shared::{
-    AnalysisResponse, AnalysisResult, AnalysisStatus, ConfirmRequest, HistoryItem, HistoryResponse,
-    LlmStatus, OcrStatus, TableRow, UploadResponse,
+    Allergen, AlternativeSource, AlternativesResponse, AnalysisResponse, AnalysisResult,
+    AnalysisStatus, CommunityCardPayload, ConfirmRequest, DietRegime, HistoryItem, HistoryResponse,
+    LlmStatus, OcrStatus, RuleOverlay, ScoreExplanationResponse, TableRow, UploadResponse,
 };
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
     db,
     errors::AppError,
     middleware::OptionalAuthUser,
-    services::{llm::PreferenceType, ocr, storage},
+    services::{
+        allergens,
+        alternatives::{self, Candidate, Subject},
+        barcode,
+        confidence::{self, ConfidenceSignals},
+        diet,
+        llm::{PreferenceBlend, PreferenceType},
+        ocr, reconcile, rule_overlays, score_attribution, scoring, storage,
+    },
     state::AppState,
 };
 
@@ -24,6 +34,11 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/upload", axum::routing::post(upload_handler))
         .route("/:id", axum::routing::get(get_handler))
+        .route(
+            "/:id/alternatives",
+            axum::routing::get(alternatives_handler),
+        )
+        .route("/:id/explanation", axum::routing::get(explanation_handler))
         .route("/:id/confirm", axum::routing::post(confirm_handler))
         .route("/:id/retry-ocr", axum::routing::post(retry_ocr_handler))
         .route("/:id/retry-llm", axum::routing::post(retry_llm_handler))
@@ -109,7 +124,167 @@ async fn get_handler(
         .await?
         .ok_or_else(|| AppError::NotFound("analysis not found".to_string()))?;
 
-    Ok(Json(to_analysis_response(&row)))
+    let mut response = to_analysis_response(&row);
+    if let Some(gtin) = &row.gtin {
+        response.canonical_result = db::get_product_by_gtin(&state.pool, gtin)
+            .await?
+            .filter(|product| product.canonical_analysis_id != Some(row.id))
+            .and_then(|product| product.result)
+            .and_then(|value| serde_json::from_value::<AnalysisResult>(value).ok());
+    }
+    Ok(Json(response))
+}
+
+/// Explain a completed analysis' health score.
+///
+/// Results stored before attributions existed are attributed on the fly; the preference
+/// weights they were scored with are not kept, so their dimensions collapse into one entry.
+async fn explanation_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ScoreExplanationResponse>, AppError> {
+    let row = db::get_analysis(&state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("analysis not found".to_string()))?;
+    let result = row
+        .result
+        .and_then(|value| serde_json::from_value::<AnalysisResult>(value).ok())
+        .ok_or_else(|| AppError::BadRequest("分析尚未完成".to_string()))?;
+
+    let (attribution, reconstructed) = match result.score_attribution.clone() {
+        Some(attribution) => (attribution, false),
+        None => (score_attribution::attribute(&result, &[]), true),
+    };
+    Ok(Json(ScoreExplanationResponse {
+        id,
+        health_score: result.health_score,
+        score_breakdown: result.score_breakdown.unwrap_or_default(),
+        attribution,
+        reconstructed,
+    }))
+}
+
+/// Higher-scoring products in the same inferred category without the ingredients the
+/// analysis flagged or the viewer's allergens
+async fn alternatives_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    OptionalAuthUser { user_id: auth_user }: OptionalAuthUser,
+    Query(query): Query<AlternativesQuery>,
+) -> Result<Json<AlternativesResponse>, AppError> {
+    let row = db::get_analysis(&state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("analysis not found".to_string()))?;
+    let result = row
+        .result
+        .and_then(|value| serde_json::from_value::<AnalysisResult>(value).ok())
+        .ok_or_else(|| AppError::BadRequest("分析尚未完成".to_string()))?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_ALTERNATIVES)
+        .clamp(1, MAX_ALTERNATIVES) as usize;
+
+    let product_category = match &row.gtin {
+        Some(gtin) => db::get_product_by_gtin(&state.pool, gtin)
+            .await?
+            .and_then(|product| product.category),
+        None => None,
+    };
+    let text = row.confirmed_text.or(row.ocr_text).unwrap_or_default();
+    let Some(category) = alternatives::infer_category(product_category.as_deref(), &text) else {
+        return Ok(Json(AlternativesResponse {
+            analysis_id: id,
+            category: None,
+            items: Vec::new(),
+        }));
+    };
+
+    let mut flagged = alternatives::flagged_ingredients(&result);
+    let mut allergens = Vec::new();
+    match (auth_user, row.profile_id) {
+        (Some(user_id), Some(profile_id)) => {
+            if let Some(profile) = db::get_family_profile(&state.pool, profile_id, user_id).await? {
+                allergens::merge_keys(&mut allergens, &profile.allergens);
+                for ingredient in profile.avoid_ingredients {
+                    if !flagged.contains(&ingredient) {
+                        flagged.push(ingredient);
+                    }
+                }
+            }
+        }
+        (Some(user_id), None) => {
+            if let Some(preferences) = db::get_user_preferences(&state.pool, user_id).await? {
+                allergens = Allergen::from_preferences(&preferences.preferences);
+            }
+        }
+        (None, _) => {}
+    }
+
+    let products =
+        db::list_alternative_products(&state.pool, result.health_score, id, ALTERNATIVE_CANDIDATES)
+            .await?;
+    let posts =
+        db::list_alternative_posts(&state.pool, result.health_score, id, ALTERNATIVE_CANDIDATES)
+            .await?;
+    let candidates = products
+        .into_iter()
+        .map(|product| Candidate {
+            source: AlternativeSource::Product,
+            id: product.id,
+            name: product.name,
+            brand: product.brand,
+            product_category: product.category,
+            ingredient_text: product.ingredient_text.unwrap_or_default(),
+            health_score: product.health_score,
+            high_risk_count: product
+                .result
+                .and_then(|value| serde_json::from_value::<AnalysisResult>(value).ok())
+                .map(|result| {
+                    alternatives::high_risk_count(
+                        result
+                            .ingredients
+                            .iter()
+                            .map(|item| item.risk_level.as_str()),
+                    )
+                }),
+        })
+        .chain(posts.into_iter().map(|post| {
+            Candidate {
+                source: AlternativeSource::Community,
+                id: post.id,
+                name: post.summary_text.chars().take(30).collect(),
+                brand: None,
+                product_category: None,
+                ingredient_text: post.ingredients_raw,
+                health_score: post.health_score,
+                high_risk_count: serde_json::from_value::<CommunityCardPayload>(post.card_payload)
+                    .ok()
+                    .map(|card| {
+                        alternatives::high_risk_count(
+                            card.ingredients.iter().map(|item| item.risk_level.as_str()),
+                        )
+                    }),
+            }
+        }))
+        .collect();
+
+    let subject = Subject {
+        health_score: result.health_score,
+        category,
+        flagged: &flagged,
+        high_risk_count: alternatives::high_risk_count(
+            result
+                .ingredients
+                .iter()
+                .map(|item| item.risk_level.as_str()),
+        ),
+        allergens: &allergens,
+    };
+    Ok(Json(AlternativesResponse {
+        analysis_id: id,
+        category: Some(category.label.to_string()),
+        items: alternatives::rank(&subject, candidates, limit),
+    }))
 }
 
 /// Confirm OCR text and start LLM analysis
@@ -127,6 +302,18 @@ async fn confirm_handler(
         db::attach_user_to_analysis(&state.pool, id, user_id).await?;
     }
 
+    let profile = match payload.profile_id {
+        Some(profile_id) => {
+            let user_id =
+                auth_user.ok_or_else(|| AppError::Unauthorized("请先登录".to_string()))?;
+            let profile = db::get_family_profile(&state.pool, profile_id, user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("家庭成员不存在".to_string()))?;
+            Some((user_id, profile))
+        }
+        None => None,
+    };
+
     if row.ocr_status != "completed" {
         return Err(AppError::BadRequest("OCR not completed yet".to_string()));
     }
@@ -137,16 +324,43 @@ async fn confirm_handler(
             "confirmed text length must be 1-5000".to_string(),
         ));
     }
+    let invalid_weight = payload.preferences.iter().any(|item| {
+        item.weight
+            .is_some_and(|weight| !weight.is_finite() || weight <= 0.0)
+    });
+    if payload.preferences.len() > MAX_PREFERENCES || invalid_weight {
+        return Err(AppError::BadRequest(format!(
+            "偏好最多 {} 项，权重需为正数",
+            MAX_PREFERENCES
+        )));
+    }
 
     db::update_confirmed_text(&state.pool, id, &confirmed_text, "llm_pending").await?;
+    db::set_analysis_profile(
+        &state.pool,
+        id,
+        profile.as_ref().map(|(_, profile)| profile),
+    )
+    .await?;
 
-    let preference = PreferenceType::from_str(payload.preference.as_deref());
+    let preference =
+        PreferenceBlend::from_request(payload.preference.as_deref(), &payload.preferences);
+    let mut options = match &profile {
+        Some((user_id, profile)) => {
+            AnalysisOptions::for_profile(&state.pool, *user_id, profile, preference).await
+        }
+        None => AnalysisOptions::for_user(&state.pool, auth_user.or(row.user_id), preference).await,
+    };
+    db::set_analysis_preference(&state.pool, id, options.preference.primary().as_key()).await?;
+    allergens::merge_keys(&mut options.allergens, &payload.allergens);
+    diet::merge_keys(&mut options.diets, &payload.diets);
 
     let pool = state.pool.clone();
     let llm = state.llm.clone();
     let rules = state.rules.clone();
+    let ocr = OcrReading::from_row(&row);
     tokio::spawn(async move {
-        run_llm_task(pool, llm, rules, id, confirmed_text, preference).await;
+        run_llm_task(pool, llm, rules, id, confirmed_text, ocr, options).await;
     });
 
     let updated = db::get_analysis(&state.pool, id)
@@ -197,8 +411,22 @@ async fn retry_llm_handler(
     let pool = state.pool.clone();
     let llm = state.llm.clone();
     let rules = state.rules.clone();
+    let ocr = OcrReading::from_row(&row);
+    let profile = match (row.user_id, row.profile_id) {
+        (Some(user_id), Some(profile_id)) => {
+            db::get_family_profile(&state.pool, profile_id, user_id).await?
+        }
+        _ => None,
+    };
+    let options = match (row.user_id, &profile) {
+        (Some(user_id), Some(profile)) => {
+            AnalysisOptions::for_profile(&state.pool, user_id, profile, PreferenceBlend::default())
+                .await
+        }
+        _ => AnalysisOptions::for_user(&state.pool, row.user_id, PreferenceBlend::default()).await,
+    };
     tokio::spawn(async move {
-        run_llm_task(pool, llm, rules, id, confirmed_text, PreferenceType::None).await;
+        run_llm_task(pool, llm, rules, id, confirmed_text, ocr, options).await;
     });
 
     let updated = db::get_analysis(&state.pool, id)
@@ -211,6 +439,7 @@ async fn retry_llm_handler(
 /// Get analysis history
 async fn history_handler(
     State(state): State<AppState>,
+    OptionalAuthUser { user_id: auth_user }: OptionalAuthUser,
     Query(params): Query<HistoryQuery>,
 ) -> Result<Json<HistoryResponse>, AppError> {
     let page = params.page.unwrap_or(1).max(1);
@@ -227,13 +456,18 @@ async fn history_handler(
                 .and_then(|r| r.get("summary"))
                 .and_then(|s| s.as_str())
                 .map(|s| s.to_string());
+            // Annotations are private to the analysis owner.
+            let owned = auth_user.is_some() && row.user_id == auth_user;
             HistoryItem {
                 id: row.id,
                 image_url: row.image_url,
                 health_score: row.health_score,
                 summary,
+                profile_name: row.profile_name,
                 created_at: row.created_at.to_rfc3339(),
-                is_favorite: false,
+                is_favorite: owned && row.is_favorite,
+                tags: if owned { row.tags } else { Vec::new() },
+                note: row.note.filter(|_| owned),
             }
         })
         .collect();
@@ -252,8 +486,20 @@ struct HistoryQuery {
     limit: Option<i64>,
 }
 
+#[derive(Debug, Deserialize)]
+struct AlternativesQuery {
+    limit: Option<i64>,
+}
+
+const DEFAULT_ALTERNATIVES: i64 = 5;
+const MAX_ALTERNATIVES: i64 = 20;
+/// Candidates read from each source before filtering by category and ingredients
+const ALTERNATIVE_CANDIDATES: i64 = 200;
+
 const MAX_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
 const MAX_TEXT_LENGTH: usize = 5000;
+/// Most focuses a blended preference may list
+const MAX_PREFERENCES: usize = 10;
 
 fn validate_content_type(content_type: Option<&str>) -> Result<(), AppError> {
     // Only do basic validation, actual format is auto-detected by image crate
@@ -281,6 +527,11 @@ fn to_analysis_response(row: &db::AnalysisRow) -> AnalysisResponse {
             .as_ref()
             .and_then(|value| serde_json::from_value::<AnalysisResult>(value.clone()).ok()),
         error_message: row.error_message.clone(),
+        gtin: row.gtin.clone(),
+        product_id: row.product_id,
+        canonical_result: None,
+        profile_id: row.profile_id,
+        profile_name: row.profile_name.clone(),
         created_at: row.created_at.to_rfc3339(),
         updated_at: row.updated_at.to_rfc3339(),
     }
@@ -368,8 +619,10 @@ async fn run_ocr_task(
         }
     };
 
-    let ocr_text = match ocr::extract_text(&image_path, &config.ocr).await {
-        Ok(text) => text.trim().to_string(),
+    detect_barcode(&pool, analysis_id, image_path.clone()).await;
+
+    let output = match ocr::extract_text(&image_path, &config.ocr).await {
+        Ok(output) => output,
         Err(err) => {
             let _ = db::update_ocr_status(
                 &pool,
@@ -383,6 +636,7 @@ async fn run_ocr_task(
         }
     };
 
+    let ocr_text = output.text.trim().to_string();
     if ocr_text.is_empty() || ocr_text.len() > MAX_TEXT_LENGTH {
         let _ = db::update_ocr_status(
             &pool,
@@ -395,7 +649,125 @@ async fn run_ocr_task(
         return;
     }
 
-    let _ = db::save_ocr_result(&pool, analysis_id, &ocr_text, "ocr_completed").await;
+    let _ = db::save_ocr_result(
+        &pool,
+        analysis_id,
+        &ocr_text,
+        output.confidence,
+        "ocr_completed",
+    )
+    .await;
+}
+
+/// Decode a product barcode from the stored image; failures never block OCR
+async fn detect_barcode(pool: &sqlx::PgPool, analysis_id: Uuid, image_path: std::path::PathBuf) {
+    let decoded = tokio::task::spawn_blocking(move || barcode::decode_file(&image_path)).await;
+    match decoded {
+        Ok(Ok(Some(code))) => {
+            if let Err(err) = db::save_analysis_gtin(pool, analysis_id, &code.gtin).await {
+                warn!("failed to save gtin for {}: {}", analysis_id, err);
+            }
+        }
+        Ok(Ok(None)) => {}
+        Ok(Err(err)) => warn!("barcode decode failed for {}: {}", analysis_id, err),
+        Err(err) => warn!("barcode task panicked for {}: {}", analysis_id, err),
+    }
+}
+
+/// OCR output the user confirmed or edited, used to judge result confidence
+struct OcrReading {
+    text: Option<String>,
+    confidence: Option<f32>,
+}
+
+impl OcrReading {
+    fn from_row(row: &db::AnalysisRow) -> Self {
+        Self {
+            text: row.ocr_text.clone(),
+            confidence: row.ocr_confidence,
+        }
+    }
+}
+
+/// Analysis settings chosen by the user for one run
+struct AnalysisOptions {
+    preference: PreferenceBlend,
+    /// Allergens that raise a blocker warning when detected
+    allergens: Vec<Allergen>,
+    /// The saved allergens could not be loaded; the result must say so
+    allergens_unavailable: bool,
+    /// Diets to give compatibility verdicts for
+    diets: Vec<DietRegime>,
+    /// Personal rule overlays merged over the global rules
+    overlays: Vec<RuleOverlay>,
+}
+
+impl AnalysisOptions {
+    /// Settings saved for the user; lookup failures lose the personal settings, and a
+    /// failed allergen lookup is flagged so the result can warn about it
+    async fn for_user(
+        pool: &sqlx::PgPool,
+        user_id: Option<Uuid>,
+        preference: PreferenceBlend,
+    ) -> Self {
+        let mut options = Self {
+            preference,
+            allergens: Vec::new(),
+            allergens_unavailable: false,
+            diets: Vec::new(),
+            overlays: Vec::new(),
+        };
+        let Some(user_id) = user_id else {
+            return options;
+        };
+        match db::get_user_preferences(pool, user_id).await {
+            Ok(Some(row)) => {
+                options.allergens = Allergen::from_preferences(&row.preferences);
+                options.diets = DietRegime::from_preferences(&row.preferences);
+            }
+            Ok(None) => {}
+            Err(err) => {
+                warn!("failed to load allergens for {}: {}", user_id, err);
+                options.allergens_unavailable = true;
+            }
+        }
+        match db::list_rule_overlays(pool, user_id).await {
+            Ok(rows) => {
+                options.overlays = rows
+                    .into_iter()
+                    .map(db::RuleOverlayRow::into_overlay)
+                    .collect();
+            }
+            Err(err) => warn!("failed to load rule overlays for {}: {}", user_id, err),
+        }
+        options
+    }
+
+    /// Settings for a family profile: its preference (when set), allergens and avoid-list
+    /// replace the account's; the account's diets and rule overlays still apply underneath
+    async fn for_profile(
+        pool: &sqlx::PgPool,
+        user_id: Uuid,
+        profile: &db::FamilyProfileRow,
+        preference: PreferenceBlend,
+    ) -> Self {
+        let preference = match profile.preference.as_deref() {
+            Some(key) => PreferenceBlend::single(PreferenceType::from_str(Some(key))),
+            None => preference,
+        };
+        let mut options = Self::for_user(pool, Some(user_id), preference).await;
+        options.allergens = profile
+            .allergens
+            .iter()
+            .filter_map(|key| Allergen::parse(key))
+            .collect();
+        options.allergens_unavailable = false;
+        options.overlays.extend(rule_overlays::avoid_overlays(
+            &profile.name,
+            &profile.avoid_ingredients,
+        ));
+        options
+    }
 }
 
 async fn run_llm_task(
@@ -404,11 +776,28 @@ async fn run_llm_task(
     rules: std::sync::Arc<tokio::sync::RwLock<crate::services::rules::RuleEngine>>,
     analysis_id: Uuid,
     text: String,
-    preference: PreferenceType,
+    ocr: OcrReading,
+    options: AnalysisOptions,
 ) {
+    let preference = &options.preference;
     let _ = db::update_llm_status(&pool, analysis_id, "processing", "llm_processing", None).await;
 
-    let mut result = match llm.analyze_ingredients(&text, preference).await {
+    // Rules run first so the prompt can quote the matched rules.
+    let evaluation = {
+        let guard = rules.read().await;
+        if options.overlays.is_empty() {
+            guard.evaluate(&text, preference)
+        } else {
+            guard
+                .with_overlays(&options.overlays)
+                .evaluate(&text, preference)
+        }
+    };
+
+    let mut result = match llm
+        .analyze_ingredients(&text, preference, &evaluation.hits)
+        .await
+    {
         Ok(result) => result,
         Err(err) => {
             let _ = db::update_llm_status(
@@ -423,15 +812,34 @@ async fn run_llm_task(
         }
     };
 
-    let evaluation = {
-        let guard = rules.read().await;
-        guard.evaluate(&text, preference)
-    };
+    result.confidence = Some(confidence::assess(&ConfidenceSignals {
+        ocr_confidence: ocr.confidence,
+        ocr_text: ocr.text.as_deref(),
+        confirmed_text: &text,
+        ingredients: &evaluation.ingredients,
+        hits: &evaluation.hits,
+        llm_ingredients: &result.ingredients,
+        rules_error: evaluation.load_error.as_deref(),
+    }));
     result.rule_hits = evaluation.hits;
-    result.confidence = Some(evaluation.confidence);
+    result.ingredient_tree = evaluation.ingredients;
+    result.rule_set_version = evaluation.rule_set_version;
+    result.diet_verdicts = diet::evaluate(
+        &options.diets,
+        &evaluation.diet_hits,
+        evaluation.load_error.is_some(),
+    );
 
     let result = ensure_summary_table(result);
     let result = apply_score_breakdown(result, preference);
+    let result = reconcile::reconcile_with_rules(result);
+    let mut result = allergens::apply_allergen_alerts(result, &options.allergens);
+    if options.allergens_unavailable {
+        result = allergens::warn_allergens_unavailable(result);
+    }
+    let mut result = scoring::apply_position_weighting(result);
+    let weights = breakdown_weights(&result, preference);
+    result.score_attribution = Some(score_attribution::attribute(&result, &weights));
     let result_json = match serde_json::to_value(&result) {
         Ok(value) => value,
         Err(err) => {
@@ -458,32 +866,33 @@ async fn run_llm_task(
     .await;
 }
 
-fn apply_score_breakdown(mut result: AnalysisResult, preference: PreferenceType) -> AnalysisResult {
-    let breakdown = match result.score_breakdown.as_ref() {
-        Some(items) if !items.is_empty() => items,
-        _ => return result,
-    };
-
-    let weights = score_weights(preference);
-    let mut weighted_sum = 0.0;
-    let mut weight_total = 0.0;
-
-    for item in breakdown {
-        if let Some(dimension) = normalize_dimension(&item.dimension) {
-            if let Some(weight) = weights.iter().find(|(key, _)| *key == dimension) {
-                let score = item.score.clamp(0, 100) as f32;
-                weighted_sum += score * weight.1;
-                weight_total += weight.1;
-            }
-        }
-    }
-
-    if weight_total > 0.0 {
-        let computed = (weighted_sum / weight_total).round() as i32;
-        result.health_score = computed.clamp(0, 100);
+fn apply_score_breakdown(
+    mut result: AnalysisResult,
+    preference: &PreferenceBlend,
+) -> AnalysisResult {
+    let weights = breakdown_weights(&result, preference);
+    if let Some(score) = score_attribution::weighted_score(&result, &weights) {
+        result.health_score = score;
     }
+    result
+}
 
+/// Index into `score_breakdown` and weight of every dimension the preference scores
+fn breakdown_weights(result: &AnalysisResult, preference: &PreferenceBlend) -> Vec<(usize, f32)> {
+    let weights = preference.blend_table(score_weights);
     result
+        .score_breakdown
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let dimension = normalize_dimension(&item.dimension)?;
+            weights
+                .iter()
+                .find(|(key, _)| *key == dimension)
+                .map(|(_, weight)| (index, *weight))
+        })
+        .collect()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -38,7 +38,7 @@ async fn register(
     db::ensure_user_preferences(&state.pool, user.id).await?;
 
     let analysis_count = db::count_user_analyses(&state.pool, user.id).await?;
-    let tokens = auth::issue_tokens(&state.config.auth, user.id)?;
+    let tokens = auth::issue_tokens(&state.config.auth, user.id, user.user_role())?;
     store_refresh_token(&state, user.id, &tokens.refresh_token).await?;
 
     Ok(Json(AuthResponse {
@@ -50,6 +50,8 @@ async fn register(
             login_id: user_login_id(&user),
             created_at: user.created_at.to_rfc3339(),
             analysis_count,
+            role: user.user_role(),
+            permissions: user.user_role().permissions().to_vec(),
         },
     }))
 }
@@ -91,7 +93,7 @@ async fn login(
     db::update_user_last_login(&state.pool, user.id).await?;
 
     let analysis_count = db::count_user_analyses(&state.pool, user.id).await?;
-    let tokens = auth::issue_tokens(&state.config.auth, user.id)?;
+    let tokens = auth::issue_tokens(&state.config.auth, user.id, user.user_role())?;
     store_refresh_token(&state, user.id, &tokens.refresh_token).await?;
 
     Ok(Json(AuthResponse {
@@ -103,6 +105,8 @@ async fn login(
             login_id: user_login_id(&user),
             created_at: user.created_at.to_rfc3339(),
             analysis_count,
+            role: user.user_role(),
+            permissions: user.user_role().permissions().to_vec(),
         },
     }))
 }
@@ -128,7 +132,7 @@ async fn refresh_token(
         .ok_or_else(|| AppError::Unauthorized("invalid refresh token".to_string()))?;
 
     let analysis_count = db::count_user_analyses(&state.pool, user.id).await?;
-    let tokens = auth::issue_tokens(&state.config.auth, user.id)?;
+    let tokens = auth::issue_tokens(&state.config.auth, user.id, user.user_role())?;
     let new_refresh_key = format!("auth:refresh:{}", tokens.refresh_token);
     let user_tokens_key = format!("auth:user:{}:tokens", user.id);
     let _: i64 = redis.del(&refresh_key).await?;
@@ -151,6 +155,8 @@ async fn refresh_token(
             login_id: user_login_id(&user),
             created_at: user.created_at.to_rfc3339(),
             analysis_count,
+            role: user.user_role(),
+            permissions: user.user_role().permissions().to_vec(),
         },
     }))
 }
@@ -10,7 +10,7 @@ use uuid::Uuid;
 use crate::{
     db,
     errors::AppError,
-    middleware::OptionalAuthUser,
+    middleware::{permissions::ModerateCommunity, OptionalAuthUser, RequirePermission},
     services::{community, storage},
     state::AppState,
 };
@@ -35,6 +35,7 @@ pub fn routes() -> Router<AppState> {
             "/posts/:id",
             axum::routing::get(get_post).delete(delete_post),
         )
+        .route("/posts/:id/moderate", axum::routing::delete(moderate_post))
 }
 
 async fn create_post(
@@ -208,3 +209,15 @@ async fn delete_post(
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
+
+/// Remove any post, for moderators
+async fn moderate_post(
+    State(state): State<AppState>,
+    _moderator: RequirePermission<ModerateCommunity>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    if db::delete_community_post(&state.pool, id).await? == 0 {
+        return Err(AppError::NotFound("帖子不存在".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
@@ -0,0 +1,152 @@
+//! Family member profile handlers
+
+use axum::{
+    extract::{Path, State},
+    Json, Router,
+};
+use shared::{Allergen, FamilyProfile, FamilyProfileInput, FamilyProfileListResponse};
+use uuid::Uuid;
+
+use crate::{db, errors::AppError, middleware::AuthUser, state::AppState};
+
+const MAX_PROFILES: usize = 10;
+const MAX_NAME_LENGTH: usize = 30;
+const MAX_PREFERENCE_LENGTH: usize = 32;
+const MAX_AVOID_INGREDIENTS: usize = 50;
+const MAX_INGREDIENT_LENGTH: usize = 100;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(list_profiles))
+        .route("/", axum::routing::post(create_profile))
+        .route("/:id", axum::routing::put(update_profile))
+        .route("/:id", axum::routing::delete(delete_profile))
+}
+
+async fn list_profiles(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<Json<FamilyProfileListResponse>, AppError> {
+    let rows = db::list_family_profiles(&state.pool, user_id).await?;
+    Ok(Json(FamilyProfileListResponse {
+        items: rows
+            .into_iter()
+            .map(db::FamilyProfileRow::into_profile)
+            .collect(),
+    }))
+}
+
+async fn create_profile(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Json(payload): Json<FamilyProfileInput>,
+) -> Result<Json<FamilyProfile>, AppError> {
+    let existing = db::list_family_profiles(&state.pool, user_id).await?;
+    if existing.len() >= MAX_PROFILES {
+        return Err(AppError::BadRequest(format!(
+            "家庭成员最多 {} 位",
+            MAX_PROFILES
+        )));
+    }
+    let input = validate(payload, &existing, None)?;
+    let row = db::insert_family_profile(&state.pool, user_id, &input).await?;
+    Ok(Json(row.into_profile()))
+}
+
+async fn update_profile(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<FamilyProfileInput>,
+) -> Result<Json<FamilyProfile>, AppError> {
+    let existing = db::list_family_profiles(&state.pool, user_id).await?;
+    let input = validate(payload, &existing, Some(id))?;
+    let row = db::update_family_profile(&state.pool, id, user_id, &input)
+        .await?
+        .ok_or_else(|| AppError::NotFound("家庭成员不存在".to_string()))?;
+    Ok(Json(row.into_profile()))
+}
+
+async fn delete_profile(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let deleted = db::delete_family_profile(&state.pool, id, user_id).await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound("家庭成员不存在".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Normalize the input and reject invalid fields or a second profile with the same name;
+/// `current` is the profile being updated
+fn validate(
+    input: FamilyProfileInput,
+    existing: &[db::FamilyProfileRow],
+    current: Option<Uuid>,
+) -> Result<FamilyProfileInput, AppError> {
+    let name = input.name.trim().to_string();
+    let length = name.chars().count();
+    if length == 0 || length > MAX_NAME_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "成员名称长度需为 1-{}",
+            MAX_NAME_LENGTH
+        )));
+    }
+    if existing
+        .iter()
+        .any(|row| Some(row.id) != current && row.name == name)
+    {
+        return Err(AppError::BadRequest("已存在同名家庭成员".to_string()));
+    }
+
+    let preference = input
+        .preference
+        .map(|value| value.trim().to_lowercase())
+        .filter(|value| !value.is_empty());
+    if preference
+        .as_ref()
+        .is_some_and(|value| value.len() > MAX_PREFERENCE_LENGTH)
+    {
+        return Err(AppError::BadRequest("未知分析偏好".to_string()));
+    }
+
+    let mut allergens = Vec::new();
+    for key in &input.allergens {
+        let allergen = Allergen::parse(key)
+            .ok_or_else(|| AppError::BadRequest(format!("未知过敏原：{}", key)))?;
+        let key = allergen.as_key().to_string();
+        if !allergens.contains(&key) {
+            allergens.push(key);
+        }
+    }
+
+    let mut avoid_ingredients: Vec<String> = Vec::new();
+    for ingredient in &input.avoid_ingredients {
+        let ingredient = ingredient.trim();
+        if ingredient.is_empty() || avoid_ingredients.iter().any(|item| item == ingredient) {
+            continue;
+        }
+        if ingredient.chars().count() > MAX_INGREDIENT_LENGTH {
+            return Err(AppError::BadRequest(format!(
+                "配料名称长度不能超过 {}",
+                MAX_INGREDIENT_LENGTH
+            )));
+        }
+        avoid_ingredients.push(ingredient.to_string());
+    }
+    if avoid_ingredients.len() > MAX_AVOID_INGREDIENTS {
+        return Err(AppError::BadRequest(format!(
+            "规避配料最多 {} 项",
+            MAX_AVOID_INGREDIENTS
+        )));
+    }
+
+    Ok(FamilyProfileInput {
+        name,
+        preference,
+        allergens,
+        avoid_ingredients,
+    })
+}
@@ -1,6 +1,11 @@
 //! Request handlers
 
+pub mod admin_rules;
+pub mod admin_users;
 pub mod analysis;
 pub mod auth;
 pub mod community;
+pub mod family_profiles;
+pub mod products;
+pub mod rule_overlays;
 pub mod users;
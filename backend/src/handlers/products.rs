@@ -0,0 +1,160 @@
+//! Product catalog handlers
+
+use axum::{
+    extract::{Path, Query, State},
+    Json, Router,
+};
+use serde::Deserialize;
+use shared::{
+    AnalysisResult, AttachAnalysisRequest, Permission, Product, ProductListItem,
+    ProductListResponse,
+};
+
+use crate::{db, errors::AppError, middleware::AuthUser, services::barcode, state::AppState};
+
+const MAX_NAME_LENGTH: usize = 200;
+
+#[derive(Debug, Deserialize)]
+struct ProductSearchQuery {
+    q: Option<String>,
+    page: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Create product routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(search_products))
+        .route("/barcode/:gtin", axum::routing::get(get_by_barcode))
+        .route("/attach", axum::routing::post(attach_analysis))
+}
+
+/// Look up a product and its canonical analysis by barcode
+async fn get_by_barcode(
+    State(state): State<AppState>,
+    Path(gtin): Path<String>,
+) -> Result<Json<Product>, AppError> {
+    let gtin = barcode::normalize_gtin(&gtin)
+        .ok_or_else(|| AppError::BadRequest("条码格式不正确".to_string()))?;
+    let row = db::get_product_by_gtin(&state.pool, &gtin)
+        .await?
+        .ok_or_else(|| AppError::NotFound("商品不存在".to_string()))?;
+
+    Ok(Json(to_product(row)))
+}
+
+/// Search products by name or brand
+async fn search_products(
+    State(state): State<AppState>,
+    Query(params): Query<ProductSearchQuery>,
+) -> Result<Json<ProductListResponse>, AppError> {
+    let query = params.q.unwrap_or_default();
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(AppError::BadRequest("搜索关键词不能为空".to_string()));
+    }
+    let page = params.page.unwrap_or(1).max(1);
+    let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let offset = (page - 1) * limit;
+
+    let (total, rows) = db::search_products(&state.pool, query, limit, offset).await?;
+    let items = rows
+        .into_iter()
+        .map(|row| ProductListItem {
+            id: row.id,
+            gtin: row.gtin,
+            brand: row.brand,
+            name: row.name,
+            category: row.category,
+            health_score: row.health_score,
+        })
+        .collect();
+
+    Ok(Json(ProductListResponse {
+        total,
+        page,
+        limit,
+        items,
+    }))
+}
+
+/// Attach a completed analysis to a product, making it the canonical result when the
+/// caller curates the catalog or the product has none
+async fn attach_analysis(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Json(payload): Json<AttachAnalysisRequest>,
+) -> Result<Json<Product>, AppError> {
+    let analysis = db::get_analysis(&state.pool, payload.analysis_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("analysis not found".to_string()))?;
+    if analysis.user_id != Some(user_id) {
+        return Err(AppError::Forbidden("只能关联自己的分析记录".to_string()));
+    }
+    if analysis.status != "completed" {
+        return Err(AppError::BadRequest("分析尚未完成".to_string()));
+    }
+
+    let gtin = match payload.gtin.as_deref().filter(|v| !v.trim().is_empty()) {
+        Some(value) => barcode::normalize_gtin(value)
+            .ok_or_else(|| AppError::BadRequest("条码格式不正确".to_string()))?,
+        None => analysis
+            .gtin
+            .clone()
+            .ok_or_else(|| AppError::BadRequest("未识别到条码，请手动填写".to_string()))?,
+    };
+
+    let name = payload.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::BadRequest("商品名称长度需为 1-200".to_string()));
+    }
+    let brand = non_empty(payload.brand.as_deref());
+    let category = non_empty(payload.category.as_deref());
+
+    // The catalog is shared: only curators may rename a product or replace its canonical
+    // analysis; other users fill in what is missing.
+    let curator = db::get_user_by_id(&state.pool, user_id)
+        .await?
+        .is_some_and(|user| {
+            user.user_role()
+                .permissions()
+                .contains(&Permission::CurateCatalog)
+        });
+    db::upsert_product_with_analysis(
+        &state.pool,
+        &gtin,
+        brand,
+        name,
+        category,
+        analysis.id,
+        curator,
+    )
+    .await?;
+    let row = db::get_product_by_gtin(&state.pool, &gtin)
+        .await?
+        .ok_or_else(|| AppError::Internal("failed to load product".to_string()))?;
+
+    Ok(Json(to_product(row)))
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn to_product(row: db::ProductRow) -> Product {
+    Product {
+        id: row.id,
+        gtin: row.gtin,
+        brand: row.brand,
+        name: row.name,
+        category: row.category,
+        ingredient_text: row.ingredient_text,
+        canonical_analysis_id: row.canonical_analysis_id,
+        health_score: row.health_score,
+        canonical_result: row
+            .result
+            .and_then(|value| serde_json::from_value::<AnalysisResult>(value).ok()),
+        created_at: row.created_at.to_rfc3339(),
+        updated_at: row.updated_at.to_rfc3339(),
+    }
+}
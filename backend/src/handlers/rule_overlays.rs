@@ -0,0 +1,159 @@
+//! Personal rule overlay handlers
+
+use axum::{
+    extract::{Path, State},
+    Json, Router,
+};
+use shared::{RuleOverlay, RuleOverlayInput, RuleOverlayListResponse};
+use uuid::Uuid;
+
+use crate::{
+    db,
+    errors::AppError,
+    middleware::AuthUser,
+    services::{rule_validation::RISK_LEVELS, rules::normalize_token},
+    state::AppState,
+};
+
+const MAX_OVERLAYS: usize = 200;
+const MAX_INGREDIENT_LENGTH: usize = 100;
+const MAX_NOTE_LENGTH: usize = 500;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", axum::routing::get(list_overlays))
+        .route("/", axum::routing::post(create_overlay))
+        .route("/:id", axum::routing::put(update_overlay))
+        .route("/:id", axum::routing::delete(delete_overlay))
+}
+
+async fn list_overlays(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+) -> Result<Json<RuleOverlayListResponse>, AppError> {
+    let rows = db::list_rule_overlays(&state.pool, user_id).await?;
+    Ok(Json(RuleOverlayListResponse {
+        items: rows
+            .into_iter()
+            .map(db::RuleOverlayRow::into_overlay)
+            .collect(),
+    }))
+}
+
+async fn create_overlay(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Json(payload): Json<RuleOverlayInput>,
+) -> Result<Json<RuleOverlay>, AppError> {
+    let existing = db::list_rule_overlays(&state.pool, user_id).await?;
+    if existing.len() >= MAX_OVERLAYS {
+        return Err(AppError::BadRequest(format!(
+            "个人规则最多 {} 条",
+            MAX_OVERLAYS
+        )));
+    }
+    let input = validate(&state, payload, &existing, None).await?;
+    let row = db::insert_rule_overlay(&state.pool, user_id, &input).await?;
+    Ok(Json(row.into_overlay()))
+}
+
+async fn update_overlay(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RuleOverlayInput>,
+) -> Result<Json<RuleOverlay>, AppError> {
+    let existing = db::list_rule_overlays(&state.pool, user_id).await?;
+    let input = validate(&state, payload, &existing, Some(id)).await?;
+    let row = db::update_rule_overlay(&state.pool, id, user_id, &input)
+        .await?
+        .ok_or_else(|| AppError::NotFound("个人规则不存在".to_string()))?;
+    Ok(Json(row.into_overlay()))
+}
+
+async fn delete_overlay(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let deleted = db::delete_rule_overlay(&state.pool, id, user_id).await?;
+    if deleted == 0 {
+        return Err(AppError::NotFound("个人规则不存在".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Normalize the input and reject invalid fields or a second overlay for the same
+/// ingredient or rule; `current` is the overlay being updated
+async fn validate(
+    state: &AppState,
+    input: RuleOverlayInput,
+    existing: &[db::RuleOverlayRow],
+    current: Option<Uuid>,
+) -> Result<RuleOverlayInput, AppError> {
+    // Trusted ingredients have no risk of their own; store the lowest level.
+    let risk_level = match input.risk_level.trim().to_lowercase() {
+        level if level.is_empty() && input.trusted => "low".to_string(),
+        level => level,
+    };
+    if !RISK_LEVELS.contains(&risk_level.as_str()) {
+        return Err(AppError::BadRequest(format!(
+            "未知风险等级：{}",
+            input.risk_level
+        )));
+    }
+
+    let rule_id = input
+        .rule_id
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let mut ingredient = input.ingredient.trim().to_string();
+    if let Some(rule_id) = &rule_id {
+        let rule = db::get_rule(&state.pool, rule_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("规则不存在".to_string()))?;
+        if ingredient.is_empty() {
+            ingredient = rule.name;
+        }
+    }
+    let length = ingredient.chars().count();
+    if length == 0 || length > MAX_INGREDIENT_LENGTH {
+        return Err(AppError::BadRequest(format!(
+            "配料名称长度需为 1-{}",
+            MAX_INGREDIENT_LENGTH
+        )));
+    }
+
+    let note = input
+        .note
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if note
+        .as_ref()
+        .is_some_and(|note| note.chars().count() > MAX_NOTE_LENGTH)
+    {
+        return Err(AppError::BadRequest(format!(
+            "备注长度不能超过 {}",
+            MAX_NOTE_LENGTH
+        )));
+    }
+
+    let key = normalize_token(&ingredient);
+    let duplicate = existing
+        .iter()
+        .filter(|row| Some(row.id) != current)
+        .any(|row| {
+            normalize_token(&row.ingredient) == key || (rule_id.is_some() && row.rule_id == rule_id)
+        });
+    if duplicate {
+        return Err(AppError::BadRequest("已存在该配料的个人规则".to_string()));
+    }
+
+    Ok(RuleOverlayInput {
+        ingredient,
+        rule_id,
+        risk_level,
+        note,
+        trusted: input.trusted,
+    })
+}
@@ -4,16 +4,24 @@ use axum::{
     extract::{Path, Query, State},
     Json, Router,
 };
+use chrono::{NaiveDate, NaiveTime};
 use redis::AsyncCommands;
 use serde::Deserialize;
 use shared::{
-    BatchDeleteRequest, HistoryItem, HistoryPruneRequest, HistoryPruneResponse, HistoryResponse,
-    LocalHistoryMigrateRequest, LocalHistoryMigrateResponse, UpdatePreferencesRequest,
-    UserPreferences, UserProfile,
+    BatchDeleteRequest, FavoriteRequest, HistoryAnnotations, HistoryAnnotationsResponse,
+    HistoryItem, HistoryNotesRequest, HistoryPruneRequest, HistoryPruneResponse, HistoryResponse,
+    IngredientCount, InsightsResponse, LocalHistoryMigrateRequest, LocalHistoryMigrateResponse,
+    MonthComparison, UpdatePreferencesRequest, UserPreferences, UserProfile, WeeklyInsight,
 };
 use uuid::Uuid;
 
-use crate::{db, errors::AppError, middleware::AuthUser, state::AppState};
+use crate::{
+    db,
+    errors::AppError,
+    middleware::AuthUser,
+    services::{allergens::ALLERGEN_WARNING_TYPE, llm::PreferenceType},
+    state::AppState,
+};
 
 pub fn routes() -> Router<AppState> {
     Router::new()
@@ -26,6 +34,11 @@ pub fn routes() -> Router<AppState> {
         .route("/history/batch", axum::routing::post(migrate_history_batch))
         .route("/history/prune", axum::routing::post(prune_history))
         .route("/history/:id", axum::routing::delete(delete_history))
+        .route("/history/:id/favorite", axum::routing::put(set_favorite))
+        .route("/history/:id/notes", axum::routing::put(set_notes))
+        .route("/insights", axum::routing::get(get_insights))
+        .nest("/rule-overlays", super::rule_overlays::routes())
+        .nest("/profiles", super::family_profiles::routes())
 }
 
 async fn get_profile(
@@ -42,6 +55,8 @@ async fn get_profile(
         login_id: resolve_login_id(&user),
         created_at: user.created_at.to_rfc3339(),
         analysis_count,
+        role: user.user_role(),
+        permissions: user.user_role().permissions().to_vec(),
     }))
 }
 
@@ -63,7 +78,7 @@ async fn delete_account(
     Ok(Json(serde_json::json!({ "success": true })))
 }
 
-fn resolve_login_id(user: &db::UserRow) -> String {
+pub(crate) fn resolve_login_id(user: &db::UserRow) -> String {
     user.username.clone().unwrap_or_else(|| "用户".to_string())
 }
 
@@ -109,8 +124,9 @@ async fn list_history(
     let page = params.page.unwrap_or(1).max(1);
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
     let offset = (page - 1) * limit;
+    let filter = params.filter()?;
 
-    let (total, rows) = db::list_user_history(&state.pool, user_id, limit, offset).await?;
+    let (total, rows) = db::list_user_history(&state.pool, user_id, &filter, limit, offset).await?;
     let items = rows
         .into_iter()
         .map(|row| {
@@ -125,8 +141,11 @@ async fn list_history(
                 image_url: row.image_url,
                 health_score: row.health_score,
                 summary,
+                profile_name: row.profile_name,
                 created_at: row.created_at.to_rfc3339(),
-                is_favorite: false,
+                is_favorite: row.is_favorite,
+                tags: row.tags,
+                note: row.note,
             }
         })
         .collect();
@@ -173,16 +192,82 @@ async fn migrate_history_batch(
     }
 
     let migrated = db::migrate_user_histories(&state.pool, user_id, &payload.ids).await?;
+    for local in payload.annotations {
+        if !migrated.contains(&local.id) {
+            continue;
+        }
+        // Local annotations were checked on the device; skip any that no longer pass.
+        let Ok(annotations) = local.annotations.normalized() else {
+            continue;
+        };
+        db::set_history_annotations(
+            &state.pool,
+            user_id,
+            local.id,
+            annotations.is_favorite,
+            &annotations.tags,
+            annotations.note.as_deref(),
+        )
+        .await?;
+    }
     let total_after = db::count_user_analyses(&state.pool, user_id).await?;
-    let skipped = payload.ids.len() as i64 - migrated as i64;
+    let skipped = payload.ids.len() as i64 - migrated.len() as i64;
 
     Ok(Json(LocalHistoryMigrateResponse {
-        migrated: migrated as i64,
+        migrated: migrated.len() as i64,
         skipped,
         total_after,
     }))
 }
 
+fn annotations_response(row: db::AnnotationsRow) -> HistoryAnnotationsResponse {
+    HistoryAnnotationsResponse {
+        id: row.id,
+        annotations: HistoryAnnotations {
+            is_favorite: row.is_favorite,
+            tags: row.tags,
+            note: row.note,
+        },
+    }
+}
+
+async fn set_favorite(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<FavoriteRequest>,
+) -> Result<Json<HistoryAnnotationsResponse>, AppError> {
+    let row = db::set_history_favorite(&state.pool, user_id, id, payload.is_favorite)
+        .await?
+        .ok_or_else(|| AppError::NotFound("history item not found".to_string()))?;
+    Ok(Json(annotations_response(row)))
+}
+
+async fn set_notes(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<HistoryNotesRequest>,
+) -> Result<Json<HistoryAnnotationsResponse>, AppError> {
+    let annotations = HistoryAnnotations {
+        is_favorite: false,
+        tags: payload.tags,
+        note: payload.note,
+    }
+    .normalized()
+    .map_err(AppError::BadRequest)?;
+    let row = db::set_history_notes(
+        &state.pool,
+        user_id,
+        id,
+        &annotations.tags,
+        annotations.note.as_deref(),
+    )
+    .await?
+    .ok_or_else(|| AppError::NotFound("history item not found".to_string()))?;
+    Ok(Json(annotations_response(row)))
+}
+
 async fn prune_history(
     State(state): State<AppState>,
     AuthUser { user_id }: AuthUser,
@@ -203,8 +288,160 @@ async fn prune_history(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+struct InsightsQuery {
+    weeks: Option<i32>,
+}
+
+const DEFAULT_INSIGHT_WEEKS: i32 = 12;
+const MAX_INSIGHT_WEEKS: i32 = 52;
+const TOP_INGREDIENTS: i64 = 10;
+
+async fn get_insights(
+    State(state): State<AppState>,
+    AuthUser { user_id }: AuthUser,
+    Query(params): Query<InsightsQuery>,
+) -> Result<Json<InsightsResponse>, AppError> {
+    let weeks = params
+        .weeks
+        .unwrap_or(DEFAULT_INSIGHT_WEEKS)
+        .clamp(1, MAX_INSIGHT_WEEKS);
+
+    let overview = db::get_insights_overview(&state.pool, user_id, ALLERGEN_WARNING_TYPE).await?;
+    let weekly = db::list_weekly_insights(&state.pool, user_id, weeks).await?;
+    let high_risk_ingredients =
+        db::list_frequent_ingredients(&state.pool, user_id, Some("high"), None, TOP_INGREDIENTS)
+            .await?;
+    let additives = db::list_frequent_ingredients(
+        &state.pool,
+        user_id,
+        None,
+        Some("additive"),
+        TOP_INGREDIENTS,
+    )
+    .await?;
+
+    let counts = |rows: Vec<db::IngredientCountRow>| -> Vec<IngredientCount> {
+        rows.into_iter()
+            .map(|row| IngredientCount {
+                name: row.name,
+                analyses: row.analyses,
+            })
+            .collect()
+    };
+    let change = match (overview.current_average, overview.previous_average) {
+        (Some(current), Some(previous)) => Some(current - previous),
+        _ => None,
+    };
+
+    Ok(Json(InsightsResponse {
+        total_analyses: overview.total_analyses,
+        average_score: overview.average_score,
+        weekly_trend: weekly.first().and_then(|week| week.trend),
+        weeks: weekly
+            .into_iter()
+            .map(|week| WeeklyInsight {
+                week_start: week.week_start,
+                analyses: week.analyses,
+                average_score: week.average_score,
+            })
+            .collect(),
+        high_risk_ingredients: counts(high_risk_ingredients),
+        additives: counts(additives),
+        allergen_warning_analyses: overview.warning_analyses,
+        allergen_warning_share: (overview.total_analyses > 0)
+            .then(|| overview.warning_analyses as f64 / overview.total_analyses as f64),
+        month_comparison: MonthComparison {
+            current_month: overview.current_month,
+            current_analyses: overview.current_analyses,
+            current_average: overview.current_average,
+            previous_analyses: overview.previous_analyses,
+            previous_average: overview.previous_average,
+            change,
+        },
+    }))
+}
+
 #[derive(Debug, Deserialize)]
 struct HistoryQuery {
     page: Option<i64>,
     limit: Option<i64>,
+    /// Search words
+    q: Option<String>,
+    min_score: Option<i32>,
+    max_score: Option<i32>,
+    /// First day, `YYYY-MM-DD` (UTC)
+    from: Option<String>,
+    /// Last day, inclusive
+    to: Option<String>,
+    preference: Option<String>,
+    warning_type: Option<String>,
+    ingredient: Option<String>,
+    /// Only favorites when `true`
+    favorite: Option<bool>,
+    tag: Option<String>,
+}
+
+const MAX_SEARCH_LENGTH: usize = 100;
+
+impl HistoryQuery {
+    fn filter(&self) -> Result<db::HistoryFilter, AppError> {
+        let text = |value: &Option<String>| -> Result<Option<String>, AppError> {
+            let value = value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty());
+            if value.is_some_and(|value| value.chars().count() > MAX_SEARCH_LENGTH) {
+                return Err(AppError::BadRequest(format!(
+                    "搜索条件长度不能超过 {}",
+                    MAX_SEARCH_LENGTH
+                )));
+            }
+            Ok(value.map(str::to_string))
+        };
+        let day = |value: &Option<String>| -> Result<Option<NaiveDate>, AppError> {
+            text(value)?
+                .map(|value| {
+                    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+                        .map_err(|_| AppError::BadRequest("日期格式应为 YYYY-MM-DD".to_string()))
+                })
+                .transpose()
+        };
+
+        for score in [self.min_score, self.max_score].into_iter().flatten() {
+            if !(0..=100).contains(&score) {
+                return Err(AppError::BadRequest("评分范围应为 0-100".to_string()));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_score, self.max_score) {
+            if min > max {
+                return Err(AppError::BadRequest("最低评分不能高于最高评分".to_string()));
+            }
+        }
+        let from = day(&self.from)?;
+        let to = day(&self.to)?;
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::BadRequest("开始日期不能晚于结束日期".to_string()));
+            }
+        }
+
+        Ok(db::HistoryFilter {
+            query: text(&self.q)?,
+            min_score: self.min_score,
+            max_score: self.max_score,
+            created_from: from.map(|date| date.and_time(NaiveTime::MIN).and_utc()),
+            created_until: to
+                .and_then(|date| date.succ_opt())
+                .map(|date| date.and_time(NaiveTime::MIN).and_utc()),
+            preference: text(&self.preference)?
+                .map(|value| PreferenceType::from_str(Some(&value)).as_key().to_string()),
+            warning_type: text(&self.warning_type)?,
+            ingredient: text(&self.ingredient)?,
+            favorite: self.favorite.unwrap_or(false),
+            tag: text(&self.tag)?
+                .map(|tag| tag.trim_start_matches('#').trim().to_string())
+                .filter(|tag| !tag.is_empty()),
+        })
+    }
 }
@@ -1,3 +1,5 @@
+pub mod cli;
 pub mod config;
+pub mod db;
 pub mod errors;
 pub mod services;
@@ -13,7 +13,6 @@ mod state;
 
 use anyhow::Result;
 use std::net::SocketAddr;
-use std::time::Duration;
 use tracing::{info, warn};
 use tracing_subscriber::fmt::time::UtcTime;
 use tracing_subscriber::{fmt, EnvFilter};
@@ -44,6 +43,16 @@ async fn main() -> Result<()> {
     let http = reqwest::Client::new();
     let llm = services::llm::build_llm_client(&config.llm, http.clone());
 
+    if !config.auth.admin_user_ids.is_empty() {
+        match db::grant_admin_roles(&pool, &config.auth.admin_user_ids).await {
+            Ok(granted) => info!(
+                "granted admin role to {} user(s) from ADMIN_USER_IDS",
+                granted
+            ),
+            Err(err) => warn!("failed to apply ADMIN_USER_IDS: {}", err),
+        }
+    }
+
     let rules = match services::rules::RuleEngine::try_load_from_db(&pool).await {
         Ok(engine) => engine,
         Err(err) => {
@@ -53,24 +62,18 @@ async fn main() -> Result<()> {
     };
     let rules = std::sync::Arc::new(tokio::sync::RwLock::new(rules));
 
-    let refresh_interval = Duration::from_secs(config.rules_refresh_seconds);
-    let rules_for_refresh = rules.clone();
-    let pool_for_refresh = pool.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(refresh_interval);
-        loop {
-            interval.tick().await;
-            match services::rules::RuleEngine::try_load_from_db(&pool_for_refresh).await {
-                Ok(engine) => {
-                    let mut guard = rules_for_refresh.write().await;
-                    *guard = engine;
-                }
-                Err(err) => {
-                    warn!("failed to refresh rules from db: {}", err);
-                }
-            }
-        }
-    });
+    tokio::spawn(services::rules::watch_rule_changes(
+        pool.clone(),
+        rules.clone(),
+    ));
+
+    if config.retention.enabled {
+        tokio::spawn(services::retention::run_periodically(
+            pool.clone(),
+            config.upload_dir.clone(),
+            config.retention.clone(),
+        ));
+    }
 
     let state = state::AppState {
         pool,
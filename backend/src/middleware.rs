@@ -7,11 +7,13 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use std::{sync::OnceLock, time::Instant};
+use shared::UserRole;
+use std::{marker::PhantomData, sync::OnceLock, time::Instant};
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
+    db,
     errors::{AppError, ErrorMeta},
     services::auth,
     state::AppState,
@@ -113,26 +115,141 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
-        let auth_header = parts
-            .headers
-            .get(header::AUTHORIZATION)
-            .and_then(|value| value.to_str().ok())
-            .ok_or_else(|| AppError::Unauthorized("missing authorization header".to_string()))?;
+        let (user_id, _) = require_claims(parts, state)?;
+        Ok(Self { user_id })
+    }
+}
 
-        let token = auth_header
-            .strip_prefix("Bearer ")
-            .ok_or_else(|| AppError::Unauthorized("invalid authorization header".to_string()))?;
+/// Role markers for [`RequireRole`]
+pub mod roles {
+    use shared::UserRole;
 
-        let claims = auth::decode_access_token(&state.config.auth, token)
-            .map_err(|_| AppError::Unauthorized("invalid token".to_string()))?;
+    pub trait RoleMarker: Send + Sync {
+        const ROLE: UserRole;
+    }
 
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| AppError::Unauthorized("invalid token subject".to_string()))?;
+    pub struct Admin;
 
-        Ok(Self { user_id })
+    impl RoleMarker for Admin {
+        const ROLE: UserRole = UserRole::Admin;
+    }
+}
+
+/// Permission markers for [`RequirePermission`]
+pub mod permissions {
+    use shared::Permission;
+
+    pub trait PermissionMarker: Send + Sync {
+        const PERMISSION: Permission;
+    }
+
+    pub struct ManageRules;
+    pub struct ModerateCommunity;
+    pub struct ManageUsers;
+
+    impl PermissionMarker for ManageRules {
+        const PERMISSION: Permission = Permission::ManageRules;
+    }
+
+    impl PermissionMarker for ModerateCommunity {
+        const PERMISSION: Permission = Permission::ModerateCommunity;
+    }
+
+    impl PermissionMarker for ManageUsers {
+        const PERMISSION: Permission = Permission::ManageUsers;
+    }
+}
+
+/// Authenticated user whose current role is `R` or a higher one
+#[allow(dead_code)]
+pub struct RequireRole<R> {
+    pub user_id: Uuid,
+    _marker: PhantomData<R>,
+}
+
+#[async_trait]
+impl<R: roles::RoleMarker> FromRequestParts<AppState> for RequireRole<R> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, _) = require_claims(parts, state)?;
+        if stored_role(state, user_id).await? < R::ROLE {
+            return Err(AppError::Forbidden("权限不足".to_string()));
+        }
+        Ok(Self {
+            user_id,
+            _marker: PhantomData,
+        })
     }
 }
 
+/// Authenticated user whose current role grants permission `P`
+pub struct RequirePermission<P> {
+    pub user_id: Uuid,
+    _marker: PhantomData<P>,
+}
+
+#[async_trait]
+impl<P: permissions::PermissionMarker> FromRequestParts<AppState> for RequirePermission<P> {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let (user_id, _) = require_claims(parts, state)?;
+        if !stored_role(state, user_id)
+            .await?
+            .permissions()
+            .contains(&P::PERMISSION)
+        {
+            return Err(AppError::Forbidden("权限不足".to_string()));
+        }
+        Ok(Self {
+            user_id,
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// Roles change without reissuing access tokens, so the stored role decides
+async fn stored_role(state: &AppState, user_id: Uuid) -> Result<UserRole, AppError> {
+    Ok(db::get_user_by_id(&state.pool, user_id)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("user not found".to_string()))?
+        .user_role())
+}
+
+/// Decode the bearer token, rejecting requests without one
+fn require_claims(parts: &Parts, state: &AppState) -> Result<(Uuid, auth::AuthClaims), AppError> {
+    let auth_header = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("missing authorization header".to_string()))?;
+    decode_bearer(auth_header, state)
+}
+
+fn decode_bearer(
+    auth_header: &str,
+    state: &AppState,
+) -> Result<(Uuid, auth::AuthClaims), AppError> {
+    let token = auth_header
+        .strip_prefix("Bearer ")
+        .ok_or_else(|| AppError::Unauthorized("invalid authorization header".to_string()))?;
+
+    let claims = auth::decode_access_token(&state.config.auth, token)
+        .map_err(|_| AppError::Unauthorized("invalid token".to_string()))?;
+
+    let user_id = Uuid::parse_str(&claims.sub)
+        .map_err(|_| AppError::Unauthorized("invalid token subject".to_string()))?;
+
+    Ok((user_id, claims))
+}
+
 #[derive(Debug, Clone)]
 pub struct OptionalAuthUser {
     pub user_id: Option<Uuid>,
@@ -155,16 +272,7 @@ impl FromRequestParts<AppState> for OptionalAuthUser {
             None => return Ok(Self { user_id: None }),
         };
 
-        let token = auth_header
-            .strip_prefix("Bearer ")
-            .ok_or_else(|| AppError::Unauthorized("invalid authorization header".to_string()))?;
-
-        let claims = auth::decode_access_token(&state.config.auth, token)
-            .map_err(|_| AppError::Unauthorized("invalid token".to_string()))?;
-
-        let user_id = Uuid::parse_str(&claims.sub)
-            .map_err(|_| AppError::Unauthorized("invalid token subject".to_string()))?;
-
+        let (user_id, _) = decode_bearer(auth_header, state)?;
         Ok(Self {
             user_id: Some(user_id),
         })
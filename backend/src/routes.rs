@@ -5,7 +5,7 @@ use tower_http::{cors::CorsLayer, limit::RequestBodyLimitLayer, services::ServeD
 
 use crate::state::AppState;
 use crate::{
-    handlers::{analysis, auth, community, users},
+    handlers::{admin_rules, admin_users, analysis, auth, community, products, users},
     middleware,
 };
 
@@ -17,6 +17,10 @@ pub fn create_routes(state: AppState) -> Router {
         .nest("/api/v1/auth", auth::routes())
         .nest("/api/v1/users", users::routes())
         .nest("/api/v1/community", community::routes())
+        .nest("/api/v1/products", products::routes())
+        .nest("/api/v1/admin/rules", admin_rules::routes())
+        .nest("/api/v1/admin/rule-versions", admin_rules::version_routes())
+        .nest("/api/v1/admin/users", admin_users::routes())
         .nest_service("/uploads", ServeDir::new(upload_dir))
         .layer(CorsLayer::permissive())
         .layer(DefaultBodyLimit::max(10 * 1024 * 1024))
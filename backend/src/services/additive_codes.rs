@@ -0,0 +1,189 @@
+//! Additive code recognition: E-numbers, INS numbers and GB 2760 (CNS) codes
+//!
+//! E-numbers are the EU subset of the INS numbering, so `E211` and `INS 211` share
+//! one lookup key. CNS codes from GB 2760 use the `NN.NNN` format.
+
+/// Lookup key for an E-number or INS number value such as `211` or `150d`
+pub fn ins_key(value: &str) -> String {
+    format!("ins:{}", value)
+}
+
+/// Lookup key for a GB 2760 CNS code such as `17.003`
+pub fn cns_key(value: &str) -> String {
+    format!("cns:{}", value)
+}
+
+/// Normalize an E-number or INS number from rule data (`E211`, `ins 211`, `211`)
+pub fn normalize_ins(raw: &str) -> Option<String> {
+    let cleaned: String = raw
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .collect();
+    let digits = cleaned
+        .strip_prefix("ins")
+        .or_else(|| cleaned.strip_prefix('e'))
+        .unwrap_or(&cleaned);
+    let chars: Vec<char> = digits.chars().collect();
+    let (value, consumed) = read_ins_number(&chars, 0)?;
+    (consumed == chars.len() || chars[consumed] == '(').then_some(value)
+}
+
+/// Normalize a CNS code from rule data (`17.003`, `CNS 17.003`)
+pub fn normalize_cns(raw: &str) -> Option<String> {
+    let cleaned: String = raw
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    let code = cleaned.strip_prefix("cns").unwrap_or(&cleaned);
+    let code = code.trim_start_matches(['号', ':']);
+    let chars: Vec<char> = code.chars().collect();
+    let (value, consumed) = read_cns_code(&chars, 0)?;
+    (consumed == chars.len()).then_some(value)
+}
+
+/// Find every additive code in a normalized (lowercase, space-free) token and return lookup keys
+pub fn extract_code_keys(token: &str) -> Vec<String> {
+    let chars: Vec<char> = token.chars().collect();
+    let mut keys = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let at_boundary = i == 0 || !chars[i - 1].is_ascii_alphabetic();
+        if let Some((key, next)) = at_boundary.then(|| read_code_at(&chars, i)).flatten() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+            i = next;
+        } else {
+            i += 1;
+        }
+    }
+    keys
+}
+
+fn read_code_at(chars: &[char], start: usize) -> Option<(String, usize)> {
+    if starts_with(chars, start, "cns") {
+        let mut pos = start + 3;
+        while pos < chars.len() && matches!(chars[pos], '号' | ':') {
+            pos += 1;
+        }
+        let (value, next) = read_cns_code(chars, pos)?;
+        return Some((cns_key(&value), next));
+    }
+    if starts_with(chars, start, "ins") {
+        let mut pos = start + 3;
+        if pos < chars.len() && matches!(chars[pos], ':' | '-' | '.') {
+            pos += 1;
+        }
+        let (value, next) = read_ins_number(chars, pos)?;
+        return Some((ins_key(&value), next));
+    }
+    if chars[start] == 'e' {
+        let mut pos = start + 1;
+        if pos < chars.len() && chars[pos] == '-' {
+            pos += 1;
+        }
+        let (value, next) = read_ins_number(chars, pos)?;
+        return Some((ins_key(&value), next));
+    }
+    // A bare GB 2760 code is only trusted when it fills a parenthesized annotation.
+    if chars[start].is_ascii_digit() && start > 0 && chars[start - 1] == '(' {
+        let (value, next) = read_cns_code(chars, start)?;
+        if next < chars.len() && chars[next] == ')' {
+            return Some((cns_key(&value), next));
+        }
+    }
+    None
+}
+
+/// Read 3-4 digits plus an optional single-letter suffix, skipping a roman-numeral qualifier
+fn read_ins_number(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let mut pos = start;
+    while pos < chars.len() && chars[pos].is_ascii_digit() {
+        pos += 1;
+    }
+    let digit_count = pos - start;
+    if !(3..=4).contains(&digit_count) {
+        return None;
+    }
+    let mut value: String = chars[start..pos].iter().collect();
+
+    let next_is_letter = |p: usize| p < chars.len() && chars[p].is_ascii_alphabetic();
+    if next_is_letter(pos) && !next_is_letter(pos + 1) {
+        value.push(chars[pos]);
+        pos += 1;
+    }
+    if pos < chars.len() && chars[pos] == '(' {
+        let roman_end = chars[pos + 1..]
+            .iter()
+            .position(|c| !matches!(c, 'i' | 'v' | 'x'))
+            .map(|offset| pos + 1 + offset);
+        if let Some(end) = roman_end {
+            if end > pos + 1 && chars[end] == ')' {
+                pos = end + 1;
+            }
+        }
+    }
+    Some((value, pos))
+}
+
+/// Read an `NN.NNN` code not followed by further digits
+fn read_cns_code(chars: &[char], start: usize) -> Option<(String, usize)> {
+    let end = start + 6;
+    let candidate = chars.get(start..end)?;
+    let valid = candidate.iter().enumerate().all(|(i, c)| {
+        if i == 2 {
+            *c == '.'
+        } else {
+            c.is_ascii_digit()
+        }
+    });
+    if !valid || chars.get(end).is_some_and(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((candidate.iter().collect(), end))
+}
+
+fn starts_with(chars: &[char], start: usize, prefix: &str) -> bool {
+    let prefix: Vec<char> = prefix.chars().collect();
+    chars
+        .get(start..start + prefix.len())
+        .is_some_and(|slice| slice == prefix.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_codes_in_any_position() {
+        assert_eq!(extract_code_keys("e211"), vec!["ins:211"]);
+        assert_eq!(extract_code_keys("苯甲酸钠(e-211)"), vec!["ins:211"]);
+        assert_eq!(extract_code_keys("防腐剂ins202"), vec!["ins:202"]);
+        assert_eq!(extract_code_keys("cns号17.003"), vec!["cns:17.003"]);
+        assert_eq!(extract_code_keys("山梨酸钾(17.004)"), vec!["cns:17.004"]);
+        assert_eq!(
+            extract_code_keys("e150d,e160a(ii)"),
+            vec!["ins:150d", "ins:160a"]
+        );
+    }
+
+    #[test]
+    fn ignores_numbers_that_are_not_codes() {
+        assert!(extract_code_keys("白砂糖12.5%").is_empty());
+        assert!(extract_code_keys("vitamine12345").is_empty());
+        assert!(extract_code_keys("维生素e").is_empty());
+    }
+
+    #[test]
+    fn normalizes_rule_codes() {
+        assert_eq!(normalize_ins("E 211").as_deref(), Some("211"));
+        assert_eq!(normalize_ins("INS 160a(ii)").as_deref(), Some("160a"));
+        assert_eq!(normalize_ins("E21"), None);
+        assert_eq!(normalize_cns("CNS 17.003").as_deref(), Some("17.003"));
+        assert_eq!(normalize_cns("17.0031"), None);
+    }
+}
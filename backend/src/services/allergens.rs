@@ -0,0 +1,343 @@
+//! Blocker alerts for allergens the user selected
+
+use shared::{Allergen, AnalysisResult, IngredientNode, Warning};
+
+pub const ALLERGEN_WARNING_TYPE: &str = "过敏原警报";
+/// Type of the blocker saying the saved allergens could not be checked; kept apart from
+/// [`ALLERGEN_WARNING_TYPE`] so it does not count as a detected allergen
+pub const ALLERGENS_UNAVAILABLE_WARNING_TYPE: &str = "过敏原未检查";
+
+/// Ingredient name fragments that contain an allergen. Keep them specific: `蛋白` alone
+/// is any protein, so whey protein must not read as egg.
+fn keywords(allergen: Allergen) -> &'static [&'static str] {
+    match allergen {
+        Allergen::Peanut => &["花生"],
+        Allergen::Milk => &[
+            "牛奶",
+            "牛乳",
+            "羊奶",
+            "奶粉",
+            "乳粉",
+            "乳清",
+            "奶油",
+            "黄油",
+            "奶酪",
+            "乳酪",
+            "芝士",
+            "炼乳",
+            "酪蛋白",
+            "酪朊",
+            "乳糖",
+            "乳制品",
+        ],
+        Allergen::Egg => &[
+            "鸡蛋",
+            "鸭蛋",
+            "鹌鹑蛋",
+            "蛋黄",
+            "蛋清",
+            "全蛋",
+            "蛋液",
+            "蛋粉",
+        ],
+        Allergen::Gluten => &[
+            "小麦",
+            "面粉",
+            "麸质",
+            "谷朊",
+            "谷蛋白",
+            "面筋",
+            "大麦",
+            "黑麦",
+            "麦芽",
+        ],
+        Allergen::Soy => &["大豆", "黄豆", "豆粉", "豆浆", "豆腐", "酱油"],
+        Allergen::Shellfish => &[
+            "甲壳", "虾", "蟹", "贝类", "扇贝", "干贝", "牡蛎", "生蚝", "蚝油", "蛤", "蚬", "螺",
+            "鱿鱼", "墨鱼", "章鱼",
+        ],
+        Allergen::Fish => &["鱼"],
+        Allergen::Sesame => &["芝麻"],
+        Allergen::TreeNuts => &[
+            "坚果",
+            "核桃",
+            "杏仁",
+            "腰果",
+            "榛子",
+            "开心果",
+            "巴旦木",
+            "碧根果",
+            "夏威夷果",
+            "松子",
+        ],
+        Allergen::Sulfites => &["二氧化硫", "亚硫酸"],
+    }
+}
+
+/// Names that contain a keyword without containing the allergen, e.g. squid for fish
+fn exclusions(allergen: Allergen) -> &'static [&'static str] {
+    match allergen {
+        Allergen::Fish => &["鱿鱼", "墨鱼", "章鱼", "鲍鱼", "鱼腥草"],
+        Allergen::Shellfish => &["螺旋藻"],
+        // Starch and sugar derivatives, not grain: 麦芽糊精 is maltodextrin
+        Allergen::Gluten => &["麦芽糊精", "麦芽糖浆", "麦芽糖"],
+        // Arachidonic acid, a fatty acid added to infant formula
+        Allergen::Peanut => &["花生四烯酸"],
+        _ => &[],
+    }
+}
+
+/// Whether an ingredient name contains the allergen
+pub(crate) fn names_allergen(name: &str, allergen: Allergen) -> bool {
+    let mut name = name.to_string();
+    for excluded in exclusions(allergen) {
+        name = name.replace(excluded, "");
+    }
+    keywords(allergen)
+        .iter()
+        .any(|keyword| name.contains(keyword))
+}
+
+fn collect_node_names<'a>(nodes: &'a [IngredientNode], names: &mut Vec<&'a str>) {
+    for node in nodes {
+        names.push(&node.name);
+        collect_node_names(&node.children, names);
+    }
+}
+
+/// Allergen keys from a request, parsed and merged into `selected` without duplicates
+pub fn merge_keys(selected: &mut Vec<Allergen>, keys: &[String]) {
+    for allergen in keys.iter().filter_map(|key| Allergen::parse(key)) {
+        if !selected.contains(&allergen) {
+            selected.push(allergen);
+        }
+    }
+}
+
+/// Put one blocker warning per selected allergen at the front of `warnings`, naming the
+/// ingredients that contain it.
+///
+/// Rule hits tagged with the allergen are checked first, then every name in the parsed
+/// ingredient tree and the LLM's ingredient list, so an allergen the rule library does
+/// not cover is still caught.
+pub fn apply_allergen_alerts(mut result: AnalysisResult, selected: &[Allergen]) -> AnalysisResult {
+    let mut names = Vec::new();
+    collect_node_names(&result.ingredient_tree, &mut names);
+    names.extend(result.ingredients.iter().map(|item| item.name.as_str()));
+
+    let mut alerts = Vec::new();
+    for &allergen in selected {
+        let mut ingredients: Vec<String> = Vec::new();
+        let mut add = |name: &str| {
+            let name = name.trim();
+            if !name.is_empty() && !ingredients.iter().any(|known| known == name) {
+                ingredients.push(name.to_string());
+            }
+        };
+        for hit in result
+            .rule_hits
+            .iter()
+            .filter(|hit| hit.allergens.iter().any(|key| key == allergen.as_key()))
+        {
+            add(hit.matched_text.as_deref().unwrap_or(&hit.name));
+        }
+        for name in names.iter().filter(|name| names_allergen(name, allergen)) {
+            add(name);
+        }
+        if ingredients.is_empty() {
+            continue;
+        }
+        alerts.push(Warning {
+            warning_type: ALLERGEN_WARNING_TYPE.to_string(),
+            message: format!(
+                "检测到您设置的过敏原「{}」：{}，请勿食用",
+                allergen.label(),
+                ingredients.join("、")
+            ),
+            ingredients,
+            blocker: true,
+        });
+    }
+    if !alerts.is_empty() {
+        alerts.append(&mut result.warnings);
+        result.warnings = alerts;
+    }
+    result
+}
+
+/// Lead with a blocker saying the saved allergens could not be checked
+pub fn warn_allergens_unavailable(mut result: AnalysisResult) -> AnalysisResult {
+    result.warnings.insert(
+        0,
+        Warning {
+            warning_type: ALLERGENS_UNAVAILABLE_WARNING_TYPE.to_string(),
+            ingredients: Vec::new(),
+            message: "暂时无法读取您保存的过敏原设置，本次结果未按其检查，请自行核对配料表"
+                .to_string(),
+            blocker: true,
+        },
+    );
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{IngredientInfo, RuleHit};
+
+    fn hit(name: &str, matched_text: &str, allergens: &[&str]) -> RuleHit {
+        RuleHit {
+            rule_id: None,
+            name: name.to_string(),
+            category: "allergen".to_string(),
+            risk_level: "high".to_string(),
+            description: String::new(),
+            group_tags: Vec::new(),
+            evidence: None,
+            source: None,
+            matched_text: Some(matched_text.to_string()),
+            match_type: None,
+            match_score: None,
+            position: None,
+            declared_percentage: None,
+            score_impact: None,
+            allergens: allergens.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    fn result(hits: Vec<RuleHit>) -> AnalysisResult {
+        AnalysisResult {
+            health_score: 70,
+            summary: String::new(),
+            table: Vec::new(),
+            ingredients: Vec::new(),
+            warnings: vec![Warning {
+                warning_type: "添加剂".to_string(),
+                ingredients: vec!["苯甲酸钠".to_string()],
+                message: "含防腐剂".to_string(),
+                blocker: false,
+            }],
+            recommendation: String::new(),
+            overall_assessment: None,
+            focus_summary: None,
+            focus_ingredients: None,
+            score_breakdown: None,
+            rule_hits: hits,
+            confidence: None,
+            ingredient_tree: Vec::new(),
+            rule_set_version: None,
+            rule_conflicts: Vec::new(),
+            diet_verdicts: Vec::new(),
+            score_attribution: None,
+        }
+    }
+
+    #[test]
+    fn selected_allergens_become_leading_blockers() {
+        let hits = vec![
+            hit("花生", "花生碎", &["peanut"]),
+            hit("花生", "花生仁", &["peanut"]),
+            hit("牛奶", "奶粉", &["milk"]),
+        ];
+        let alerted = apply_allergen_alerts(result(hits), &[Allergen::Peanut, Allergen::Egg]);
+
+        assert_eq!(alerted.warnings.len(), 2);
+        assert!(alerted.warnings[0].blocker);
+        assert_eq!(alerted.warnings[0].ingredients, vec!["花生碎", "花生仁"]);
+        assert!(alerted.warnings[0].message.contains("花生"));
+        assert!(!alerted.warnings[1].blocker);
+    }
+
+    fn ingredient(name: &str) -> IngredientInfo {
+        IngredientInfo {
+            name: name.to_string(),
+            category: "other".to_string(),
+            risk_level: "low".to_string(),
+            description: None,
+        }
+    }
+
+    #[test]
+    fn ingredient_names_raise_blockers_without_rule_hits() {
+        let mut input = result(Vec::new());
+        input.ingredient_tree = vec![IngredientNode {
+            name: "调味料".to_string(),
+            annotations: Vec::new(),
+            position: 1,
+            percentage: None,
+            matched_rules: Vec::new(),
+            children: vec![IngredientNode {
+                name: "虾仁".to_string(),
+                annotations: Vec::new(),
+                position: 1,
+                percentage: None,
+                matched_rules: Vec::new(),
+                children: Vec::new(),
+            }],
+        }];
+        input.ingredients = vec![ingredient("蟹肉"), ingredient("鱿鱼"), ingredient("虾仁")];
+        let alerted = apply_allergen_alerts(input, &[Allergen::Shellfish, Allergen::Fish]);
+
+        assert_eq!(alerted.warnings.len(), 2);
+        assert_eq!(
+            alerted.warnings[0].ingredients,
+            vec!["虾仁", "蟹肉", "鱿鱼"]
+        );
+        assert!(!alerted.warnings[1].blocker);
+    }
+
+    #[test]
+    fn similar_names_raise_no_blockers() {
+        let mut input = result(Vec::new());
+        input.ingredients = vec![
+            ingredient("乳清蛋白粉"),
+            ingredient("二氧化碳"),
+            ingredient("荞麦"),
+            ingredient("麦芽糊精"),
+            ingredient("麦芽糖"),
+            ingredient("麦芽糖浆"),
+            ingredient("花生四烯酸"),
+        ];
+        let alerted = apply_allergen_alerts(
+            input,
+            &[
+                Allergen::Egg,
+                Allergen::Sulfites,
+                Allergen::Gluten,
+                Allergen::Peanut,
+            ],
+        );
+        assert_eq!(alerted.warnings.len(), 1);
+        assert!(names_allergen("麦芽提取物", Allergen::Gluten));
+        assert!(names_allergen("花生油", Allergen::Peanut));
+    }
+
+    #[test]
+    fn unavailable_settings_lead_with_a_blocker() {
+        let warned = warn_allergens_unavailable(result(Vec::new()));
+        assert!(warned.warnings[0].blocker);
+        assert_eq!(
+            warned.warnings[0].warning_type,
+            ALLERGENS_UNAVAILABLE_WARNING_TYPE
+        );
+        assert!(warned
+            .warnings
+            .iter()
+            .all(|warning| warning.warning_type != ALLERGEN_WARNING_TYPE));
+        assert_eq!(warned.warnings.len(), 2);
+    }
+
+    #[test]
+    fn merge_keys_skips_unknown_and_selected() {
+        let mut selected = vec![Allergen::Milk];
+        merge_keys(
+            &mut selected,
+            &[
+                "milk".to_string(),
+                "sesame".to_string(),
+                "durian".to_string(),
+            ],
+        );
+        assert_eq!(selected, vec![Allergen::Milk, Allergen::Sesame]);
+    }
+}
@@ -0,0 +1,405 @@
+//! Healthier alternatives to an analysed product
+//!
+//! Candidates are catalog products (scored by their canonical analysis) and community
+//! posts. A candidate qualifies when it falls in the same inferred category, scores
+//! higher, and contains none of the ingredients flagged in the analysed product nor the
+//! user's allergens.
+
+use shared::{Allergen, Alternative, AlternativeSource, AnalysisResult};
+use uuid::Uuid;
+
+use crate::services::allergens::names_allergen;
+use crate::services::confidence::normalize_risk;
+use crate::services::ingredient_parser::parse_ingredients;
+use crate::services::rules::normalize_token;
+
+/// Leading ingredients that decide the category; labels list ingredients by weight
+const CATEGORY_TOKENS: usize = 6;
+
+/// Flagged ingredients named in a reason
+const MAX_REASON_INGREDIENTS: usize = 3;
+
+/// Food category inferred from an ingredient list
+#[derive(Debug, PartialEq, Eq)]
+pub struct FoodCategory {
+    pub key: &'static str,
+    pub label: &'static str,
+    /// Product category names that map straight to this category
+    aliases: &'static [&'static str],
+    /// Ingredient name fragments typical of the category
+    keywords: &'static [&'static str],
+    /// Ingredient names that only count when they are the whole name
+    exact: &'static [&'static str],
+}
+
+const CATEGORIES: &[FoodCategory] = &[
+    FoodCategory {
+        key: "beverage",
+        label: "饮料",
+        aliases: &["饮料", "饮品", "果汁", "汽水", "茶饮"],
+        keywords: &[
+            "饮用水",
+            "纯净水",
+            "矿泉水",
+            "二氧化碳",
+            "浓缩果汁",
+            "浓缩汁",
+            "茶叶",
+            "茶粉",
+            "咖啡",
+        ],
+        exact: &["水"],
+    },
+    FoodCategory {
+        key: "dairy",
+        label: "乳制品",
+        aliases: &["乳制品", "牛奶", "酸奶", "奶粉", "奶酪"],
+        keywords: &[
+            "生牛乳",
+            "生乳",
+            "牛奶",
+            "乳粉",
+            "奶粉",
+            "发酵乳",
+            "乳清",
+            "干酪",
+            "奶酪",
+            "稀奶油",
+        ],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "instant_noodles",
+        label: "方便面",
+        aliases: &["方便面", "泡面", "速食面"],
+        keywords: &["面饼", "调味包", "酱包", "蔬菜包", "粉包"],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "bakery",
+        label: "饼干糕点",
+        aliases: &["饼干", "糕点", "面包", "蛋糕"],
+        keywords: &["小麦粉", "面粉", "起酥油", "酥油", "黄油", "鸡蛋", "全蛋"],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "confectionery",
+        label: "糖果巧克力",
+        aliases: &["糖果", "巧克力", "果冻", "软糖"],
+        keywords: &[
+            "可可脂",
+            "可可液块",
+            "代可可脂",
+            "巧克力",
+            "葡萄糖浆",
+            "麦芽糖浆",
+            "明胶",
+            "卡拉胶",
+        ],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "snack",
+        label: "膨化零食",
+        aliases: &["薯片", "膨化食品", "零食"],
+        keywords: &["马铃薯", "土豆", "薯粉", "玉米粉", "玉米", "大米粉"],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "meat",
+        label: "肉制品",
+        aliases: &["肉制品", "香肠", "火腿", "肉干"],
+        keywords: &["猪肉", "鸡肉", "牛肉", "鸭肉", "羊肉", "火腿", "鸡胸肉"],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "nuts",
+        label: "坚果炒货",
+        aliases: &["坚果", "炒货"],
+        keywords: &[
+            "花生",
+            "瓜子",
+            "核桃",
+            "杏仁",
+            "腰果",
+            "开心果",
+            "巴旦木",
+            "榛子",
+        ],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "cereal",
+        label: "谷物冲调",
+        aliases: &["麦片", "谷物", "冲调"],
+        keywords: &["燕麦", "麦片", "谷物", "藜麦", "黑米", "糙米"],
+        exact: &[],
+    },
+    FoodCategory {
+        key: "condiment",
+        label: "调味品",
+        aliases: &["调味品", "调味料", "酱料", "酱油"],
+        keywords: &[
+            "酱油",
+            "酿造",
+            "味精",
+            "食醋",
+            "豆瓣",
+            "鸡精",
+            "香辛料",
+            "蚝油",
+        ],
+        exact: &["食用盐"],
+    },
+];
+
+/// Category of a product from its catalog category, else from its leading ingredients.
+///
+/// Each of the first ingredients votes for the categories it is typical of, the first
+/// ingredient counting most; ties go to the category listed first.
+pub fn infer_category(
+    product_category: Option<&str>,
+    ingredient_text: &str,
+) -> Option<&'static FoodCategory> {
+    if let Some(label) = product_category
+        .map(str::trim)
+        .filter(|label| !label.is_empty())
+    {
+        if let Some(category) = CATEGORIES
+            .iter()
+            .find(|category| category.aliases.iter().any(|alias| label.contains(alias)))
+        {
+            return Some(category);
+        }
+    }
+
+    let names: Vec<String> = parse_ingredients(ingredient_text)
+        .into_iter()
+        .take(CATEGORY_TOKENS)
+        .map(|node| normalize_token(&node.name))
+        .collect();
+    let mut best: Option<(&'static FoodCategory, f32)> = None;
+    for category in CATEGORIES {
+        let score = names
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| {
+                category
+                    .keywords
+                    .iter()
+                    .any(|keyword| name.contains(keyword))
+                    || category.exact.iter().any(|exact| name == exact)
+            })
+            .map(|(index, _)| 1.0 / (1.0 + 0.5 * index as f32))
+            .sum::<f32>();
+        if score > 0.0 && best.is_none_or(|(_, known)| score > known) {
+            best = Some((category, score));
+        }
+    }
+    best.map(|(category, _)| category)
+}
+
+/// Ingredients the analysis flagged: medium or high risk rule hits, high risk rows and
+/// every ingredient a warning names
+pub fn flagged_ingredients(result: &AnalysisResult) -> Vec<String> {
+    let hits = result
+        .rule_hits
+        .iter()
+        .filter(|hit| matches!(normalize_risk(&hit.risk_level), Some("medium" | "high")))
+        .map(|hit| hit.matched_text.as_deref().unwrap_or(&hit.name));
+    let rows = result
+        .ingredients
+        .iter()
+        .filter(|row| normalize_risk(&row.risk_level) == Some("high"))
+        .map(|row| row.name.as_str());
+    let warned = result
+        .warnings
+        .iter()
+        .flat_map(|warning| warning.ingredients.iter().map(String::as_str));
+
+    let mut flagged: Vec<String> = Vec::new();
+    for name in hits.chain(rows).chain(warned) {
+        let name = name.trim();
+        if !name.is_empty() && !flagged.iter().any(|known| known == name) {
+            flagged.push(name.to_string());
+        }
+    }
+    flagged
+}
+
+/// Number of high risk entries among risk levels
+pub fn high_risk_count<'a>(risk_levels: impl IntoIterator<Item = &'a str>) -> usize {
+    risk_levels
+        .into_iter()
+        .filter(|level| normalize_risk(level) == Some("high"))
+        .count()
+}
+
+/// The analysed product alternatives are compared with
+pub struct Subject<'a> {
+    pub health_score: i32,
+    pub category: &'static FoodCategory,
+    pub flagged: &'a [String],
+    pub high_risk_count: usize,
+    /// The user's allergens; candidates containing any are skipped
+    pub allergens: &'a [Allergen],
+}
+
+/// A product or community post that may replace the subject
+pub struct Candidate {
+    pub source: AlternativeSource,
+    pub id: Uuid,
+    pub name: String,
+    pub brand: Option<String>,
+    pub product_category: Option<String>,
+    pub ingredient_text: String,
+    pub health_score: i32,
+    /// High risk ingredients, when the candidate's analysis lists risk levels
+    pub high_risk_count: Option<usize>,
+}
+
+/// Qualifying candidates, best first: highest score, then fewest high risk ingredients.
+/// Candidates with the same ingredient list are suggested once.
+pub fn rank(subject: &Subject, candidates: Vec<Candidate>, limit: usize) -> Vec<Alternative> {
+    let mut qualified: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        let text = normalize_token(&candidate.ingredient_text);
+        if candidate.health_score <= subject.health_score
+            || text.is_empty()
+            || infer_category(
+                candidate.product_category.as_deref(),
+                &candidate.ingredient_text,
+            ) != Some(subject.category)
+            || subject
+                .flagged
+                .iter()
+                .map(|name| normalize_token(name))
+                .any(|name| !name.is_empty() && text.contains(&name))
+            || subject
+                .allergens
+                .iter()
+                .any(|&allergen| names_allergen(&candidate.ingredient_text, allergen))
+            || qualified
+                .iter()
+                .any(|known| normalize_token(&known.ingredient_text) == text)
+        {
+            continue;
+        }
+        qualified.push(candidate);
+    }
+
+    qualified.sort_by_key(|candidate| {
+        (
+            -candidate.health_score,
+            candidate.high_risk_count.unwrap_or(usize::MAX),
+        )
+    });
+    qualified
+        .into_iter()
+        .take(limit)
+        .map(|candidate| Alternative {
+            reasons: reasons(subject, &candidate),
+            score_gain: candidate.health_score - subject.health_score,
+            source: candidate.source,
+            id: candidate.id,
+            name: candidate.name,
+            brand: candidate.brand,
+            health_score: candidate.health_score,
+        })
+        .collect()
+}
+
+fn reasons(subject: &Subject, candidate: &Candidate) -> Vec<String> {
+    let mut reasons = vec![format!(
+        "健康评分 {} 分，比当前产品高 {} 分",
+        candidate.health_score,
+        candidate.health_score - subject.health_score
+    )];
+    if !subject.flagged.is_empty() {
+        let mut names = subject
+            .flagged
+            .iter()
+            .take(MAX_REASON_INGREDIENTS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("、");
+        if subject.flagged.len() > MAX_REASON_INGREDIENTS {
+            names.push_str(" 等");
+        }
+        reasons.push(format!("不含当前产品中需注意的配料：{}", names));
+    }
+    if let Some(count) = candidate
+        .high_risk_count
+        .filter(|count| *count < subject.high_risk_count)
+    {
+        reasons.push(format!(
+            "高风险配料 {} 项（当前产品 {} 项）",
+            count, subject.high_risk_count
+        ));
+    }
+    if !subject.allergens.is_empty() {
+        reasons.push("未检出您设置的过敏原".to_string());
+    }
+    reasons.push(format!("同属{}", subject.category.label));
+    reasons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(name: &str, text: &str, health_score: i32) -> Candidate {
+        Candidate {
+            source: AlternativeSource::Product,
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            brand: None,
+            product_category: None,
+            ingredient_text: text.to_string(),
+            health_score,
+            high_risk_count: Some(0),
+        }
+    }
+
+    #[test]
+    fn infers_category_from_leading_ingredients() {
+        let category = infer_category(None, "配料：生牛乳，白砂糖，乳清蛋白粉").unwrap();
+        assert_eq!(category.key, "dairy");
+        let category = infer_category(None, "水，白砂糖，浓缩苹果汁，柠檬酸").unwrap();
+        assert_eq!(category.key, "beverage");
+        let category = infer_category(Some("夹心饼干"), "白砂糖").unwrap();
+        assert_eq!(category.key, "bakery");
+        assert!(infer_category(None, "白砂糖，柠檬酸").is_none());
+    }
+
+    #[test]
+    fn ranks_better_products_without_flagged_ingredients() {
+        let flagged = vec!["阿斯巴甜".to_string()];
+        let subject = Subject {
+            health_score: 40,
+            category: infer_category(None, "水，阿斯巴甜").unwrap(),
+            flagged: &flagged,
+            high_risk_count: 2,
+            allergens: &[Allergen::Milk],
+        };
+        let candidates = vec![
+            candidate("无糖可乐", "水，二氧化碳，阿斯巴甜", 70),
+            candidate("苏打水", "饮用水，二氧化碳", 85),
+            candidate("奶茶", "水，奶粉，红茶粉", 75),
+            candidate("柠檬水", "水，浓缩柠檬汁，白砂糖", 60),
+            candidate("纯牛奶", "生牛乳", 90),
+            candidate("汽水", "水，白砂糖，二氧化碳", 35),
+        ];
+
+        let ranked = rank(&subject, candidates, 5);
+
+        let names: Vec<&str> = ranked.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, vec!["苏打水", "柠檬水"]);
+        assert_eq!(ranked[0].score_gain, 45);
+        assert!(ranked[0].reasons[1].contains("阿斯巴甜"));
+        assert!(ranked[0]
+            .reasons
+            .iter()
+            .any(|reason| reason.contains("高风险配料 0 项")));
+    }
+}
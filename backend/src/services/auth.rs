@@ -9,6 +9,7 @@ use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use shared::{Permission, UserRole};
 use uuid::Uuid;
 
 use crate::config::AuthConfig;
@@ -22,6 +23,11 @@ pub struct AuthClaims {
     pub iat: usize,
     pub exp: usize,
     pub jti: String,
+    /// Tokens issued before roles existed decode as regular users.
+    #[serde(default)]
+    pub role: UserRole,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
 }
 
 pub struct TokenPair {
@@ -55,7 +61,11 @@ pub fn verify_password(hash: &str, password: &str) -> Result<bool, anyhow::Error
         .is_ok())
 }
 
-pub fn issue_tokens(config: &AuthConfig, user_id: Uuid) -> Result<TokenPair, anyhow::Error> {
+pub fn issue_tokens(
+    config: &AuthConfig,
+    user_id: Uuid,
+    role: UserRole,
+) -> Result<TokenPair, anyhow::Error> {
     let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as usize;
     let access_exp = now + (config.access_ttl_days * 24 * 3600) as usize;
     let refresh_token = Uuid::new_v4().to_string();
@@ -66,6 +76,8 @@ pub fn issue_tokens(config: &AuthConfig, user_id: Uuid) -> Result<TokenPair, any
         iat: now,
         exp: access_exp,
         jti: refresh_token.clone(),
+        role,
+        permissions: role.permissions().to_vec(),
     };
 
     let access_token = jsonwebtoken::encode(
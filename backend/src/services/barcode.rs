@@ -0,0 +1,391 @@
+//! 1D barcode (EAN-13 / UPC-A / EAN-8) decoding from uploaded photos
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::Result;
+use image::{imageops, GrayImage};
+
+/// Supported barcode symbologies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BarcodeFormat {
+    Ean13,
+    UpcA,
+    Ean8,
+}
+
+/// A decoded barcode
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Barcode {
+    pub format: BarcodeFormat,
+    /// GTIN digits; UPC-A is normalized to GTIN-13 with a leading zero
+    pub gtin: String,
+}
+
+/// Number of horizontal scanlines sampled per orientation
+const SCANLINES: u32 = 48;
+/// Minimum contrast between darkest and brightest pixel on a scanline
+const MIN_CONTRAST: u8 = 40;
+/// Maximum summed deviation (in modules) accepted for one digit
+const MAX_DIGIT_ERROR: f32 = 1.6;
+/// Downscale very large photos before scanning
+const MAX_SCAN_WIDTH: u32 = 1600;
+
+/// L-code widths (space, bar, space, bar); R-codes use the same widths starting with a bar
+const L_PATTERNS: [[u8; 4]; 10] = [
+    [3, 2, 1, 1],
+    [2, 2, 2, 1],
+    [2, 1, 2, 2],
+    [1, 4, 1, 1],
+    [1, 1, 3, 2],
+    [1, 2, 3, 1],
+    [1, 1, 1, 4],
+    [1, 3, 1, 2],
+    [1, 2, 1, 3],
+    [3, 1, 1, 2],
+];
+
+/// Parity of the six left-hand digits (true = G-code) keyed by the implied first digit
+const FIRST_DIGIT_PARITY: [[bool; 6]; 10] = [
+    [false, false, false, false, false, false],
+    [false, false, true, false, true, true],
+    [false, false, true, true, false, true],
+    [false, false, true, true, true, false],
+    [false, true, false, false, true, true],
+    [false, true, true, false, false, true],
+    [false, true, true, true, false, false],
+    [false, true, false, true, false, true],
+    [false, true, false, true, true, false],
+    [false, true, true, false, true, false],
+];
+
+/// Decode a barcode from an image file on disk
+pub fn decode_file(path: &Path) -> Result<Option<Barcode>> {
+    let bytes = std::fs::read(path)?;
+    decode_bytes(&bytes)
+}
+
+/// Decode a barcode from encoded image bytes
+pub fn decode_bytes(bytes: &[u8]) -> Result<Option<Barcode>> {
+    let image = image::load_from_memory(bytes)?;
+    let mut gray = image.to_luma8();
+    if gray.width() > MAX_SCAN_WIDTH {
+        let height = gray.height() * MAX_SCAN_WIDTH / gray.width();
+        gray = imageops::resize(
+            &gray,
+            MAX_SCAN_WIDTH,
+            height,
+            imageops::FilterType::Triangle,
+        );
+    }
+    Ok(decode_luma(&gray))
+}
+
+/// Scan horizontal and vertical lines and return the most frequently decoded barcode
+pub fn decode_luma(image: &GrayImage) -> Option<Barcode> {
+    let mut votes: HashMap<Barcode, usize> = HashMap::new();
+    for oriented in [image.clone(), imageops::rotate90(image)] {
+        for line in scanlines(&oriented) {
+            let runs = run_lengths(&line);
+            if let Some(barcode) = decode_runs(&runs).or_else(|| decode_runs(&reverse_runs(&runs)))
+            {
+                *votes.entry(barcode).or_default() += 1;
+            }
+        }
+    }
+
+    votes
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then_with(|| b.gtin.cmp(&a.gtin)))
+        .map(|(barcode, _)| barcode)
+}
+
+/// Reverse runs for upside-down barcodes, keeping a light run first
+fn reverse_runs(runs: &[u32]) -> Vec<u32> {
+    let mut reversed: Vec<u32> = runs.iter().rev().copied().collect();
+    if runs.len().is_multiple_of(2) {
+        reversed.insert(0, 0);
+    }
+    reversed
+}
+
+fn scanlines(image: &GrayImage) -> Vec<Vec<u8>> {
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let step = (height / SCANLINES).max(1);
+    (step / 2..height)
+        .step_by(step as usize)
+        .map(|y| (0..width).map(|x| image.get_pixel(x, y)[0]).collect())
+        .collect()
+}
+
+/// Binarize a scanline and convert it to alternating run lengths.
+///
+/// The first run is always light (quiet zone), so odd indices are bars.
+fn run_lengths(line: &[u8]) -> Vec<u32> {
+    let (Some(&min), Some(&max)) = (line.iter().min(), line.iter().max()) else {
+        return Vec::new();
+    };
+    if max - min < MIN_CONTRAST {
+        return Vec::new();
+    }
+    let threshold = ((min as u16 + max as u16) / 2) as u8;
+
+    let mut runs = vec![0u32];
+    let mut dark = false;
+    for &pixel in line {
+        let is_dark = pixel < threshold;
+        if is_dark != dark {
+            runs.push(0);
+            dark = is_dark;
+        }
+        *runs.last_mut().unwrap() += 1;
+    }
+    runs
+}
+
+fn decode_runs(runs: &[u32]) -> Option<Barcode> {
+    // Bars sit at odd indices because run_lengths always starts with a light run.
+    for start in (1..runs.len()).step_by(2) {
+        if let Some(barcode) = decode_ean13_at(runs, start).or_else(|| decode_ean8_at(runs, start))
+        {
+            return Some(barcode);
+        }
+    }
+    None
+}
+
+fn decode_ean13_at(runs: &[u32], start: usize) -> Option<Barcode> {
+    let window = runs.get(start..start + 59)?;
+    let module = window.iter().sum::<u32>() as f32 / 95.0;
+    if !has_quiet_zone(runs, start, module)
+        || !is_guard(&window[0..3], module)
+        || !is_guard(&window[27..32], module)
+        || !is_guard(&window[56..59], module)
+    {
+        return None;
+    }
+
+    let mut digits = Vec::with_capacity(13);
+    let mut parity = [false; 6];
+    for (index, chunk) in window[3..27].chunks(4).enumerate() {
+        let (digit, is_g) = match_left_digit(chunk)?;
+        parity[index] = is_g;
+        digits.push(digit);
+    }
+    for chunk in window[32..56].chunks(4) {
+        digits.push(match_digit(chunk)?);
+    }
+
+    let first = FIRST_DIGIT_PARITY.iter().position(|p| *p == parity)? as u8;
+    digits.insert(0, first);
+    if !checksum_valid(&digits) {
+        return None;
+    }
+
+    let gtin: String = digits.iter().map(|d| char::from(b'0' + d)).collect();
+    let format = if first == 0 {
+        BarcodeFormat::UpcA
+    } else {
+        BarcodeFormat::Ean13
+    };
+    Some(Barcode { format, gtin })
+}
+
+fn decode_ean8_at(runs: &[u32], start: usize) -> Option<Barcode> {
+    let window = runs.get(start..start + 43)?;
+    let module = window.iter().sum::<u32>() as f32 / 67.0;
+    if !has_quiet_zone(runs, start, module)
+        || !is_guard(&window[0..3], module)
+        || !is_guard(&window[19..24], module)
+        || !is_guard(&window[40..43], module)
+    {
+        return None;
+    }
+
+    let mut digits = Vec::with_capacity(8);
+    for chunk in window[3..19].chunks(4) {
+        digits.push(match_digit(chunk)?);
+    }
+    for chunk in window[24..40].chunks(4) {
+        digits.push(match_digit(chunk)?);
+    }
+    if !checksum_valid(&digits) {
+        return None;
+    }
+
+    Some(Barcode {
+        format: BarcodeFormat::Ean8,
+        gtin: digits.iter().map(|d| char::from(b'0' + d)).collect(),
+    })
+}
+
+/// Require a light margin before the start guard so digits inside text are not mistaken for guards
+fn has_quiet_zone(runs: &[u32], start: usize, module: f32) -> bool {
+    runs[start - 1] as f32 >= module * 5.0
+}
+
+fn is_guard(runs: &[u32], module: f32) -> bool {
+    runs.iter().all(|&width| {
+        let ratio = width as f32 / module;
+        (0.4..=1.8).contains(&ratio)
+    })
+}
+
+/// Match a left-hand digit against both L and G codes, returning the digit and whether it was G
+fn match_left_digit(runs: &[u32]) -> Option<(u8, bool)> {
+    let l = best_match(runs, false);
+    let g = best_match(runs, true);
+    match (l, g) {
+        (Some((ld, le)), Some((gd, ge))) => Some(if le <= ge { (ld, false) } else { (gd, true) }),
+        (Some((ld, _)), None) => Some((ld, false)),
+        (None, Some((gd, _))) => Some((gd, true)),
+        (None, None) => None,
+    }
+}
+
+fn match_digit(runs: &[u32]) -> Option<u8> {
+    best_match(runs, false).map(|(digit, _)| digit)
+}
+
+fn best_match(runs: &[u32], reversed: bool) -> Option<(u8, f32)> {
+    let total: u32 = runs.iter().sum();
+    if total == 0 {
+        return None;
+    }
+    let scale = 7.0 / total as f32;
+
+    let mut best: Option<(u8, f32)> = None;
+    for (digit, pattern) in L_PATTERNS.iter().enumerate() {
+        let error: f32 = runs
+            .iter()
+            .enumerate()
+            .map(|(i, &width)| {
+                let expected = if reversed { pattern[3 - i] } else { pattern[i] };
+                (width as f32 * scale - expected as f32).abs()
+            })
+            .sum();
+        let improves = match best {
+            Some((_, best_error)) => error < best_error,
+            None => true,
+        };
+        if error <= MAX_DIGIT_ERROR && improves {
+            best = Some((digit as u8, error));
+        }
+    }
+    best
+}
+
+/// Normalize user-supplied GTIN text: digits only, UPC-A padded to GTIN-13, check digit verified
+pub fn normalize_gtin(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if !trimmed.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let gtin = match trimmed.len() {
+        8 | 13 | 14 => trimmed.to_string(),
+        12 => format!("0{}", trimmed),
+        _ => return None,
+    };
+    let digits: Vec<u8> = gtin.bytes().map(|b| b - b'0').collect();
+    checksum_valid(&digits).then_some(gtin)
+}
+
+/// Validate the GS1 mod-10 check digit (last digit)
+pub fn checksum_valid(digits: &[u8]) -> bool {
+    let Some((&check, body)) = digits.split_last() else {
+        return false;
+    };
+    let sum: u32 = body
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| d as u32 * if i % 2 == 0 { 3 } else { 1 })
+        .sum();
+    (10 - sum % 10) % 10 == check as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+
+    /// Render an EAN-13 as module widths (bar/space alternating, starting with a bar)
+    fn ean13_modules(code: &str) -> Vec<u8> {
+        let digits: Vec<usize> = code.bytes().map(|b| (b - b'0') as usize).collect();
+        let parity = FIRST_DIGIT_PARITY[digits[0]];
+        let mut widths = vec![1, 1, 1];
+        for (i, &d) in digits[1..7].iter().enumerate() {
+            let mut pattern = L_PATTERNS[d];
+            if parity[i] {
+                pattern.reverse();
+            }
+            widths.extend_from_slice(&pattern);
+        }
+        widths.extend_from_slice(&[1, 1, 1, 1, 1]);
+        for &d in &digits[7..] {
+            widths.extend_from_slice(&L_PATTERNS[d]);
+        }
+        widths.extend_from_slice(&[1, 1, 1]);
+        widths
+    }
+
+    fn render(widths: &[u8], scale: u32, height: u32) -> GrayImage {
+        let quiet = 12 * scale;
+        let total: u32 = widths.iter().map(|&w| w as u32 * scale).sum::<u32>() + quiet * 2;
+        let mut image = GrayImage::from_pixel(total, height, Luma([255]));
+        let mut x = quiet;
+        for (i, &w) in widths.iter().enumerate() {
+            let color = if i % 2 == 0 { 20 } else { 255 };
+            for dx in 0..w as u32 * scale {
+                for y in 0..height {
+                    image.put_pixel(x + dx, y, Luma([color]));
+                }
+            }
+            x += w as u32 * scale;
+        }
+        image
+    }
+
+    #[test]
+    fn checksum_accepts_valid_gtin() {
+        let digits: Vec<u8> = "6901234567892".bytes().map(|b| b - b'0').collect();
+        assert!(checksum_valid(&digits));
+        let digits: Vec<u8> = "6901234567891".bytes().map(|b| b - b'0').collect();
+        assert!(!checksum_valid(&digits));
+    }
+
+    #[test]
+    fn normalize_gtin_pads_upca_and_rejects_bad_input() {
+        assert_eq!(
+            normalize_gtin(" 036000291452 ").as_deref(),
+            Some("0036000291452")
+        );
+        assert_eq!(normalize_gtin("6901234567891"), None);
+        assert_eq!(normalize_gtin("69012345a7892"), None);
+    }
+
+    #[test]
+    fn decodes_rendered_ean13() {
+        let image = render(&ean13_modules("6901234567892"), 3, 40);
+        let barcode = decode_luma(&image).expect("barcode");
+        assert_eq!(barcode.gtin, "6901234567892");
+        assert_eq!(barcode.format, BarcodeFormat::Ean13);
+    }
+
+    #[test]
+    fn decodes_rotated_upca() {
+        let image = render(&ean13_modules("0036000291452"), 2, 30);
+        let barcode = decode_luma(&imageops::rotate270(&image)).expect("barcode");
+        assert_eq!(barcode.gtin, "0036000291452");
+        assert_eq!(barcode.format, BarcodeFormat::UpcA);
+    }
+
+    #[test]
+    fn blank_image_has_no_barcode() {
+        let image = GrayImage::from_pixel(200, 100, Luma([255]));
+        assert!(decode_luma(&image).is_none());
+    }
+}
@@ -0,0 +1,405 @@
+//! Confidence model for analysis results
+//!
+//! Combines independent signals about how trustworthy a result is. Each signal adds a
+//! `ConfidenceFactor` whose score moves the total up or down from a neutral base.
+
+use shared::{ConfidenceFactor, ConfidenceInfo, IngredientInfo, IngredientNode, RuleHit};
+
+use crate::services::rule_matcher::levenshtein;
+use crate::services::rules::names_match;
+
+const BASE_SCORE: i32 = 60;
+
+/// Longest text compared for the edit distance; longer texts are truncated
+const MAX_EDIT_COMPARE_CHARS: usize = 2000;
+
+/// Inputs to the confidence model
+#[derive(Debug, Clone, Copy)]
+pub struct ConfidenceSignals<'a> {
+    /// Mean OCR line confidence between 0 and 1
+    pub ocr_confidence: Option<f32>,
+    /// Text as recognized by OCR
+    pub ocr_text: Option<&'a str>,
+    /// Text confirmed by the user and analyzed
+    pub confirmed_text: &'a str,
+    /// Parsed ingredient tree with matched rules
+    pub ingredients: &'a [IngredientNode],
+    pub hits: &'a [RuleHit],
+    /// Ingredients as returned by the LLM
+    pub llm_ingredients: &'a [IngredientInfo],
+    /// Set when the rule library could not be loaded
+    pub rules_error: Option<&'a str>,
+}
+
+/// Score every signal and derive the overall confidence level
+pub fn assess(signals: &ConfidenceSignals) -> ConfidenceInfo {
+    let mut factors = Vec::new();
+    let mut reasons = Vec::new();
+
+    if let Some(error) = signals.rules_error {
+        factors.push(factor(
+            "rule_engine",
+            "规则引擎",
+            -30,
+            "规则库加载失败".to_string(),
+        ));
+        reasons.push(format!("规则库不可用：{}", error));
+    }
+    if let Some(confidence) = signals.ocr_confidence {
+        factors.push(ocr_factor(confidence, &mut reasons));
+    }
+    factors.push(coverage_factor(signals, &mut reasons));
+    if let Some(agreement) = agreement_factor(signals.hits, signals.llm_ingredients, &mut reasons) {
+        factors.push(agreement);
+    }
+    if let Some(quality) = match_quality_factor(signals.hits, &mut reasons) {
+        factors.push(quality);
+    }
+    if let Some(ocr_text) = signals.ocr_text.filter(|text| !text.trim().is_empty()) {
+        factors.push(edit_factor(ocr_text, signals.confirmed_text, &mut reasons));
+    }
+
+    let score = BASE_SCORE + factors.iter().map(|factor| factor.score).sum::<i32>();
+    let level = if score >= 75 {
+        "high"
+    } else if score >= 50 {
+        "medium"
+    } else {
+        "low"
+    };
+    if reasons.is_empty() {
+        reasons.push("各项信号一致，结果可信".to_string());
+    }
+
+    ConfidenceInfo {
+        level: level.to_string(),
+        reasons,
+        factors,
+    }
+}
+
+fn ocr_factor(confidence: f32, reasons: &mut Vec<String>) -> ConfidenceFactor {
+    let (score, label) = if confidence >= 0.9 {
+        (10, "OCR 识别置信度高")
+    } else if confidence >= 0.75 {
+        (0, "OCR 识别置信度一般")
+    } else {
+        reasons.push("OCR 识别置信度低，建议核对原图".to_string());
+        (-20, "OCR 识别置信度低")
+    };
+    factor(
+        "ocr_confidence",
+        "OCR 置信度",
+        score,
+        format!("{}（{:.2}）", label, confidence),
+    )
+}
+
+/// Share of parsed ingredients that a rule matched or the LLM recognized
+fn coverage_factor(signals: &ConfidenceSignals, reasons: &mut Vec<String>) -> ConfidenceFactor {
+    let mut leaves = Vec::new();
+    collect_leaves(signals.ingredients, &mut leaves);
+    if leaves.is_empty() {
+        reasons.push("未能解析出配料列表".to_string());
+        return factor(
+            "token_coverage",
+            "配料识别率",
+            -20,
+            "未解析出配料".to_string(),
+        );
+    }
+
+    let recognized = leaves
+        .iter()
+        .filter(|node| {
+            !node.matched_rules.is_empty()
+                || signals
+                    .llm_ingredients
+                    .iter()
+                    .any(|ingredient| names_match(&node.name, &ingredient.name))
+        })
+        .count();
+    let ratio = recognized as f32 / leaves.len() as f32;
+    let score = if ratio >= 0.8 {
+        15
+    } else if ratio >= 0.5 {
+        0
+    } else {
+        reasons.push("大部分配料未被规则或模型识别".to_string());
+        -15
+    };
+    factor(
+        "token_coverage",
+        "配料识别率",
+        score,
+        format!(
+            "识别 {}/{} 项配料（{:.0}%）",
+            recognized,
+            leaves.len(),
+            ratio * 100.0
+        ),
+    )
+}
+
+/// Whether rules and the LLM rate the same ingredients at the same risk level
+fn agreement_factor(
+    hits: &[RuleHit],
+    llm_ingredients: &[IngredientInfo],
+    reasons: &mut Vec<String>,
+) -> Option<ConfidenceFactor> {
+    let mut compared = 0;
+    let mut agreed = 0;
+    for hit in hits {
+        let Some(ingredient) = llm_ingredients.iter().find(|ingredient| {
+            names_match(&hit.name, &ingredient.name)
+                || hit
+                    .matched_text
+                    .as_deref()
+                    .is_some_and(|text| names_match(text, &ingredient.name))
+        }) else {
+            continue;
+        };
+        let (Some(rule_risk), Some(llm_risk)) = (
+            normalize_risk(&hit.risk_level),
+            normalize_risk(&ingredient.risk_level),
+        ) else {
+            continue;
+        };
+        compared += 1;
+        if rule_risk == llm_risk {
+            agreed += 1;
+        }
+    }
+    if compared == 0 {
+        return None;
+    }
+
+    let ratio = agreed as f32 / compared as f32;
+    let (score, label) = if ratio >= 0.8 {
+        (10, "规则与模型风险判断一致")
+    } else if ratio >= 0.5 {
+        (0, "部分成分风险判断不一致")
+    } else {
+        reasons.push("规则与模型对成分风险判断分歧较大".to_string());
+        (-15, "规则与模型风险判断分歧较大")
+    };
+    Some(factor(
+        "rule_llm_agreement",
+        "规则与模型一致性",
+        score,
+        format!("{}（一致 {}/{}）", label, agreed, compared),
+    ))
+}
+
+fn match_quality_factor(hits: &[RuleHit], reasons: &mut Vec<String>) -> Option<ConfidenceFactor> {
+    let scores: Vec<f32> = hits.iter().filter_map(|hit| hit.match_score).collect();
+    if scores.is_empty() {
+        return None;
+    }
+    let quality = scores.iter().sum::<f32>() / scores.len() as f32;
+    let (score, label) = if quality >= 0.95 {
+        (5, "规则均为精确匹配")
+    } else if quality >= 0.8 {
+        (0, "部分规则为包含匹配")
+    } else {
+        reasons.push("存在模糊匹配，建议核对配料文本".to_string());
+        (-10, "存在模糊匹配")
+    };
+    Some(factor(
+        "match_quality",
+        "匹配质量",
+        score,
+        format!("{}（平均 {:.2}）", label, quality),
+    ))
+}
+
+/// How much the user changed the OCR text before confirming it
+fn edit_factor(
+    ocr_text: &str,
+    confirmed_text: &str,
+    reasons: &mut Vec<String>,
+) -> ConfidenceFactor {
+    let ratio = edit_ratio(ocr_text, confirmed_text);
+    let (score, label) = if ratio <= 0.1 {
+        (5, "基本未修改 OCR 文本")
+    } else if ratio <= 0.4 {
+        (0, "修改了部分 OCR 文本")
+    } else {
+        reasons.push("用户大幅修改了 OCR 文本，结果未经图片核验".to_string());
+        (-15, "大幅修改了 OCR 文本")
+    };
+    factor(
+        "user_edits",
+        "用户修改",
+        score,
+        format!("{}（改动约 {:.0}%）", label, ratio * 100.0),
+    )
+}
+
+/// Character edit distance between the texts, relative to the longer one; whitespace is
+/// ignored
+fn edit_ratio(before: &str, after: &str) -> f32 {
+    let before: Vec<char> = before
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take(MAX_EDIT_COMPARE_CHARS)
+        .collect();
+    let after: Vec<char> = after
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take(MAX_EDIT_COMPARE_CHARS)
+        .collect();
+    let longest = before.len().max(after.len());
+    if longest == 0 {
+        return 0.0;
+    }
+    levenshtein(&before, &after) as f32 / longest as f32
+}
+
+fn collect_leaves<'a>(nodes: &'a [IngredientNode], leaves: &mut Vec<&'a IngredientNode>) {
+    for node in nodes {
+        if node.children.is_empty() {
+            leaves.push(node);
+        } else {
+            collect_leaves(&node.children, leaves);
+        }
+    }
+}
+
+pub(crate) fn normalize_risk(value: &str) -> Option<&'static str> {
+    match value.trim().to_lowercase().as_str() {
+        "low" | "低" => Some("low"),
+        "medium" | "中" => Some("medium"),
+        "high" | "高" => Some("high"),
+        _ => None,
+    }
+}
+
+fn factor(key: &str, label: &str, score: i32, detail: String) -> ConfidenceFactor {
+    ConfidenceFactor {
+        key: key.to_string(),
+        label: label.to_string(),
+        score,
+        detail: Some(detail),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, matched: &[&str]) -> IngredientNode {
+        IngredientNode {
+            name: name.to_string(),
+            annotations: Vec::new(),
+            position: 1,
+            percentage: None,
+            matched_rules: matched.iter().map(|rule| rule.to_string()).collect(),
+            children: Vec::new(),
+        }
+    }
+
+    fn hit(name: &str, risk_level: &str) -> RuleHit {
+        RuleHit {
+            rule_id: None,
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: risk_level.to_string(),
+            description: String::new(),
+            group_tags: Vec::new(),
+            evidence: None,
+            source: None,
+            matched_text: Some(name.to_string()),
+            match_type: Some("exact".to_string()),
+            match_score: Some(1.0),
+            position: None,
+            declared_percentage: None,
+            score_impact: None,
+            allergens: Vec::new(),
+        }
+    }
+
+    fn llm(name: &str, risk_level: &str) -> IngredientInfo {
+        IngredientInfo {
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: risk_level.to_string(),
+            description: None,
+        }
+    }
+
+    fn score_of(info: &ConfidenceInfo, key: &str) -> Option<i32> {
+        info.factors
+            .iter()
+            .find(|factor| factor.key == key)
+            .map(|factor| factor.score)
+    }
+
+    #[test]
+    fn consistent_signals_give_high_confidence() {
+        let ingredients = vec![node("水", &[]), node("山梨酸钾", &["山梨酸钾"])];
+        let hits = vec![hit("山梨酸钾", "medium")];
+        let llm_ingredients = vec![llm("水", "low"), llm("山梨酸钾", "medium")];
+        let info = assess(&ConfidenceSignals {
+            ocr_confidence: Some(0.96),
+            ocr_text: Some("配料：水、山梨酸钾"),
+            confirmed_text: "配料：水、山梨酸钾",
+            ingredients: &ingredients,
+            hits: &hits,
+            llm_ingredients: &llm_ingredients,
+            rules_error: None,
+        });
+
+        assert_eq!(info.level, "high");
+        assert_eq!(score_of(&info, "token_coverage"), Some(15));
+        assert_eq!(score_of(&info, "rule_llm_agreement"), Some(10));
+        assert_eq!(score_of(&info, "user_edits"), Some(5));
+    }
+
+    #[test]
+    fn poor_ocr_and_heavy_edits_give_low_confidence() {
+        let ingredients = vec![node("小麦粉", &[]), node("食用盐", &[]), node("水", &[])];
+        let info = assess(&ConfidenceSignals {
+            ocr_confidence: Some(0.52),
+            ocr_text: Some("西己米斗：刁、麦?"),
+            confirmed_text: "配料：小麦粉、食用盐、水",
+            ingredients: &ingredients,
+            hits: &[],
+            llm_ingredients: &[],
+            rules_error: None,
+        });
+
+        assert_eq!(info.level, "low");
+        assert_eq!(score_of(&info, "ocr_confidence"), Some(-20));
+        assert_eq!(score_of(&info, "user_edits"), Some(-15));
+        assert_eq!(score_of(&info, "rule_llm_agreement"), None);
+        assert_eq!(info.reasons.len(), 3);
+    }
+
+    #[test]
+    fn flags_rule_and_llm_disagreement() {
+        let hits = vec![hit("亚硝酸钠", "high"), hit("苯甲酸钠", "medium")];
+        let llm_ingredients = vec![llm("亚硝酸钠", "low"), llm("苯甲酸钠（防腐剂）", "低")];
+        let info = assess(&ConfidenceSignals {
+            ocr_confidence: None,
+            ocr_text: None,
+            confirmed_text: "亚硝酸钠、苯甲酸钠",
+            ingredients: &[
+                node("亚硝酸钠", &["亚硝酸钠"]),
+                node("苯甲酸钠", &["苯甲酸钠"]),
+            ],
+            hits: &hits,
+            llm_ingredients: &llm_ingredients,
+            rules_error: None,
+        });
+
+        assert_eq!(score_of(&info, "rule_llm_agreement"), Some(-15));
+        assert_eq!(score_of(&info, "ocr_confidence"), None);
+    }
+
+    #[test]
+    fn edit_ratio_ignores_whitespace() {
+        assert_eq!(edit_ratio("白砂糖 、水", "白砂糖、水"), 0.0);
+        assert!((edit_ratio("白沙糖、水", "白砂糖、水") - 0.2).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,126 @@
+//! Diet compatibility verdicts
+//!
+//! Rules mark ingredients with diet tags (`not_vegan`, `maybe_not_halal`, ...). Every
+//! ingredient matched by a tagged rule is classified against each selected diet: a
+//! `not_` tag rules the diet out, a `maybe_not_` tag leaves it uncertain.
+
+use shared::{DietRegime, DietStatus, DietVerdict, RuleHit};
+
+/// Diet keys from a request, parsed and merged into `selected` without duplicates
+pub fn merge_keys(selected: &mut Vec<DietRegime>, keys: &[String]) {
+    for diet in keys.iter().filter_map(|key| DietRegime::parse(key)) {
+        if !selected.contains(&diet) {
+            selected.push(diet);
+        }
+    }
+}
+
+/// One verdict per selected diet, in the order selected.
+///
+/// `diet_hits` are the matches of rules carrying diet tags. Without a rule library no
+/// ingredient can be classified, so every verdict is uncertain.
+pub fn evaluate(
+    diets: &[DietRegime],
+    diet_hits: &[RuleHit],
+    rules_unavailable: bool,
+) -> Vec<DietVerdict> {
+    diets
+        .iter()
+        .map(|&diet| {
+            if rules_unavailable {
+                return DietVerdict {
+                    diet,
+                    status: DietStatus::Uncertain,
+                    ingredients: Vec::new(),
+                };
+            }
+            let excluded = ingredient_names(diet_hits, |tags| diet.excluded_by(tags));
+            if !excluded.is_empty() {
+                return DietVerdict {
+                    diet,
+                    status: DietStatus::Incompatible,
+                    ingredients: excluded,
+                };
+            }
+            let questioned = ingredient_names(diet_hits, |tags| diet.questioned_by(tags));
+            DietVerdict {
+                diet,
+                status: if questioned.is_empty() {
+                    DietStatus::Compatible
+                } else {
+                    DietStatus::Uncertain
+                },
+                ingredients: questioned,
+            }
+        })
+        .collect()
+}
+
+/// Label text of the hits whose tags satisfy `matches`, without duplicates
+fn ingredient_names(hits: &[RuleHit], matches: impl Fn(&[String]) -> bool) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for hit in hits.iter().filter(|hit| matches(&hit.group_tags)) {
+        let name = hit.matched_text.as_deref().unwrap_or(&hit.name).trim();
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(name: &str, matched_text: &str, tags: &[&str]) -> RuleHit {
+        RuleHit {
+            rule_id: None,
+            name: name.to_string(),
+            category: "diet".to_string(),
+            risk_level: "low".to_string(),
+            description: String::new(),
+            group_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            evidence: None,
+            source: None,
+            matched_text: Some(matched_text.to_string()),
+            match_type: None,
+            match_score: None,
+            position: None,
+            declared_percentage: None,
+            score_impact: None,
+            allergens: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn classifies_each_selected_diet() {
+        let hits = vec![
+            hit("猪肉", "猪油", &["not_vegetarian", "not_halal"]),
+            hit("明胶", "明胶", &["not_vegetarian", "maybe_not_halal"]),
+            hit("牛奶", "奶粉", &["not_vegan"]),
+        ];
+        let verdicts = evaluate(
+            &[DietRegime::Vegan, DietRegime::Halal, DietRegime::GlutenFree],
+            &hits,
+            false,
+        );
+
+        assert_eq!(verdicts[0].status, DietStatus::Incompatible);
+        assert_eq!(verdicts[0].ingredients, vec!["猪油", "明胶", "奶粉"]);
+        assert_eq!(verdicts[1].status, DietStatus::Incompatible);
+        assert_eq!(verdicts[1].ingredients, vec!["猪油"]);
+        assert_eq!(verdicts[2].status, DietStatus::Compatible);
+        assert!(verdicts[2].ingredients.is_empty());
+    }
+
+    #[test]
+    fn doubtful_ingredients_leave_the_diet_uncertain() {
+        let hits = vec![hit("燕麦", "燕麦片", &["maybe_not_gluten_free"])];
+        let verdicts = evaluate(&[DietRegime::GlutenFree], &hits, false);
+        assert_eq!(verdicts[0].status, DietStatus::Uncertain);
+        assert_eq!(verdicts[0].ingredients, vec!["燕麦片"]);
+
+        let verdicts = evaluate(&[DietRegime::Keto], &[], true);
+        assert_eq!(verdicts[0].status, DietStatus::Uncertain);
+    }
+}
@@ -0,0 +1,296 @@
+//! Ingredient list parsing
+//!
+//! Labels nest compound ingredients in brackets, e.g. `复合调味料（食用盐、白砂糖、味精）`,
+//! and annotate single ingredients with their function class or additive code, e.g.
+//! `山梨酸钾（防腐剂）`. The parser keeps that structure as a tree instead of flattening
+//! the list on every separator.
+
+use shared::IngredientNode;
+
+use crate::services::additive_codes;
+
+/// Parse an ingredient list into top-level nodes with nested sub-ingredients
+pub fn parse_ingredients(text: &str) -> Vec<IngredientNode> {
+    let cleaned = text
+        .replace("配料表", "")
+        .replace("配料", "")
+        .replace('：', ":");
+    let cleaned = cleaned.trim_start_matches([':', ' ', '\t', '\n']);
+    let chars: Vec<char> = cleaned.chars().collect();
+    parse_list(&chars)
+}
+
+fn parse_list(chars: &[char]) -> Vec<IngredientNode> {
+    let mut nodes: Vec<IngredientNode> = split_top_level(chars)
+        .into_iter()
+        .filter_map(parse_item)
+        .collect();
+    for (index, node) in nodes.iter_mut().enumerate() {
+        node.position = index as u32 + 1;
+    }
+    nodes
+}
+
+/// Split on list separators that are not inside brackets
+fn split_top_level(chars: &[char]) -> Vec<&[char]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in chars.iter().enumerate() {
+        if is_open(*c) {
+            depth += 1;
+        } else if is_close(*c) {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && is_separator(*c) {
+            parts.push(&chars[start..i]);
+            start = i + 1;
+        }
+    }
+    parts.push(&chars[start..]);
+    parts
+}
+
+fn parse_item(chars: &[char]) -> Option<IngredientNode> {
+    let mut name = String::new();
+    let mut groups: Vec<&[char]> = Vec::new();
+    let mut depth = 0usize;
+    let mut group_start = 0;
+    for (i, c) in chars.iter().enumerate() {
+        if is_open(*c) {
+            if depth == 0 {
+                group_start = i + 1;
+            }
+            depth += 1;
+        } else if is_close(*c) {
+            match depth {
+                // Stray closing bracket from OCR noise.
+                0 => {}
+                1 => {
+                    depth = 0;
+                    groups.push(&chars[group_start..i]);
+                }
+                _ => depth -= 1,
+            }
+        } else if depth == 0 {
+            name.push(*c);
+        }
+    }
+    if depth > 0 {
+        // Unclosed bracket: everything after it is still the group content.
+        groups.push(&chars[group_start..]);
+    }
+
+    let (name, declared) = take_percentage(&name);
+    let mut percentage = declared;
+    let mut annotations = Vec::new();
+    let mut children = Vec::new();
+    for group in groups {
+        let has_list = split_top_level(group).len() > 1;
+        if has_list {
+            children.extend(parse_list(group));
+            continue;
+        }
+        let text: String = group.iter().collect();
+        let text = text.trim();
+        if text.is_empty() {
+            continue;
+        }
+        if is_annotation(text) {
+            let (rest, declared) = take_percentage(text);
+            if declared.is_some() {
+                percentage = percentage.or(declared);
+                if rest.is_empty() {
+                    continue;
+                }
+            }
+            annotations.push(rest);
+        } else {
+            children.extend(parse_list(group));
+        }
+    }
+
+    // `增稠剂:黄原胶` names the function class before the ingredient.
+    let mut name = name.trim().to_string();
+    if let Some((label, rest)) = name.split_once(':') {
+        let (label, rest) = (label.trim().to_string(), rest.trim().to_string());
+        if !label.is_empty() && !rest.is_empty() {
+            annotations.insert(0, label);
+            name = rest;
+        }
+    }
+    let name = name.trim_matches([':', '.', '。', '*']).trim().to_string();
+
+    if name.is_empty() {
+        // `(食用盐、白砂糖)` with no leading name: keep the content rather than dropping it.
+        return match children.len() {
+            0 => None,
+            1 if annotations.is_empty() => children.pop(),
+            _ => Some(IngredientNode {
+                name: annotations.first().cloned().unwrap_or_default(),
+                annotations: annotations.into_iter().skip(1).collect(),
+                position: 0,
+                percentage,
+                matched_rules: Vec::new(),
+                children,
+            }),
+        };
+    }
+
+    Some(IngredientNode {
+        name,
+        annotations,
+        position: 0,
+        percentage,
+        matched_rules: Vec::new(),
+        children,
+    })
+}
+
+/// Remove a declared percentage such as `12%` or `含量≥10%`, returning the rest of the text
+fn take_percentage(text: &str) -> (String, Option<f32>) {
+    const QUALIFIERS: &[&str] = &[
+        "≥",
+        "≤",
+        ">=",
+        "<=",
+        ">",
+        "<",
+        "＞",
+        "＜",
+        "=",
+        "约",
+        "不低于",
+        "不少于",
+        "大于",
+        "含量",
+        "添加量",
+        ":",
+    ];
+    let chars: Vec<char> = text.chars().collect();
+    let Some(sign) = chars.iter().position(|c| matches!(c, '%' | '％')) else {
+        return (text.trim().to_string(), None);
+    };
+    let mut start = sign;
+    while start > 0 && (chars[start - 1].is_ascii_digit() || chars[start - 1] == '.') {
+        start -= 1;
+    }
+    let number: String = chars[start..sign].iter().collect();
+    let value = match number.parse::<f32>() {
+        Ok(value) if (0.0..=100.0).contains(&value) => value,
+        _ => return (text.trim().to_string(), None),
+    };
+
+    let mut prefix: String = chars[..start].iter().collect();
+    loop {
+        let trimmed = prefix.trim_end();
+        match QUALIFIERS.iter().find_map(|q| trimmed.strip_suffix(q)) {
+            Some(rest) => prefix = rest.to_string(),
+            None => {
+                prefix = trimmed.to_string();
+                break;
+            }
+        }
+    }
+    let suffix: String = chars[sign + 1..].iter().collect();
+    (
+        format!("{}{}", prefix, suffix).trim().to_string(),
+        Some(value),
+    )
+}
+
+/// Bracketed text that describes its ingredient rather than naming a sub-ingredient
+fn is_annotation(text: &str) -> bool {
+    const FUNCTION_CLASSES: &[&str] = &["香精", "香料", "色素", "食品添加剂", "营养强化剂"];
+    let compact: String = text
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if compact.ends_with('剂') || FUNCTION_CLASSES.contains(&compact.as_str()) {
+        return true;
+    }
+    if compact.contains('%') {
+        return true;
+    }
+    compact.is_ascii() && !additive_codes::extract_code_keys(&format!("({})", compact)).is_empty()
+}
+
+fn is_open(c: char) -> bool {
+    matches!(c, '(' | '（' | '[' | '【' | '{')
+}
+
+fn is_close(c: char) -> bool {
+    matches!(c, ')' | '）' | ']' | '】' | '}')
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ',' | '，' | '、' | ';' | '；' | '\n' | '/' | '|')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(nodes: &[IngredientNode]) -> Vec<&str> {
+        nodes.iter().map(|node| node.name.as_str()).collect()
+    }
+
+    #[test]
+    fn nests_compound_ingredients() {
+        let nodes = parse_ingredients("配料：小麦粉，复合调味料（食用盐、白砂糖、味精），水");
+        assert_eq!(names(&nodes), vec!["小麦粉", "复合调味料", "水"]);
+        assert_eq!(names(&nodes[1].children), vec!["食用盐", "白砂糖", "味精"]);
+        assert!(nodes[1].annotations.is_empty());
+    }
+
+    #[test]
+    fn keeps_function_class_and_codes_as_annotations() {
+        let nodes =
+            parse_ingredients("山梨酸钾（防腐剂）、苯甲酸钠(E211)、增稠剂:黄原胶、柠檬酸(01.101)");
+        assert_eq!(
+            names(&nodes),
+            vec!["山梨酸钾", "苯甲酸钠", "黄原胶", "柠檬酸"]
+        );
+        assert_eq!(nodes[0].annotations, vec!["防腐剂"]);
+        assert_eq!(nodes[1].annotations, vec!["E211"]);
+        assert_eq!(nodes[2].annotations, vec!["增稠剂"]);
+        assert_eq!(nodes[3].annotations, vec!["01.101"]);
+        assert!(nodes.iter().all(|node| node.children.is_empty()));
+    }
+
+    #[test]
+    fn parses_deep_nesting_and_single_children() {
+        let nodes = parse_ingredients("巧克力酱(可可液块、植物油(棕榈油)、乳化剂(大豆磷脂))");
+        assert_eq!(nodes.len(), 1);
+        let sauce = &nodes[0];
+        assert_eq!(names(&sauce.children), vec!["可可液块", "植物油", "乳化剂"]);
+        assert_eq!(names(&sauce.children[1].children), vec!["棕榈油"]);
+        assert_eq!(names(&sauce.children[2].children), vec!["大豆磷脂"]);
+    }
+
+    #[test]
+    fn tolerates_unbalanced_brackets() {
+        let nodes = parse_ingredients("白砂糖)、调味料(食用盐、香辛料");
+        assert_eq!(names(&nodes), vec!["白砂糖", "调味料"]);
+        assert_eq!(names(&nodes[1].children), vec!["食用盐", "香辛料"]);
+    }
+
+    #[test]
+    fn records_position_and_declared_percentage() {
+        let nodes =
+            parse_ingredients("白砂糖 12%、果汁含量≥10%、鸡肉(含量≥20%)、水、调味料(盐、糖)");
+        assert_eq!(
+            names(&nodes),
+            vec!["白砂糖", "果汁", "鸡肉", "水", "调味料"]
+        );
+        let positions: Vec<u32> = nodes.iter().map(|node| node.position).collect();
+        assert_eq!(positions, vec![1, 2, 3, 4, 5]);
+        let percentages: Vec<Option<f32>> = nodes.iter().map(|node| node.percentage).collect();
+        assert_eq!(
+            percentages,
+            vec![Some(12.0), Some(10.0), Some(20.0), None, None]
+        );
+        assert!(nodes[2].annotations.is_empty());
+        assert_eq!(nodes[4].children[1].position, 2);
+    }
+}
@@ -39,12 +39,133 @@ impl PreferenceType {
     }
 }
 
+/// Focuses contributing less than this share of the weight do not raise rule risk levels
+pub const MIN_RISK_BUMP_WEIGHT: f32 = 0.25;
+
+/// One focus of a blended preference
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightedPreference {
+    pub preference: PreferenceType,
+    /// Share of the total weight, between 0 and 1
+    pub weight: f32,
+}
+
+/// Analysis focuses with weights summing to 1, heaviest first. Never empty: no focus is
+/// a single `PreferenceType::None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreferenceBlend {
+    focuses: Vec<WeightedPreference>,
+}
+
+impl Default for PreferenceBlend {
+    fn default() -> Self {
+        Self::single(PreferenceType::None)
+    }
+}
+
+impl From<PreferenceType> for PreferenceBlend {
+    fn from(preference: PreferenceType) -> Self {
+        Self::single(preference)
+    }
+}
+
+impl PreferenceBlend {
+    pub fn single(preference: PreferenceType) -> Self {
+        Self {
+            focuses: vec![WeightedPreference {
+                preference,
+                weight: 1.0,
+            }],
+        }
+    }
+
+    /// Blend focuses by relative weight. Repeated focuses add up; non-positive or
+    /// non-finite weights are skipped. Ties keep the given order.
+    pub fn from_weights(items: impl IntoIterator<Item = (PreferenceType, f32)>) -> Self {
+        let mut focuses: Vec<WeightedPreference> = Vec::new();
+        for (preference, weight) in items {
+            if !weight.is_finite() || weight <= 0.0 {
+                continue;
+            }
+            match focuses
+                .iter_mut()
+                .find(|focus| focus.preference == preference)
+            {
+                Some(focus) => focus.weight += weight,
+                None => focuses.push(WeightedPreference { preference, weight }),
+            }
+        }
+        let total: f32 = focuses.iter().map(|focus| focus.weight).sum();
+        if focuses.is_empty() || total <= 0.0 {
+            return Self::default();
+        }
+        for focus in &mut focuses {
+            focus.weight /= total;
+        }
+        focuses.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+        Self { focuses }
+    }
+
+    /// The blend a confirm request asks for: the weighted `preferences` list when given,
+    /// otherwise the single `preference`. Missing weights count as 1.
+    pub fn from_request(preference: Option<&str>, weighted: &[shared::PreferenceWeight]) -> Self {
+        if weighted.is_empty() {
+            return Self::single(PreferenceType::from_str(preference));
+        }
+        Self::from_weights(weighted.iter().map(|item| {
+            (
+                PreferenceType::from_str(Some(&item.preference)),
+                item.weight.unwrap_or(1.0),
+            )
+        }))
+    }
+
+    pub fn focuses(&self) -> &[WeightedPreference] {
+        &self.focuses
+    }
+
+    /// The heaviest focus
+    pub fn primary(&self) -> PreferenceType {
+        self.focuses[0].preference
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.focuses.len() == 1
+    }
+
+    /// Share of the weight held by focuses satisfying `matches`
+    pub fn weight_where(&self, matches: impl Fn(PreferenceType) -> bool) -> f32 {
+        self.focuses
+            .iter()
+            .filter(|focus| matches(focus.preference))
+            .map(|focus| focus.weight)
+            .sum()
+    }
+
+    /// Weighted sum of per-focus tables keyed like the primary focus's table
+    pub fn blend_table<K: Copy + PartialEq, const N: usize>(
+        &self,
+        table: impl Fn(PreferenceType) -> [(K, f32); N],
+    ) -> [(K, f32); N] {
+        let mut blended = table(self.primary()).map(|(key, _)| (key, 0.0));
+        for focus in &self.focuses {
+            for (key, value) in table(focus.preference) {
+                if let Some(entry) = blended.iter_mut().find(|(known, _)| *known == key) {
+                    entry.1 += value * focus.weight;
+                }
+            }
+        }
+        blended
+    }
+}
+
 #[async_trait]
 pub trait LlmProviderClient: Send + Sync {
     async fn analyze_ingredients(
         &self,
         text: &str,
-        preference: PreferenceType,
+        preference: &PreferenceBlend,
+        rule_hits: &[shared::RuleHit],
     ) -> anyhow::Result<shared::AnalysisResult>;
 }
 
@@ -53,3 +174,47 @@ pub fn build_llm_client(config: &LlmConfig, http: reqwest::Client) -> Box<dyn Ll
         LlmProvider::DeepSeek => Box::new(DeepSeekClient::new(config, http)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends_normalized_weights_heaviest_first() {
+        let blend = PreferenceBlend::from_weights([
+            (PreferenceType::Allergy, 0.5),
+            (PreferenceType::Kids, 3.0),
+            (PreferenceType::Allergy, 0.5),
+            (PreferenceType::Health, -1.0),
+        ]);
+        assert_eq!(blend.primary(), PreferenceType::Kids);
+        assert_eq!(blend.focuses().len(), 2);
+        assert!((blend.focuses()[1].weight - 0.25).abs() < 1e-6);
+
+        let table = |preference: PreferenceType| match preference {
+            PreferenceType::Kids => [("sugar", 0.8), ("sensitive", 0.2)],
+            _ => [("sensitive", 0.6), ("sugar", 0.4)],
+        };
+        let blended = blend.blend_table(table);
+        assert_eq!(blended[0].0, "sugar");
+        assert!((blended[0].1 - 0.7).abs() < 1e-6);
+        assert!((blended[1].1 - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn single_preference_requests_stay_unchanged() {
+        let blend = PreferenceBlend::from_request(Some("kids"), &[]);
+        assert_eq!(blend, PreferenceBlend::single(PreferenceType::Kids));
+
+        let weighted = vec![shared::PreferenceWeight {
+            preference: "allergy".to_string(),
+            weight: None,
+        }];
+        let blend = PreferenceBlend::from_request(Some("kids"), &weighted);
+        assert_eq!(blend, PreferenceBlend::single(PreferenceType::Allergy));
+        assert_eq!(
+            PreferenceBlend::from_weights([]),
+            PreferenceBlend::default()
+        );
+    }
+}
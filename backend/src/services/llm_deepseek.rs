@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::config::LlmConfig;
-use crate::services::llm::{LlmProviderClient, PreferenceType};
+use crate::services::llm::{
+    LlmProviderClient, PreferenceBlend, PreferenceType, WeightedPreference,
+};
 
 #[derive(Clone)]
 pub struct DeepSeekClient {
@@ -27,9 +29,10 @@ impl LlmProviderClient for DeepSeekClient {
     async fn analyze_ingredients(
         &self,
         text: &str,
-        preference: PreferenceType,
+        preference: &PreferenceBlend,
+        rule_hits: &[shared::RuleHit],
     ) -> anyhow::Result<shared::AnalysisResult> {
-        let prompt = build_analysis_prompt(text, preference);
+        let prompt = build_analysis_prompt(text, preference, rule_hits);
         let request = DeepSeekRequest {
             model: self.config.model.clone(),
             messages: vec![Message {
@@ -85,8 +88,14 @@ impl LlmProviderClient for DeepSeekClient {
     }
 }
 
-fn build_analysis_prompt(text: &str, preference: PreferenceType) -> String {
+fn build_analysis_prompt(
+    text: &str,
+    preference: &PreferenceBlend,
+    rule_hits: &[shared::RuleHit],
+) -> String {
+    let preference_label = build_preference_label(preference);
     let preference_instruction = build_preference_instruction(preference);
+    let rule_knowledge = build_rule_knowledge(rule_hits);
     format!(
         r#"你是一个专业的食品配料分析专家。请分析以下配料表，并返回 JSON 格式的健康评估。
 
@@ -95,7 +104,7 @@ fn build_analysis_prompt(text: &str, preference: PreferenceType) -> String {
 
 分析偏好：{}
 {}
-
+{}
 请严格按照以下 JSON 格式返回：
 {{
   "health_score": <0-100 的整数>,
@@ -146,14 +155,92 @@ fn build_analysis_prompt(text: &str, preference: PreferenceType) -> String {
 6. overall_assessment 简要给出总体评价或结论
 7. recommendation 必须紧扣分析偏好，给出摄入建议/频次建议；控制在 20-30 字左右
 8. score_breakdown 的 dimension 必须使用指定枚举值
-9. focus_summary 与 focus_ingredients 根据偏好给出重点信息"#,
-        text,
-        preference.as_key(),
-        preference_instruction
+9. focus_summary 与 focus_ingredients 根据偏好给出重点信息
+10. 若提供了规则库参考，对其中配料的 risk_level 与说明须与规则库保持一致，并引用其依据与来源"#,
+        text, preference_label, preference_instruction, rule_knowledge
+    )
+}
+
+/// Upper bound on rules quoted in the prompt, keeping it within the model's context
+const MAX_PROMPT_RULES: usize = 30;
+
+/// Matched rules quoted to the model so its explanations follow the curated library
+fn build_rule_knowledge(rule_hits: &[shared::RuleHit]) -> String {
+    let mut seen = std::collections::HashSet::new();
+    let lines: Vec<String> = rule_hits
+        .iter()
+        .filter(|hit| seen.insert(hit.name.as_str()))
+        .take(MAX_PROMPT_RULES)
+        .map(|hit| {
+            let mut line = format!(
+                "- {}（风险：{}）：{}",
+                hit.name, hit.risk_level, hit.description
+            );
+            if let Some(evidence) = hit.evidence.as_deref().filter(|text| !text.is_empty()) {
+                line.push_str(&format!("；依据：{}", evidence));
+            }
+            if let Some(source) = hit.source.as_deref().filter(|text| !text.is_empty()) {
+                line.push_str(&format!("；来源：{}", source));
+            }
+            line
+        })
+        .collect();
+    if lines.is_empty() {
+        return String::new();
+    }
+    format!(
+        "\n规则库参考（已匹配的规则，以此为准）：\n{}\n",
+        lines.join("\n")
+    )
+}
+
+/// Preference keys for the prompt, with weight percentages when several are blended
+fn build_preference_label(preference: &PreferenceBlend) -> String {
+    if preference.is_single() {
+        return preference.primary().as_key().to_string();
+    }
+    preference
+        .focuses()
+        .iter()
+        .map(|focus| {
+            format!(
+                "{}（{}%）",
+                focus.preference.as_key(),
+                weight_percent(focus)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Focus instructions; blended focuses are listed heaviest first with their weights
+fn build_preference_instruction(preference: &PreferenceBlend) -> String {
+    if preference.is_single() {
+        return focus_instruction(preference.primary()).to_string();
+    }
+    let lines: Vec<String> = preference
+        .focuses()
+        .iter()
+        .map(|focus| {
+            format!(
+                "- {}（权重 {}%）：{}",
+                focus.preference.as_key(),
+                weight_percent(focus),
+                focus_instruction(focus.preference)
+            )
+        })
+        .collect();
+    format!(
+        "请同时兼顾以下偏好，按权重分配关注程度；recommendation 以权重最高的偏好为主，兼顾其余偏好：\n{}",
+        lines.join("\n")
     )
 }
 
-fn build_preference_instruction(preference: PreferenceType) -> &'static str {
+fn weight_percent(focus: &WeightedPreference) -> i32 {
+    (focus.weight * 100.0).round() as i32
+}
+
+fn focus_instruction(preference: PreferenceType) -> &'static str {
     match preference {
         PreferenceType::WeightLoss => {
             "请重点关注热量、糖分、脂肪与反式脂肪酸，提示高糖高脂风险；recommendation 给出控糖控脂/热量管理的摄入建议。"
@@ -244,3 +331,59 @@ fn truncate_for_log(value: &str, max: usize) -> String {
         format!("{}...<truncated>", &value[..max])
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(name: &str, evidence: Option<&str>) -> shared::RuleHit {
+        shared::RuleHit {
+            rule_id: None,
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: "medium".to_string(),
+            description: "防腐剂".to_string(),
+            group_tags: Vec::new(),
+            evidence: evidence.map(str::to_string),
+            source: Some("GB 2760".to_string()),
+            matched_text: None,
+            match_type: None,
+            match_score: None,
+            position: None,
+            declared_percentage: None,
+            score_impact: None,
+            allergens: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn prompt_quotes_matched_rules_once() {
+        let hits = vec![
+            hit("苯甲酸钠", Some("ADI 0-5 mg/kg")),
+            hit("苯甲酸钠", None),
+        ];
+        let prompt = build_analysis_prompt("水，苯甲酸钠", &PreferenceBlend::default(), &hits);
+        assert!(prompt
+            .contains("- 苯甲酸钠（风险：medium）：防腐剂；依据：ADI 0-5 mg/kg；来源：GB 2760"));
+        assert_eq!(prompt.matches("- 苯甲酸钠").count(), 1);
+    }
+
+    #[test]
+    fn prompt_omits_rule_section_without_hits() {
+        let prompt = build_analysis_prompt("水", &PreferenceBlend::default(), &[]);
+        assert!(!prompt.contains("规则库参考（"));
+        assert!(prompt.contains("分析偏好：none\n按通用健康标准分析即可"));
+    }
+
+    #[test]
+    fn prompt_lists_blended_focuses_by_weight() {
+        let blend = PreferenceBlend::from_weights([
+            (PreferenceType::Allergy, 1.0),
+            (PreferenceType::Kids, 3.0),
+        ]);
+        let prompt = build_analysis_prompt("水", &blend, &[]);
+        assert!(prompt.contains("分析偏好：kids（75%） + allergy（25%）"));
+        assert!(prompt.contains("- kids（权重 75%）：请重点关注色素"));
+        assert!(prompt.contains("- allergy（权重 25%）：请重点识别常见过敏原"));
+    }
+}
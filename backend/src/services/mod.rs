@@ -1,10 +1,25 @@
 //! Business logic services
 
+pub mod additive_codes;
+pub mod allergens;
+pub mod alternatives;
 pub mod auth;
+pub mod barcode;
 pub mod community;
+pub mod confidence;
+pub mod diet;
 pub mod image_converter;
+pub mod ingredient_parser;
 pub mod llm;
 pub mod llm_deepseek;
 pub mod ocr;
+pub mod reconcile;
+pub mod retention;
+pub mod rule_matcher;
+pub mod rule_overlays;
+pub mod rule_validation;
+pub mod rule_versions;
 pub mod rules;
+pub mod score_attribution;
+pub mod scoring;
 pub mod storage;
@@ -7,8 +7,16 @@ use reqwest::StatusCode;
 
 use crate::config::OcrConfig;
 
+/// Recognized text with the mean confidence of its lines
+#[derive(Debug, Clone)]
+pub struct OcrOutput {
+    pub text: String,
+    /// Between 0 and 1; `None` when the OCR service reports no line scores
+    pub confidence: Option<f32>,
+}
+
 /// Extract text from image using PaddleOCR
-pub async fn extract_text(image_path: &Path, config: &OcrConfig) -> Result<String> {
+pub async fn extract_text(image_path: &Path, config: &OcrConfig) -> Result<OcrOutput> {
     let bytes = tokio::fs::read(image_path).await?;
     let part = reqwest::multipart::Part::bytes(bytes)
         .file_name("image.jpg")
@@ -39,12 +47,36 @@ pub async fn extract_text(image_path: &Path, config: &OcrConfig) -> Result<Strin
     }
 
     let result: PaddleOcrResponse = response.json().await?;
-    Ok(result.text.trim().to_string())
+    Ok(OcrOutput {
+        text: result.text.trim().to_string(),
+        confidence: mean_line_confidence(&result.lines),
+    })
 }
 
 #[derive(serde::Deserialize)]
 struct PaddleOcrResponse {
     text: String,
+    #[serde(default)]
+    lines: Vec<PaddleOcrLine>,
+}
+
+#[derive(serde::Deserialize)]
+struct PaddleOcrLine {
+    text: String,
+    score: f32,
+}
+
+/// Line scores weighted by line length, so a long ingredient line counts more than a
+/// stray character
+fn mean_line_confidence(lines: &[PaddleOcrLine]) -> Option<f32> {
+    let mut weighted = 0.0;
+    let mut total = 0.0;
+    for line in lines {
+        let weight = line.text.chars().count() as f32;
+        weighted += line.score.clamp(0.0, 1.0) * weight;
+        total += weight;
+    }
+    (total > 0.0).then(|| weighted / total)
 }
 
 #[derive(serde::Deserialize)]
@@ -60,7 +92,7 @@ fn parse_ocr_error_message(body: &str) -> Option<String> {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_ocr_error_message;
+    use super::{mean_line_confidence, parse_ocr_error_message, PaddleOcrResponse};
 
     #[test]
     fn parse_message_field() {
@@ -79,4 +111,13 @@ mod tests {
         let body = "not-json";
         assert_eq!(parse_ocr_error_message(body), None);
     }
+
+    #[test]
+    fn weights_line_confidence_by_length() {
+        let body = r#"{"text":"配料：水、白砂糖\n净","lines":[{"text":"配料：水、白砂糖","score":0.9},{"text":"净","score":0.1}]}"#;
+        let parsed: PaddleOcrResponse = serde_json::from_str(body).unwrap();
+        let confidence = mean_line_confidence(&parsed.lines).unwrap();
+        assert!((confidence - 7.3 / 9.0).abs() < 1e-6);
+        assert_eq!(mean_line_confidence(&[]), None);
+    }
 }
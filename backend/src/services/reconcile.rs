@@ -0,0 +1,244 @@
+//! Reconciliation of LLM ingredient rows with rule hits
+
+use shared::{AnalysisResult, IngredientInfo, RuleConflict, RuleHit, TableRow, Warning};
+
+use crate::services::confidence::normalize_risk;
+use crate::services::rules::names_match;
+use crate::services::scoring::risk_points;
+
+/// Upper bound on health score points moved by rule overrides, in either direction
+pub(crate) const MAX_CONFLICT_ADJUSTMENT: i32 = 10;
+
+const RULE_WARNING_TYPE: &str = "规则库提示";
+
+/// Make the LLM's rows agree with rule hits; curated rules are authoritative.
+///
+/// Rows naming a hit ingredient take the rule's risk level and description, and every
+/// disagreement is recorded in `rule_conflicts`. Hits the LLM did not list get their own
+/// rows, high-risk hits without a warning get one, and the health score moves by the risk
+/// difference of each conflict. Run after the score breakdown, which recomputes the score.
+pub fn reconcile_with_rules(mut result: AnalysisResult) -> AnalysisResult {
+    let hits = result.rule_hits.clone();
+    let mut conflicts = Vec::new();
+
+    for hit in &hits {
+        if normalize_risk(&hit.risk_level).is_none() {
+            continue;
+        }
+
+        let mut aligned = false;
+        for row in result
+            .ingredients
+            .iter_mut()
+            .filter(|row| hit_names(hit, &row.name))
+        {
+            aligned = true;
+            conflicts.extend(conflict(hit, &row.name, &row.risk_level));
+            row.risk_level = hit.risk_level.clone();
+            if !hit.description.is_empty() {
+                row.description = Some(hit.description.clone());
+            }
+        }
+        let listed_in_ingredients = aligned;
+        for row in result
+            .table
+            .iter_mut()
+            .filter(|row| hit_names(hit, &row.name))
+        {
+            if !listed_in_ingredients {
+                conflicts.extend(conflict(hit, &row.name, &row.risk_level));
+            }
+            aligned = true;
+            row.risk_level = hit.risk_level.clone();
+            if !hit.description.is_empty() {
+                row.note = hit.description.clone();
+            }
+        }
+
+        if !aligned {
+            add_rows(&mut result, hit);
+        }
+        if normalize_risk(&hit.risk_level) == Some("high")
+            && !result
+                .warnings
+                .iter()
+                .any(|warning| warning.ingredients.iter().any(|name| hit_names(hit, name)))
+        {
+            result.warnings.push(Warning {
+                warning_type: RULE_WARNING_TYPE.to_string(),
+                ingredients: vec![hit.name.clone()],
+                message: hit.description.clone(),
+                blocker: false,
+            });
+        }
+    }
+
+    let adjustment = conflicts
+        .iter()
+        .map(|conflict| conflict.score_adjustment)
+        .sum::<i32>()
+        .clamp(-MAX_CONFLICT_ADJUSTMENT, MAX_CONFLICT_ADJUSTMENT);
+    result.health_score = (result.health_score + adjustment).clamp(0, 100);
+    result.rule_conflicts = conflicts;
+    result
+}
+
+fn hit_names(hit: &RuleHit, name: &str) -> bool {
+    names_match(&hit.name, name)
+        || hit
+            .matched_text
+            .as_deref()
+            .is_some_and(|text| names_match(text, name))
+}
+
+fn conflict(hit: &RuleHit, ingredient: &str, llm_risk_level: &str) -> Option<RuleConflict> {
+    let rule_risk = normalize_risk(&hit.risk_level)?;
+    let llm_risk = normalize_risk(llm_risk_level);
+    if llm_risk == Some(rule_risk) {
+        return None;
+    }
+    // An unrated LLM row is corrected without moving the score.
+    let score_adjustment = llm_risk
+        .map(|llm_risk| (risk_points(llm_risk) - risk_points(rule_risk)).round() as i32)
+        .unwrap_or(0);
+    Some(RuleConflict {
+        ingredient: ingredient.to_string(),
+        rule_id: hit.rule_id.clone(),
+        rule_name: hit.name.clone(),
+        llm_risk_level: llm_risk_level.to_string(),
+        rule_risk_level: hit.risk_level.clone(),
+        score_adjustment,
+    })
+}
+
+fn add_rows(result: &mut AnalysisResult, hit: &RuleHit) {
+    result.ingredients.push(IngredientInfo {
+        name: hit.name.clone(),
+        category: hit.category.clone(),
+        risk_level: hit.risk_level.clone(),
+        description: Some(hit.description.clone()).filter(|text| !text.is_empty()),
+    });
+    if !result.table.is_empty() {
+        result.table.push(TableRow {
+            name: hit.name.clone(),
+            category: hit.category.clone(),
+            function: String::new(),
+            risk_level: hit.risk_level.clone(),
+            note: hit.description.clone(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(name: &str, risk_level: &str) -> RuleHit {
+        RuleHit {
+            rule_id: Some(format!("rule-{}", name)),
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: risk_level.to_string(),
+            description: format!("{}规则说明", name),
+            group_tags: Vec::new(),
+            evidence: None,
+            source: None,
+            matched_text: Some(name.to_string()),
+            match_type: Some("exact".to_string()),
+            match_score: Some(1.0),
+            position: Some(1),
+            declared_percentage: None,
+            score_impact: None,
+            allergens: Vec::new(),
+        }
+    }
+
+    fn row(name: &str, risk_level: &str) -> IngredientInfo {
+        IngredientInfo {
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: risk_level.to_string(),
+            description: Some("模型说明".to_string()),
+        }
+    }
+
+    fn result(ingredients: Vec<IngredientInfo>, hits: Vec<RuleHit>) -> AnalysisResult {
+        AnalysisResult {
+            health_score: 80,
+            summary: String::new(),
+            table: ingredients
+                .iter()
+                .map(|item| TableRow {
+                    name: item.name.clone(),
+                    category: item.category.clone(),
+                    function: "防腐".to_string(),
+                    risk_level: item.risk_level.clone(),
+                    note: String::new(),
+                })
+                .collect(),
+            ingredients,
+            warnings: Vec::new(),
+            recommendation: String::new(),
+            overall_assessment: None,
+            focus_summary: None,
+            focus_ingredients: None,
+            score_breakdown: None,
+            rule_hits: hits,
+            confidence: None,
+            ingredient_tree: Vec::new(),
+            rule_set_version: None,
+            rule_conflicts: Vec::new(),
+            diet_verdicts: Vec::new(),
+            score_attribution: None,
+        }
+    }
+
+    #[test]
+    fn rule_overrides_llm_risk_and_records_conflict() {
+        let reconciled = reconcile_with_rules(result(
+            vec![row("亚硝酸钠（护色剂）", "low"), row("水", "low")],
+            vec![hit("亚硝酸钠", "high")],
+        ));
+
+        assert_eq!(reconciled.ingredients[0].risk_level, "high");
+        assert_eq!(
+            reconciled.ingredients[0].description.as_deref(),
+            Some("亚硝酸钠规则说明")
+        );
+        assert_eq!(reconciled.table[0].risk_level, "high");
+        assert_eq!(reconciled.table[0].function, "防腐");
+        assert_eq!(reconciled.rule_conflicts.len(), 1);
+        assert_eq!(reconciled.rule_conflicts[0].llm_risk_level, "low");
+        assert_eq!(reconciled.rule_conflicts[0].score_adjustment, -7);
+        assert_eq!(reconciled.health_score, 73);
+        assert_eq!(reconciled.warnings[0].ingredients, vec!["亚硝酸钠"]);
+    }
+
+    #[test]
+    fn agreeing_rows_are_left_alone() {
+        let reconciled = reconcile_with_rules(result(
+            vec![row("山梨酸钾", "medium")],
+            vec![hit("山梨酸钾", "medium")],
+        ));
+        assert!(reconciled.rule_conflicts.is_empty());
+        assert_eq!(reconciled.health_score, 80);
+        assert!(reconciled.warnings.is_empty());
+    }
+
+    #[test]
+    fn adds_rows_for_hits_the_llm_missed_and_caps_adjustment() {
+        let reconciled = reconcile_with_rules(result(
+            vec![row("苯甲酸钠", "low"), row("日落黄", "low")],
+            vec![
+                hit("苯甲酸钠", "high"),
+                hit("日落黄", "high"),
+                hit("阿斯巴甜", "medium"),
+            ],
+        ));
+
+        assert_eq!(reconciled.ingredients.len(), 3);
+        assert_eq!(reconciled.table[2].name, "阿斯巴甜");
+        assert_eq!(reconciled.rule_conflicts.len(), 2);
+        assert_eq!(reconciled.health_score, 80 - MAX_CONFLICT_ADJUSTMENT);
+    }
+}
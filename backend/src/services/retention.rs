@@ -0,0 +1,228 @@
+//! Upload retention and orphan file garbage collection
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use anyhow::Result;
+use sqlx::PgPool;
+use tokio::fs;
+use tracing::{info, warn};
+
+use crate::config::RetentionConfig;
+use crate::services::storage;
+
+/// Summary of a single retention sweep
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RetentionReport {
+    /// Unconfirmed anonymous analyses removed because they expired
+    pub purged_analyses: u64,
+    /// Files found in the upload directory
+    pub scanned_files: u64,
+    /// Files still referenced by a database row
+    pub referenced_files: u64,
+    /// Orphan files removed from disk
+    pub deleted_files: u64,
+    /// Bytes freed by removing orphan files
+    pub reclaimed_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UploadFile {
+    path: PathBuf,
+    name: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Run one retention sweep: purge expired analyses, then delete unreferenced files.
+///
+/// With `dry_run` set, nothing is deleted and the report describes what would be removed.
+pub async fn run_once(
+    pool: &PgPool,
+    upload_dir: &str,
+    config: &RetentionConfig,
+    dry_run: bool,
+) -> Result<RetentionReport> {
+    let mut report = RetentionReport {
+        purged_analyses: purge_unconfirmed_analyses(pool, config.unconfirmed_ttl_hours, dry_run)
+            .await?,
+        ..RetentionReport::default()
+    };
+
+    let files = scan_upload_dir(Path::new(upload_dir)).await?;
+    let referenced = load_referenced_files(pool).await?;
+    let cutoff = SystemTime::now()
+        .checked_sub(config.orphan_grace)
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    report.scanned_files = files.len() as u64;
+    report.referenced_files = files
+        .iter()
+        .filter(|file| referenced.contains(&file.name))
+        .count() as u64;
+
+    for file in select_orphans(files, &referenced, cutoff) {
+        if !dry_run {
+            if let Err(err) = fs::remove_file(&file.path).await {
+                warn!(
+                    "failed to delete orphan upload {}: {}",
+                    file.path.display(),
+                    err
+                );
+                continue;
+            }
+        }
+        report.deleted_files += 1;
+        report.reclaimed_bytes += file.size;
+    }
+
+    Ok(report)
+}
+
+/// Run retention sweeps forever on the configured interval
+pub async fn run_periodically(pool: PgPool, upload_dir: String, config: RetentionConfig) {
+    let mut interval = tokio::time::interval(config.interval);
+    loop {
+        interval.tick().await;
+        match run_once(&pool, &upload_dir, &config, false).await {
+            Ok(report) => info!(
+                purged_analyses = report.purged_analyses,
+                scanned_files = report.scanned_files,
+                deleted_files = report.deleted_files,
+                reclaimed_bytes = report.reclaimed_bytes,
+                "upload retention sweep finished"
+            ),
+            Err(err) => warn!("upload retention sweep failed: {}", err),
+        }
+    }
+}
+
+async fn purge_unconfirmed_analyses(pool: &PgPool, ttl_hours: i64, dry_run: bool) -> Result<u64> {
+    if ttl_hours <= 0 {
+        return Ok(0);
+    }
+
+    if dry_run {
+        let count = sqlx::query_scalar::<_, i64>(
+            r#"
+            SELECT COUNT(*) as count
+            FROM analyses
+            WHERE user_id IS NULL
+              AND confirmed_text IS NULL
+              AND created_at < NOW() - make_interval(hours => $1)
+            "#,
+        )
+        .bind(ttl_hours as i32)
+        .fetch_one(pool)
+        .await?;
+        return Ok(count as u64);
+    }
+
+    let result = sqlx::query(
+        r#"
+        DELETE FROM analyses
+        WHERE user_id IS NULL
+          AND confirmed_text IS NULL
+          AND created_at < NOW() - make_interval(hours => $1)
+        "#,
+    )
+    .bind(ttl_hours as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+async fn load_referenced_files(pool: &PgPool) -> Result<HashSet<String>> {
+    let urls = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT image_url FROM analyses
+        UNION
+        SELECT card_image_url FROM community_posts WHERE card_image_url IS NOT NULL
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(urls
+        .iter()
+        .filter_map(|url| storage::upload_file_name(url))
+        .map(|name| name.to_string())
+        .collect())
+}
+
+async fn scan_upload_dir(dir: &Path) -> Result<Vec<UploadFile>> {
+    let mut entries = match fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut files = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let Some(name) = entry.file_name().to_str().map(|name| name.to_string()) else {
+            continue;
+        };
+        files.push(UploadFile {
+            path: entry.path(),
+            name,
+            size: metadata.len(),
+            modified: metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+        });
+    }
+    Ok(files)
+}
+
+/// Files that no row references and that are older than the grace cutoff.
+///
+/// The grace period protects uploads whose row has not been inserted yet.
+fn select_orphans(
+    files: Vec<UploadFile>,
+    referenced: &HashSet<String>,
+    cutoff: SystemTime,
+) -> Vec<UploadFile> {
+    files
+        .into_iter()
+        .filter(|file| !referenced.contains(&file.name) && file.modified <= cutoff)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn file(name: &str, age_secs: u64) -> UploadFile {
+        UploadFile {
+            path: PathBuf::from("/uploads").join(name),
+            name: name.to_string(),
+            size: 100,
+            modified: SystemTime::now() - Duration::from_secs(age_secs),
+        }
+    }
+
+    #[test]
+    fn select_orphans_skips_referenced_files() {
+        let referenced = HashSet::from(["a.jpg".to_string()]);
+        let cutoff = SystemTime::now() - Duration::from_secs(60);
+        let orphans = select_orphans(
+            vec![file("a.jpg", 600), file("b.jpg", 600)],
+            &referenced,
+            cutoff,
+        );
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].name, "b.jpg");
+    }
+
+    #[test]
+    fn select_orphans_respects_grace_period() {
+        let referenced = HashSet::new();
+        let cutoff = SystemTime::now() - Duration::from_secs(60);
+        let orphans = select_orphans(vec![file("fresh.jpg", 5)], &referenced, cutoff);
+        assert!(orphans.is_empty());
+    }
+}
@@ -0,0 +1,379 @@
+//! Token matching against rule names and aliases
+//!
+//! Tokens are matched in three passes: exact lookup, rule keys contained in a longer
+//! token (e.g. `食品添加剂(山梨酸钾)`), and small edit distances for OCR typos.
+
+use std::collections::HashSet;
+
+/// Single-character keys specific enough to find inside a longer token, e.g. `虾粉`
+const SINGLE_CHAR_KEYS: &[&str] = &["虾", "蟹"];
+
+/// Aliases too generic to find inside a longer token: `乳清蛋白粉` is not egg and
+/// `焦糖色素` is not tartrazine. They still match a token on their own.
+const GENERIC_KEYS: &[&str] = &["蛋白", "色素"];
+
+/// Characters naming the form of an ingredient, which may follow a key inside a token:
+/// `虾粉`, `核桃仁`, `山梨酸钾粉`. Any other character continues the name, so `柠檬酸钠`
+/// is not `柠檬酸`.
+const FORM_SUFFIXES: &[char] = &[
+    '粉', '碎', '粒', '片', '丁', '末', '泥', '糜', '仁', '肉', '液', '汁', '浆', '酱', '油', '干',
+];
+
+/// How a token matched a rule key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Exact,
+    Substring,
+    Fuzzy,
+    /// Additive code such as `E211`, `INS 202` or `CNS 17.003`
+    Code,
+}
+
+impl MatchKind {
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Substring => "substring",
+            Self::Fuzzy => "fuzzy",
+            Self::Code => "code",
+        }
+    }
+}
+
+/// A rule key found in a token, with a 0-1 quality score
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyMatch {
+    pub key: String,
+    pub kind: MatchKind,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone)]
+struct MatchKey {
+    text: String,
+    chars: Vec<char>,
+    latin: bool,
+}
+
+/// Index of normalized rule keys
+#[derive(Debug, Clone, Default)]
+pub struct Matcher {
+    exact: HashSet<String>,
+    keys: Vec<MatchKey>,
+}
+
+impl Matcher {
+    pub fn new<I>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let exact: HashSet<String> = keys.into_iter().filter(|key| !key.is_empty()).collect();
+        let mut keys: Vec<MatchKey> = exact
+            .iter()
+            .map(|text| MatchKey {
+                text: text.clone(),
+                chars: text.chars().collect(),
+                latin: text.is_ascii(),
+            })
+            .collect();
+        // Longest keys first so greedy substring matching prefers the most specific key.
+        keys.sort_by(|a, b| b.chars.len().cmp(&a.chars.len()).then(a.text.cmp(&b.text)));
+        Self { exact, keys }
+    }
+
+    /// Match a normalized token, returning every key found in it
+    pub fn match_token(&self, token: &str) -> Vec<KeyMatch> {
+        if token.is_empty() {
+            return Vec::new();
+        }
+        if self.exact.contains(token) {
+            return vec![KeyMatch {
+                key: token.to_string(),
+                kind: MatchKind::Exact,
+                score: 1.0,
+            }];
+        }
+
+        let substring = self.substring_matches(token);
+        if !substring.is_empty() {
+            return substring;
+        }
+
+        self.fuzzy_match(token).into_iter().collect()
+    }
+
+    fn substring_matches(&self, token: &str) -> Vec<KeyMatch> {
+        let chars: Vec<char> = token.chars().collect();
+        let content_len = chars.iter().filter(|c| !is_annotation_char(**c)).count();
+        if content_len == 0 {
+            return Vec::new();
+        }
+
+        let mut taken = vec![false; chars.len()];
+        let mut matches = Vec::new();
+        for key in &self.keys {
+            if !substring_key(key) || key.chars.len() > chars.len() {
+                continue;
+            }
+            let mut found = false;
+            for start in 0..=chars.len() - key.chars.len() {
+                let end = start + key.chars.len();
+                if chars[start..end] != key.chars[..] || taken[start..end].iter().any(|t| *t) {
+                    continue;
+                }
+                if key.latin && !has_word_boundaries(&chars, start, end) {
+                    continue;
+                }
+                if !key.latin && !ends_name(&chars, end) {
+                    continue;
+                }
+                taken[start..end].iter_mut().for_each(|t| *t = true);
+                found = true;
+            }
+            if found {
+                let coverage = key.chars.len() as f32 / content_len as f32;
+                matches.push(KeyMatch {
+                    key: key.text.clone(),
+                    kind: MatchKind::Substring,
+                    score: (0.7 + 0.25 * coverage).min(0.95),
+                });
+            }
+        }
+        matches
+    }
+
+    fn fuzzy_match(&self, token: &str) -> Option<KeyMatch> {
+        let mut best: Option<KeyMatch> = None;
+        for segment in segments(token) {
+            let segment: Vec<char> = segment.chars().collect();
+            for key in &self.keys {
+                let allowed = max_edits(key);
+                if allowed == 0 || segment.len().abs_diff(key.chars.len()) > allowed {
+                    continue;
+                }
+                // The last character of a Chinese chemical name is its salt or acid:
+                // 二氧化碳 is not 二氧化硫.
+                if !key.latin && segment.last() != key.chars.last() {
+                    continue;
+                }
+                let distance = levenshtein(&segment, &key.chars);
+                if distance == 0 || distance > allowed {
+                    continue;
+                }
+                let score = 0.85 * (1.0 - distance as f32 / key.chars.len() as f32);
+                let improves = match &best {
+                    Some(current) => score > current.score,
+                    None => true,
+                };
+                if improves {
+                    best = Some(KeyMatch {
+                        key: key.text.clone(),
+                        kind: MatchKind::Fuzzy,
+                        score,
+                    });
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Edits tolerated for a key: none for short keys, more for long Latin names.
+///
+/// One character changes a short Chinese name into another compound (硝酸钠, 碳酸钠,
+/// 醋酸钠), so those must match exactly.
+fn max_edits(key: &MatchKey) -> usize {
+    let len = key.chars.len();
+    if key.latin {
+        match len {
+            0..=4 => 0,
+            5..=8 => 1,
+            _ => 2,
+        }
+    } else {
+        match len {
+            0..=3 => 0,
+            4..=5 => 1,
+            _ => 2,
+        }
+    }
+}
+
+/// Whether a key may be found inside a longer token
+fn substring_key(key: &MatchKey) -> bool {
+    if key.latin {
+        return key.chars.len() >= 3;
+    }
+    match key.chars.len() {
+        0 => false,
+        1 => SINGLE_CHAR_KEYS.contains(&key.text.as_str()),
+        _ => !GENERIC_KEYS.contains(&key.text.as_str()),
+    }
+}
+
+/// The token without parenthesized text, plus each parenthesized part
+fn segments(token: &str) -> Vec<String> {
+    let mut outer = String::new();
+    let mut inner = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in token.chars() {
+        match c {
+            '(' | '[' => {
+                depth += 1;
+                if depth == 1 {
+                    current.clear();
+                }
+            }
+            ')' | ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 && !current.is_empty() {
+                    inner.push(std::mem::take(&mut current));
+                }
+            }
+            _ if depth > 0 => current.push(c),
+            _ => outer.push(c),
+        }
+    }
+    std::iter::once(outer)
+        .chain(inner)
+        .filter(|segment| segment.chars().count() >= 2)
+        .collect()
+}
+
+fn is_annotation_char(c: char) -> bool {
+    matches!(c, '(' | ')' | '[' | ']' | ':' | '.' | '-' | '*')
+}
+
+/// Whether a Chinese name found in `chars` ends at `end`: at the token end, an annotation
+/// or a form suffix
+fn ends_name(chars: &[char], end: usize) -> bool {
+    match chars.get(end) {
+        None => true,
+        Some(c) => is_annotation_char(*c) || FORM_SUFFIXES.contains(c),
+    }
+}
+
+fn has_word_boundaries(chars: &[char], start: usize, end: usize) -> bool {
+    let before = start == 0 || !chars[start - 1].is_ascii_alphanumeric();
+    let after = end == chars.len() || !chars[end].is_ascii_alphanumeric();
+    before && after
+}
+
+pub(crate) fn levenshtein(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher() -> Matcher {
+        Matcher::new(
+            ["山梨酸钾", "山梨酸", "苯甲酸钠", "aspartame", "味精"]
+                .into_iter()
+                .map(String::from),
+        )
+    }
+
+    #[test]
+    fn exact_match_scores_one() {
+        let matches = matcher().match_token("苯甲酸钠");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].kind, MatchKind::Exact);
+        assert_eq!(matches[0].score, 1.0);
+    }
+
+    #[test]
+    fn finds_key_inside_annotated_token() {
+        for token in ["食品添加剂(山梨酸钾)", "山梨酸钾(防腐剂)"] {
+            let matches = matcher().match_token(token);
+            assert_eq!(matches.len(), 1, "{token}");
+            assert_eq!(matches[0].key, "山梨酸钾");
+            assert_eq!(matches[0].kind, MatchKind::Substring);
+            assert!(matches[0].score < 1.0 && matches[0].score >= 0.7);
+        }
+    }
+
+    #[test]
+    fn tolerates_single_cjk_typo() {
+        let matches = matcher().match_token("苯申酸钠");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].key, "苯甲酸钠");
+        assert_eq!(matches[0].kind, MatchKind::Fuzzy);
+    }
+
+    #[test]
+    fn typos_never_change_short_names_or_the_final_character() {
+        let m = Matcher::new(
+            ["硝酸钠", "磷酸盐", "二氧化硫"]
+                .into_iter()
+                .map(String::from),
+        );
+        for token in [
+            "碳酸钠",
+            "醋酸钠",
+            "硝酸钾",
+            "磷酸钠",
+            "二氧化碳",
+            "苯甲酸纳",
+        ] {
+            assert!(m.match_token(token).is_empty(), "{token}");
+        }
+    }
+
+    #[test]
+    fn substrings_must_end_the_name() {
+        let m = Matcher::new(
+            ["柠檬酸", "蛋白", "色素", "虾", "核桃"]
+                .into_iter()
+                .map(String::from),
+        );
+        for token in ["柠檬酸钠", "乳清蛋白粉", "焦糖色素"] {
+            assert!(m.match_token(token).is_empty(), "{token}");
+        }
+        for (token, key) in [
+            ("虾粉", "虾"),
+            ("核桃仁", "核桃"),
+            ("柠檬酸(酸度调节剂)", "柠檬酸"),
+        ] {
+            let matches = m.match_token(token);
+            assert_eq!(matches.len(), 1, "{token}");
+            assert_eq!(matches[0].key, key);
+        }
+        assert_eq!(m.match_token("蛋白")[0].kind, MatchKind::Exact);
+    }
+
+    #[test]
+    fn tolerates_latin_typo_but_not_short_keys() {
+        let matches = matcher().match_token("aspartarne");
+        assert_eq!(matches[0].key, "aspartame");
+        assert!(matcher().match_token("味晶").is_empty());
+    }
+
+    #[test]
+    fn latin_substring_requires_word_boundary() {
+        let m = Matcher::new(["msg".to_string()]);
+        assert!(m.match_token("msgx").is_empty());
+        assert_eq!(m.match_token("msg(e621)").len(), 1);
+    }
+
+    #[test]
+    fn levenshtein_counts_edits() {
+        let a: Vec<char> = "kitten".chars().collect();
+        let b: Vec<char> = "sitting".chars().collect();
+        assert_eq!(levenshtein(&a, &b), 3);
+    }
+}
@@ -0,0 +1,235 @@
+//! Personal rule overlays merged over the global rule library
+
+use shared::{RuleOverlay, RuleOverlayInput};
+use uuid::Uuid;
+
+use crate::services::rules::{normalize_token, RuleItem, DIET_CATEGORY};
+
+/// `source` of rules a user added for themselves
+pub const OVERLAY_SOURCE: &str = "个人规则";
+
+/// The global rules with a user's overlays applied.
+///
+/// An overlay naming a global rule, by ID or by name/alias, replaces that rule's risk level
+/// and appends the note to its description, or removes the rule when the user trusts the
+/// ingredient. Other overlays become rules of their own, matched by the ingredient name.
+/// Overrides of rules that no longer exist are skipped.
+///
+/// Diet tags survive overlays: a trusted rule with diet tags is kept as a diet-only rule,
+/// and an overridden diet-only rule becomes an ordinary rule so the ingredient is flagged.
+pub fn overlay_items(items: &[RuleItem], overlays: &[RuleOverlay]) -> Vec<RuleItem> {
+    let mut items = items.to_vec();
+    for RuleOverlay { id, overlay, .. } in overlays {
+        let note = overlay
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty());
+        let target = match &overlay.rule_id {
+            Some(rule_id) => items.iter().position(|item| &item.id == rule_id),
+            None => {
+                let key = normalize_token(&overlay.ingredient);
+                items.iter().position(|item| {
+                    normalize_token(&item.name) == key
+                        || item
+                            .aliases
+                            .iter()
+                            .any(|alias| normalize_token(alias) == key)
+                })
+            }
+        };
+
+        match target {
+            Some(index) if overlay.trusted => {
+                if items[index].has_diet_tags() {
+                    items[index].category = DIET_CATEGORY.to_string();
+                } else {
+                    items.remove(index);
+                }
+            }
+            Some(index) => {
+                let item = &mut items[index];
+                if item.category == DIET_CATEGORY {
+                    item.category = "other".to_string();
+                }
+                item.risk_level = overlay.risk_level.clone();
+                if let Some(note) = note {
+                    item.description = format!("{}（个人备注：{}）", item.description, note);
+                }
+            }
+            None if overlay.rule_id.is_none() && !overlay.trusted => items.push(RuleItem {
+                id: format!("user-{}", id),
+                name: overlay.ingredient.trim().to_string(),
+                aliases: Vec::new(),
+                category: "other".to_string(),
+                risk_level: overlay.risk_level.clone(),
+                groups: Vec::new(),
+                description: note
+                    .map(str::to_string)
+                    .unwrap_or_else(|| default_description(&overlay.risk_level).to_string()),
+                evidence: None,
+                source: Some(OVERLAY_SOURCE.to_string()),
+                e_numbers: Vec::new(),
+                ins_numbers: Vec::new(),
+                cns_codes: Vec::new(),
+                allergens: Vec::new(),
+            }),
+            None => {}
+        }
+    }
+    items
+}
+
+/// High-risk overlays for a family profile's avoid-list. Applied after the account's own
+/// overlays, so an avoided ingredient stays flagged even if the account trusts it.
+pub fn avoid_overlays(profile_name: &str, ingredients: &[String]) -> Vec<RuleOverlay> {
+    ingredients
+        .iter()
+        .map(|ingredient| RuleOverlay {
+            id: Uuid::new_v4(),
+            overlay: RuleOverlayInput {
+                ingredient: ingredient.clone(),
+                rule_id: None,
+                risk_level: "high".to_string(),
+                note: Some(format!("{}的规避配料", profile_name)),
+                trusted: false,
+            },
+            created_at: String::new(),
+            updated_at: String::new(),
+        })
+        .collect()
+}
+
+fn default_description(risk_level: &str) -> &'static str {
+    match risk_level {
+        "high" => "个人规避配料",
+        "low" => "个人信任配料",
+        _ => "个人关注配料",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, name: &str, aliases: &[&str]) -> RuleItem {
+        RuleItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: "additive".to_string(),
+            risk_level: "medium".to_string(),
+            groups: Vec::new(),
+            description: "甜味剂".to_string(),
+            evidence: None,
+            source: None,
+            e_numbers: Vec::new(),
+            ins_numbers: Vec::new(),
+            cns_codes: Vec::new(),
+            allergens: Vec::new(),
+        }
+    }
+
+    fn overlay(ingredient: &str, rule_id: Option<&str>, risk_level: &str) -> RuleOverlay {
+        RuleOverlay {
+            id: uuid::Uuid::nil(),
+            overlay: shared::RuleOverlayInput {
+                ingredient: ingredient.to_string(),
+                rule_id: rule_id.map(str::to_string),
+                risk_level: risk_level.to_string(),
+                note: Some("医生建议".to_string()),
+                trusted: false,
+            },
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn overrides_global_rule_by_id_or_alias() {
+        let items = vec![
+            rule("additive-aspartame", "阿斯巴甜", &["天冬甜素"]),
+            rule("additive-sucralose", "三氯蔗糖", &[]),
+        ];
+        let merged = overlay_items(
+            &items,
+            &[
+                overlay("天冬甜素", None, "high"),
+                overlay("", Some("additive-sucralose"), "low"),
+            ],
+        );
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].risk_level, "high");
+        assert_eq!(merged[0].description, "甜味剂（个人备注：医生建议）");
+        assert_eq!(merged[1].risk_level, "low");
+    }
+
+    #[test]
+    fn adds_personal_rules_and_skips_missing_overrides() {
+        let items = vec![rule("additive-aspartame", "阿斯巴甜", &[])];
+        let merged = overlay_items(
+            &items,
+            &[
+                overlay("棕榈油", None, "high"),
+                overlay("", Some("additive-removed"), "low"),
+            ],
+        );
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[1].name, "棕榈油");
+        assert_eq!(merged[1].source.as_deref(), Some(OVERLAY_SOURCE));
+        assert_eq!(merged[1].description, "医生建议");
+    }
+
+    #[test]
+    fn trusted_overlays_remove_rules() {
+        let items = vec![
+            rule("additive-aspartame", "阿斯巴甜", &["天冬甜素"]),
+            rule("additive-sucralose", "三氯蔗糖", &[]),
+        ];
+        let mut trusted = overlay("天冬甜素", None, "low");
+        trusted.overlay.trusted = true;
+        let mut unknown = overlay("燕麦", None, "low");
+        unknown.overlay.trusted = true;
+        let merged = overlay_items(&items, &[trusted, unknown]);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].id, "additive-sucralose");
+    }
+
+    #[test]
+    fn avoided_ingredients_override_trust() {
+        let items = vec![rule("additive-aspartame", "阿斯巴甜", &[])];
+        let mut trusted = overlay("阿斯巴甜", None, "low");
+        trusted.overlay.trusted = true;
+        let mut overlays = vec![trusted];
+        overlays.extend(avoid_overlays(
+            "宝宝",
+            &["阿斯巴甜".to_string(), "蜂蜜".to_string()],
+        ));
+        let merged = overlay_items(&items, &overlays);
+
+        assert_eq!(merged.len(), 2);
+        assert!(merged.iter().all(|item| item.risk_level == "high"));
+        assert_eq!(merged[1].name, "蜂蜜");
+        assert_eq!(merged[1].description, "宝宝的规避配料");
+    }
+
+    #[test]
+    fn overlays_keep_diet_tags() {
+        let mut milk = rule("allergen-milk", "牛奶", &[]);
+        milk.groups = vec!["not_vegan".to_string()];
+        let mut honey = rule("diet-honey", "蜂蜜", &[]);
+        honey.category = DIET_CATEGORY.to_string();
+        honey.groups = vec!["not_vegan".to_string()];
+        let mut trusted = overlay("牛奶", None, "low");
+        trusted.overlay.trusted = true;
+        let merged = overlay_items(&[milk, honey], &[trusted, overlay("蜂蜜", None, "high")]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].category, DIET_CATEGORY);
+        assert_eq!(merged[1].category, "other");
+        assert_eq!(merged[1].risk_level, "high");
+    }
+}
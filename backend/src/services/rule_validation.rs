@@ -0,0 +1,238 @@
+//! Validation of rule definitions before they enter the rule library
+
+use std::collections::HashSet;
+
+use shared::{Allergen, DietRegime};
+
+use crate::services::additive_codes;
+use crate::services::rules::{normalize_token, RuleItem, DIET_CATEGORY};
+
+pub const CATEGORIES: &[&str] = &["allergen", "additive", "other", DIET_CATEGORY];
+pub const RISK_LEVELS: &[&str] = &["low", "medium", "high"];
+pub const GROUPS: &[&str] = &[
+    "allergy",
+    "kids",
+    "weight_loss",
+    "health",
+    "fitness",
+    "pregnancy",
+];
+
+const MAX_ID_LENGTH: usize = 64;
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_DESCRIPTION_LENGTH: usize = 500;
+
+/// A normalized name or alias shared with another rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyConflict {
+    pub key: String,
+    pub other_id: String,
+}
+
+/// Check a rule's fields, returning one message per problem
+pub fn validate_rule(item: &RuleItem) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    let id_valid = !item.id.is_empty()
+        && item.id.len() <= MAX_ID_LENGTH
+        && item
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+    if !id_valid {
+        problems.push(format!(
+            "规则 ID 只能包含小写字母、数字和连字符，长度 1-{}",
+            MAX_ID_LENGTH
+        ));
+    }
+
+    if !valid_text(&item.name, MAX_NAME_LENGTH) {
+        problems.push(format!("规则名称长度需为 1-{}", MAX_NAME_LENGTH));
+    }
+    let mut keys = HashSet::from([normalize_token(&item.name)]);
+    for alias in &item.aliases {
+        if !valid_text(alias, MAX_NAME_LENGTH) {
+            problems.push(format!("别名长度需为 1-{}", MAX_NAME_LENGTH));
+        } else if !keys.insert(normalize_token(alias)) {
+            problems.push(format!("别名重复：{}", alias));
+        }
+    }
+
+    if !CATEGORIES.contains(&item.category.as_str()) {
+        problems.push(format!("未知分类：{}", item.category));
+    }
+    if !RISK_LEVELS.contains(&item.risk_level.as_str()) {
+        problems.push(format!("未知风险等级：{}", item.risk_level));
+    }
+    for group in &item.groups {
+        if !GROUPS.contains(&group.as_str()) && !DietRegime::is_diet_tag(group) {
+            problems.push(format!("未知人群分组：{}", group));
+        }
+    }
+    if item.category == DIET_CATEGORY && !item.has_diet_tags() {
+        problems.push("饮食规则需至少包含一个饮食标签".to_string());
+    }
+    if !valid_text(&item.description, MAX_DESCRIPTION_LENGTH) {
+        problems.push(format!("规则描述长度需为 1-{}", MAX_DESCRIPTION_LENGTH));
+    }
+
+    for code in item.e_numbers.iter().chain(&item.ins_numbers) {
+        if additive_codes::normalize_ins(code).is_none() {
+            problems.push(format!("无法识别的 E/INS 编号：{}", code));
+        }
+    }
+    for code in &item.cns_codes {
+        if additive_codes::normalize_cns(code).is_none() {
+            problems.push(format!("无法识别的 CNS 编号：{}", code));
+        }
+    }
+    for allergen in &item.allergens {
+        if Allergen::parse(allergen).map(Allergen::as_key) != Some(allergen.as_str()) {
+            problems.push(format!("未知过敏原：{}", allergen));
+        }
+    }
+
+    problems
+}
+
+/// Check a whole rule file: every rule's fields, duplicate IDs, and names or aliases
+/// shared between rules. Each collision is reported once.
+pub fn validate_rule_set(items: &[RuleItem]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut ids = HashSet::new();
+    for item in items {
+        if !ids.insert(item.id.as_str()) {
+            problems.push(format!("{}: 规则 ID 重复", item.id));
+        }
+        problems.extend(
+            validate_rule(item)
+                .into_iter()
+                .map(|problem| format!("{}: {}", item.id, problem)),
+        );
+    }
+
+    for (index, item) in items.iter().enumerate() {
+        for conflict in find_key_conflicts(item, &items[index + 1..]) {
+            problems.push(format!(
+                "{}: {} 已被规则 {} 使用",
+                item.id, conflict.key, conflict.other_id
+            ));
+        }
+    }
+    problems
+}
+
+/// Names and aliases of `item` that another rule already matches on
+pub fn find_key_conflicts(item: &RuleItem, others: &[RuleItem]) -> Vec<KeyConflict> {
+    let keys: Vec<String> = rule_keys(item);
+    let mut conflicts = Vec::new();
+    for other in others.iter().filter(|other| other.id != item.id) {
+        let other_keys = rule_keys(other);
+        for key in &keys {
+            if other_keys.contains(key) {
+                conflicts.push(KeyConflict {
+                    key: key.clone(),
+                    other_id: other.id.clone(),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// Normalized name and aliases the rule engine matches a rule on
+pub fn rule_keys(item: &RuleItem) -> Vec<String> {
+    std::iter::once(&item.name)
+        .chain(&item.aliases)
+        .map(|value| normalize_token(value))
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+fn valid_text(value: &str, max_length: usize) -> bool {
+    let length = value.trim().chars().count();
+    length > 0 && length <= max_length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, name: &str, aliases: &[&str]) -> RuleItem {
+        RuleItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: "additive".to_string(),
+            risk_level: "low".to_string(),
+            groups: vec!["kids".to_string()],
+            description: "常见防腐剂".to_string(),
+            evidence: None,
+            source: None,
+            e_numbers: vec!["E202".to_string()],
+            ins_numbers: Vec::new(),
+            cns_codes: vec!["17.004".to_string()],
+            allergens: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn accepts_well_formed_rule() {
+        let rule = item("additive-potassium-sorbate", "山梨酸钾", &["山梨酸"]);
+        assert!(validate_rule(&rule).is_empty());
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let mut rule = item("Bad ID", " ", &["山梨酸", "山梨 酸"]);
+        rule.category = "drink".to_string();
+        rule.risk_level = "severe".to_string();
+        rule.groups.push("elderly".to_string());
+        rule.cns_codes.push("17-004".to_string());
+        rule.allergens.push("durian".to_string());
+        let problems = validate_rule(&rule);
+        assert_eq!(problems.len(), 8, "{problems:?}");
+    }
+
+    #[test]
+    fn diet_rules_need_diet_tags() {
+        let mut rule = item("diet-honey", "蜂蜜", &[]);
+        rule.category = DIET_CATEGORY.to_string();
+        assert_eq!(validate_rule(&rule), vec!["饮食规则需至少包含一个饮食标签"]);
+        rule.groups = vec!["not_vegan".to_string(), "maybe_not_halal".to_string()];
+        assert!(validate_rule(&rule).is_empty());
+    }
+
+    #[test]
+    fn rule_set_reports_duplicate_ids_and_shared_aliases_once() {
+        let items = vec![
+            item("additive-potassium-sorbate", "山梨酸钾", &["山梨酸"]),
+            item("additive-sorbic-acid", "山梨酸", &[]),
+            item("additive-sorbic-acid", "山梨酸(E200)", &[]),
+        ];
+        let problems = validate_rule_set(&items);
+        assert_eq!(
+            problems,
+            vec![
+                "additive-sorbic-acid: 规则 ID 重复".to_string(),
+                "additive-potassium-sorbate: 山梨酸 已被规则 additive-sorbic-acid 使用".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_keys_shared_with_other_rules() {
+        let rule = item("additive-potassium-sorbate", "山梨酸钾", &["山梨酸"]);
+        let others = vec![
+            item("additive-potassium-sorbate", "山梨酸钾", &[]),
+            item("additive-sorbic-acid", "山梨酸", &[]),
+        ];
+        assert_eq!(
+            find_key_conflicts(&rule, &others),
+            vec![KeyConflict {
+                key: "山梨酸".to_string(),
+                other_id: "additive-sorbic-acid".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Versioned snapshots of the enabled rule set
+
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+
+use crate::services::rules::RuleItem;
+use shared::{RuleChange, RuleSetDiff, RuleSummary};
+
+/// Advisory lock key serializing snapshot creation across instances
+const SNAPSHOT_LOCK_KEY: i64 = 0x7275_6c65_7365_7473;
+
+/// SHA-256 of the rule set, independent of rule and list order
+pub fn content_hash(items: &[RuleItem]) -> String {
+    let canonical = canonicalize(items);
+    let bytes = serde_json::to_vec(&canonical).unwrap_or_default();
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Return the version of the given rule set, recording a new snapshot when its content
+/// differs from the latest one.
+///
+/// Instances reloading at the same time all resolve to the same version; reverting to an
+/// earlier rule set creates a new version rather than reusing the old number, so versions
+/// always increase with time.
+pub async fn record_snapshot(pool: &PgPool, items: &[RuleItem]) -> anyhow::Result<i64> {
+    let hash = content_hash(items);
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT pg_advisory_xact_lock($1)")
+        .bind(SNAPSHOT_LOCK_KEY)
+        .execute(&mut *tx)
+        .await?;
+
+    let latest = sqlx::query_as::<_, (i64, String)>(
+        "SELECT version, content_hash FROM rule_set_versions ORDER BY version DESC LIMIT 1",
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some((version, latest_hash)) = latest {
+        if latest_hash == hash {
+            tx.commit().await?;
+            return Ok(version);
+        }
+    }
+
+    let version = sqlx::query_scalar::<_, i64>(
+        "INSERT INTO rule_set_versions (content_hash, rule_count, rules) \
+         VALUES ($1, $2, $3) RETURNING version",
+    )
+    .bind(&hash)
+    .bind(items.len() as i32)
+    .bind(serde_json::to_value(canonicalize(items))?)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(version)
+}
+
+/// Rules and aliases added, removed or changed between two rule sets
+#[derive(Debug, Clone, Default)]
+pub struct RuleDiff {
+    pub added: Vec<RuleSummary>,
+    pub removed: Vec<RuleSummary>,
+    pub changed: Vec<RuleChange>,
+}
+
+/// Compare two rule sets; list fields are compared regardless of order
+pub fn diff_rules(from: &[RuleItem], to: &[RuleItem]) -> RuleDiff {
+    let from = canonicalize(from);
+    let to = canonicalize(to);
+
+    let mut diff = RuleDiff::default();
+    for item in &to {
+        match from.iter().find(|old| old.id == item.id) {
+            None => diff.added.push(summary(item)),
+            Some(old) => {
+                if let Some(change) = compare(old, item) {
+                    diff.changed.push(change);
+                }
+            }
+        }
+    }
+    diff.removed = from
+        .iter()
+        .filter(|old| !to.iter().any(|item| item.id == old.id))
+        .map(summary)
+        .collect();
+    diff
+}
+
+/// Compare two stored rule set versions
+pub fn diff_rule_sets(
+    from_version: i64,
+    from: &[RuleItem],
+    to_version: i64,
+    to: &[RuleItem],
+) -> RuleSetDiff {
+    let diff = diff_rules(from, to);
+    RuleSetDiff {
+        from_version,
+        to_version,
+        added: diff.added,
+        removed: diff.removed,
+        changed: diff.changed,
+    }
+}
+
+fn compare(old: &RuleItem, new: &RuleItem) -> Option<RuleChange> {
+    let mut changed_fields = Vec::new();
+    let mut check = |field: &str, differs: bool| {
+        if differs {
+            changed_fields.push(field.to_string());
+        }
+    };
+    check("name", old.name != new.name);
+    check("category", old.category != new.category);
+    check("risk_level", old.risk_level != new.risk_level);
+    check("groups", old.groups != new.groups);
+    check("description", old.description != new.description);
+    check("evidence", old.evidence != new.evidence);
+    check("source", old.source != new.source);
+    check("e_numbers", old.e_numbers != new.e_numbers);
+    check("ins_numbers", old.ins_numbers != new.ins_numbers);
+    check("cns_codes", old.cns_codes != new.cns_codes);
+    check("allergens", old.allergens != new.allergens);
+
+    let added_aliases: Vec<String> = new
+        .aliases
+        .iter()
+        .filter(|alias| !old.aliases.contains(alias))
+        .cloned()
+        .collect();
+    let removed_aliases: Vec<String> = old
+        .aliases
+        .iter()
+        .filter(|alias| !new.aliases.contains(alias))
+        .cloned()
+        .collect();
+
+    if changed_fields.is_empty() && added_aliases.is_empty() && removed_aliases.is_empty() {
+        return None;
+    }
+    Some(RuleChange {
+        id: new.id.clone(),
+        name: new.name.clone(),
+        changed_fields,
+        added_aliases,
+        removed_aliases,
+    })
+}
+
+fn summary(item: &RuleItem) -> RuleSummary {
+    RuleSummary {
+        id: item.id.clone(),
+        name: item.name.clone(),
+    }
+}
+
+/// Rules sorted by ID with every list field sorted, so equal content serializes equally
+fn canonicalize(items: &[RuleItem]) -> Vec<RuleItem> {
+    let mut items = items.to_vec();
+    for item in &mut items {
+        item.aliases.sort();
+        item.groups.sort();
+        item.e_numbers.sort();
+        item.ins_numbers.sort();
+        item.cns_codes.sort();
+        item.allergens.sort();
+    }
+    items.sort_by(|a, b| a.id.cmp(&b.id));
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, name: &str, aliases: &[&str]) -> RuleItem {
+        RuleItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: "additive".to_string(),
+            risk_level: "low".to_string(),
+            groups: vec!["kids".to_string(), "health".to_string()],
+            description: "常见防腐剂".to_string(),
+            evidence: None,
+            source: None,
+            e_numbers: Vec::new(),
+            ins_numbers: Vec::new(),
+            cns_codes: Vec::new(),
+            allergens: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hash_ignores_order() {
+        let a = item(
+            "additive-sodium-benzoate",
+            "苯甲酸钠",
+            &["苯甲酸", "安息香酸钠"],
+        );
+        let b = item("additive-potassium-sorbate", "山梨酸钾", &[]);
+        let mut reordered = a.clone();
+        reordered.aliases.reverse();
+        reordered.groups.reverse();
+
+        assert_eq!(
+            content_hash(&[a.clone(), b.clone()]),
+            content_hash(&[b.clone(), reordered])
+        );
+        let mut riskier = a.clone();
+        riskier.risk_level = "high".to_string();
+        assert_ne!(content_hash(&[a, b.clone()]), content_hash(&[riskier, b]));
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_rules() {
+        let from = vec![
+            item("additive-sodium-benzoate", "苯甲酸钠", &["苯甲酸"]),
+            item("additive-potassium-sorbate", "山梨酸钾", &[]),
+        ];
+        let mut benzoate = item("additive-sodium-benzoate", "苯甲酸钠", &["安息香酸钠"]);
+        benzoate.risk_level = "medium".to_string();
+        let to = vec![benzoate, item("additive-aspartame", "阿斯巴甜", &[])];
+
+        let diff = diff_rule_sets(1, &from, 2, &to);
+        assert_eq!(diff.added[0].id, "additive-aspartame");
+        assert_eq!(diff.removed[0].id, "additive-potassium-sorbate");
+        assert_eq!(
+            diff.changed,
+            vec![RuleChange {
+                id: "additive-sodium-benzoate".to_string(),
+                name: "苯甲酸钠".to_string(),
+                changed_fields: vec!["risk_level".to_string()],
+                added_aliases: vec!["安息香酸钠".to_string()],
+                removed_aliases: vec!["苯甲酸".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn reordered_lists_are_not_changes() {
+        let from = vec![item("additive-sodium-benzoate", "苯甲酸钠", &["a", "b"])];
+        let to = vec![item("additive-sodium-benzoate", "苯甲酸钠", &["b", "a"])];
+        assert!(diff_rules(&from, &to).changed.is_empty());
+    }
+}
@@ -1,12 +1,23 @@
 //! Rule-based ingredient analysis
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::PgListener;
 use sqlx::PgPool;
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
 
-use crate::services::llm::PreferenceType;
+use crate::services::additive_codes;
+use crate::services::ingredient_parser;
+use crate::services::llm::{PreferenceBlend, PreferenceType, MIN_RISK_BUMP_WEIGHT};
+use crate::services::rule_matcher::{KeyMatch, MatchKind, Matcher};
+use crate::services::rule_overlays;
+use crate::services::rule_validation;
+use crate::services::rule_versions;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuleItem {
     pub id: String,
     pub name: String,
@@ -21,6 +32,31 @@ pub struct RuleItem {
     pub evidence: Option<String>,
     #[serde(default)]
     pub source: Option<String>,
+    /// E-numbers, e.g. `E211`
+    #[serde(default)]
+    pub e_numbers: Vec<String>,
+    /// INS numbers, e.g. `211`
+    #[serde(default)]
+    pub ins_numbers: Vec<String>,
+    /// GB 2760 CNS codes, e.g. `17.003`
+    #[serde(default)]
+    pub cns_codes: Vec<String>,
+    /// Allergen keys the rule detects, e.g. `peanut`
+    #[serde(default)]
+    pub allergens: Vec<String>,
+}
+
+/// Category of rules that only carry diet tags; their matches feed the diet verdicts
+/// and never become rule hits
+pub const DIET_CATEGORY: &str = "diet";
+
+impl RuleItem {
+    /// Whether any group tag is a diet tag such as `not_vegan`
+    pub fn has_diet_tags(&self) -> bool {
+        self.groups
+            .iter()
+            .any(|group| shared::DietRegime::is_diet_tag(group))
+    }
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -34,60 +70,67 @@ struct RuleRow {
     description: String,
     evidence: Option<String>,
     source: Option<String>,
+    e_numbers: Vec<String>,
+    ins_numbers: Vec<String>,
+    cns_codes: Vec<String>,
+    allergens: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct RuleEvaluation {
     pub hits: Vec<shared::RuleHit>,
-    pub confidence: shared::ConfidenceInfo,
+    /// Matches of rules carrying diet tags, one per rule and ingredient, including the
+    /// diet-only rules left out of `hits`
+    pub diet_hits: Vec<shared::RuleHit>,
+    /// Why the rule library is unavailable, when no rules could be loaded
+    pub load_error: Option<String>,
+    /// Parsed ingredient tree with matched rule names on each node
+    pub ingredients: Vec<shared::IngredientNode>,
+    /// Version of the rule set that produced the hits; `None` for rules loaded from file
+    pub rule_set_version: Option<i64>,
 }
 
 #[derive(Clone)]
 pub struct RuleEngine {
     items: Vec<RuleItem>,
     lookup: HashMap<String, RuleItem>,
+    code_lookup: HashMap<String, RuleItem>,
+    matcher: Matcher,
+    version: Option<i64>,
     load_error: Option<String>,
 }
 
 impl RuleEngine {
     pub fn load_from_path(path: &str) -> Self {
         match load_items_from_path(path) {
-            Ok(items) => Self::build(items),
+            Ok(items) => {
+                for problem in rule_validation::validate_rule_set(&items) {
+                    warn!("rules file {}: {}", path, problem);
+                }
+                Self::from_items(items)
+            }
             Err(err) => Self {
                 items: Vec::new(),
                 lookup: HashMap::new(),
+                code_lookup: HashMap::new(),
+                matcher: Matcher::default(),
+                version: None,
                 load_error: Some(format!("rules load failed: {}", err)),
             },
         }
     }
 
+    /// Load enabled rules and resolve their rule set version, snapshotting new content
     pub async fn try_load_from_db(pool: &PgPool) -> anyhow::Result<Self> {
-        let rows = sqlx::query_as::<_, RuleRow>(
-            "SELECT id, name, aliases, category, risk_level, groups, description, evidence, source \
-             FROM rules WHERE enabled = true",
-        )
-        .fetch_all(pool)
-        .await?;
-
-        let items = rows
-            .into_iter()
-            .map(|row| RuleItem {
-                id: row.id,
-                name: row.name,
-                aliases: row.aliases,
-                category: row.category,
-                risk_level: row.risk_level,
-                groups: row.groups,
-                description: row.description,
-                evidence: row.evidence,
-                source: row.source,
-            })
-            .collect();
-
-        Ok(Self::build(items))
+        let items = load_enabled_items(pool).await?;
+        let version = rule_versions::record_snapshot(pool, &items).await?;
+        let mut engine = Self::from_items(items);
+        engine.version = Some(version);
+        Ok(engine)
     }
 
-    fn build(items: Vec<RuleItem>) -> Self {
+    /// Build an engine over the given rules, without a rule set version
+    pub fn from_items(items: Vec<RuleItem>) -> Self {
         let mut lookup = HashMap::new();
         for item in &items {
             let normalized = normalize_token(&item.name);
@@ -96,156 +139,297 @@ impl RuleEngine {
                 lookup.insert(normalize_token(alias), item.clone());
             }
         }
+        let mut code_lookup = HashMap::new();
+        for item in &items {
+            for key in rule_code_keys(item) {
+                code_lookup.insert(key, item.clone());
+            }
+        }
+        let matcher = Matcher::new(lookup.keys().cloned());
         Self {
             items,
             lookup,
+            code_lookup,
+            matcher,
+            version: None,
             load_error: None,
         }
     }
 
-    pub fn evaluate(&self, text: &str, preference: PreferenceType) -> RuleEvaluation {
+    /// This engine with a user's overlays applied, keeping the global rule set version
+    pub fn with_overlays(&self, overlays: &[shared::RuleOverlay]) -> Self {
+        if overlays.is_empty() || self.load_error.is_some() {
+            return self.clone();
+        }
+        let mut engine = Self::from_items(rule_overlays::overlay_items(&self.items, overlays));
+        engine.version = self.version;
+        engine
+    }
+
+    pub fn evaluate(&self, text: &str, preference: &PreferenceBlend) -> RuleEvaluation {
         if let Some(error) = &self.load_error {
             return RuleEvaluation {
                 hits: Vec::new(),
-                confidence: shared::ConfidenceInfo {
-                    level: "low".to_string(),
-                    reasons: vec![format!("规则库不可用：{}", error)],
-                    factors: vec![shared::ConfidenceFactor {
-                        key: "rule_engine".to_string(),
-                        label: "规则引擎".to_string(),
-                        score: -30,
-                        detail: Some("规则库加载失败".to_string()),
-                    }],
-                },
+                diet_hits: Vec::new(),
+                load_error: Some(error.clone()),
+                ingredients: ingredient_parser::parse_ingredients(text),
+                rule_set_version: None,
             };
         }
 
-        let mut hits = Vec::new();
-        let mut seen = HashSet::new();
-        for token in split_ingredients(text) {
-            if token.is_empty() {
-                continue;
+        let mut ingredients = ingredient_parser::parse_ingredients(text);
+        let mut collectors = Collectors::default();
+        self.evaluate_nodes(&mut ingredients, None, preference, &mut collectors);
+        RuleEvaluation {
+            hits: collectors.hits.hits,
+            diet_hits: collectors.diet_hits.hits,
+            load_error: None,
+            ingredients,
+            rule_set_version: self.version,
+        }
+    }
+
+    /// Match every node of the ingredient tree, recording rule names on the nodes.
+    ///
+    /// Hits on sub-ingredients carry the position of their top-level ingredient, since
+    /// only the top-level list is ordered by weight.
+    fn evaluate_nodes(
+        &self,
+        nodes: &mut [shared::IngredientNode],
+        top_position: Option<u32>,
+        preference: &PreferenceBlend,
+        collectors: &mut Collectors,
+    ) {
+        for node in nodes {
+            let position = top_position.unwrap_or(node.position);
+            let token = normalize_token(&node.name);
+            let mut matches = self.match_token(&token);
+            // Annotations only contribute additive codes, e.g. `苯甲酸钠(E211)`.
+            for annotation in &node.annotations {
+                let annotation = format!("({})", normalize_token(annotation));
+                matches.extend(self.match_codes(&annotation));
             }
-            if let Some(item) = self.lookup.get(&token) {
-                if !seen.insert(item.id.clone()) {
-                    continue;
+            for (item, key_match) in matches {
+                if !node.matched_rules.contains(&item.name) {
+                    node.matched_rules.push(item.name.clone());
+                }
+                let mut hit = build_hit(item, &token, &key_match, preference);
+                hit.position = Some(position);
+                hit.declared_percentage = node.percentage;
+                if item.has_diet_tags() {
+                    let key = format!("{}:{}", item.id, token);
+                    collectors.diet_hits.add(&key, hit.clone());
                 }
-                let mut risk_level = item.risk_level.clone();
-                if should_raise_risk(preference, &item.groups) {
-                    risk_level = bump_risk(&risk_level);
+                if item.category != DIET_CATEGORY {
+                    collectors.hits.add(&item.id, hit);
                 }
-                hits.push(shared::RuleHit {
-                    name: item.name.clone(),
-                    category: item.category.clone(),
-                    risk_level,
-                    description: item.description.clone(),
-                    group_tags: item.groups.clone(),
-                    evidence: item.evidence.clone(),
-                    source: item.source.clone(),
-                });
             }
+            self.evaluate_nodes(&mut node.children, Some(position), preference, collectors);
         }
+    }
 
-        let confidence = build_confidence(&hits, text);
+    /// Rules matched by a token through additive codes or names/aliases
+    fn match_token(&self, token: &str) -> Vec<(&RuleItem, KeyMatch)> {
+        let names = self
+            .matcher
+            .match_token(token)
+            .into_iter()
+            .filter_map(|key_match| Some((self.lookup.get(&key_match.key)?, key_match)));
+        self.match_codes(token).into_iter().chain(names).collect()
+    }
 
-        RuleEvaluation { hits, confidence }
+    /// Rules matched by additive codes only
+    fn match_codes(&self, token: &str) -> Vec<(&RuleItem, KeyMatch)> {
+        additive_codes::extract_code_keys(token)
+            .into_iter()
+            .filter_map(|key| {
+                let item = self.code_lookup.get(&key)?;
+                Some((
+                    item,
+                    KeyMatch {
+                        key,
+                        kind: MatchKind::Code,
+                        score: 1.0,
+                    },
+                ))
+            })
+            .collect()
     }
 }
 
-pub fn load_items_from_path(path: &str) -> anyhow::Result<Vec<RuleItem>> {
-    let content = std::fs::read_to_string(path)?;
-    let items = serde_json::from_str::<Vec<RuleItem>>(&content)?;
-    Ok(items)
+/// Rule hits in first-seen order, one per key
+#[derive(Default)]
+struct HitCollector {
+    hits: Vec<shared::RuleHit>,
+    seen: HashMap<String, usize>,
 }
 
-fn build_confidence(hits: &[shared::RuleHit], text: &str) -> shared::ConfidenceInfo {
-    let text_len = text.trim().chars().count();
-    let hit_count = hits.len();
-    let mut score = 50;
-
-    let mut factors = Vec::new();
-    if hit_count > 0 {
-        score += 30;
-        factors.push(shared::ConfidenceFactor {
-            key: "rule_hits".to_string(),
-            label: "规则命中".to_string(),
-            score: 30,
-            detail: Some(format!("命中 {} 条规则", hit_count)),
-        });
-    } else {
-        score -= 10;
-        factors.push(shared::ConfidenceFactor {
-            key: "rule_hits".to_string(),
-            label: "规则命中".to_string(),
-            score: -10,
-            detail: Some("未命中规则".to_string()),
-        });
-    }
+/// Hits gathered over one ingredient tree
+#[derive(Default)]
+struct Collectors {
+    /// Keyed by rule ID
+    hits: HitCollector,
+    /// Hits of rules with diet tags, keyed by rule ID and matched token
+    diet_hits: HitCollector,
+}
 
-    if text_len < 6 {
-        score -= 20;
-        factors.push(shared::ConfidenceFactor {
-            key: "text_length".to_string(),
-            label: "文本长度".to_string(),
-            score: -20,
-            detail: Some("文本过短".to_string()),
-        });
-    } else if text_len < 20 {
-        score -= 10;
-        factors.push(shared::ConfidenceFactor {
-            key: "text_length".to_string(),
-            label: "文本长度".to_string(),
-            score: -10,
-            detail: Some("文本偏短".to_string()),
-        });
-    } else {
-        factors.push(shared::ConfidenceFactor {
-            key: "text_length".to_string(),
-            label: "文本长度".to_string(),
-            score: 10,
-            detail: Some("文本长度充足".to_string()),
-        });
-        score += 10;
+impl HitCollector {
+    fn add(&mut self, key: &str, hit: shared::RuleHit) {
+        match self.seen.get(key) {
+            Some(&index) => {
+                // Keep the best-quality match for a rule seen in several places.
+                if hit.match_score > self.hits[index].match_score {
+                    self.hits[index] = hit;
+                }
+            }
+            None => {
+                self.seen.insert(key.to_string(), self.hits.len());
+                self.hits.push(hit);
+            }
+        }
     }
+}
 
-    let level = if score >= 75 {
-        "high"
-    } else if score >= 50 {
-        "medium"
-    } else {
-        "low"
-    };
-
-    let mut reasons = Vec::new();
-    if hit_count > 0 {
-        reasons.push("命中规则库成分".to_string());
-    } else {
-        reasons.push("未命中规则，基于模型解释".to_string());
+/// Code lookup keys for a rule; E-numbers and INS numbers share one key space
+fn rule_code_keys(item: &RuleItem) -> Vec<String> {
+    let ins = item
+        .e_numbers
+        .iter()
+        .chain(&item.ins_numbers)
+        .filter_map(|raw| additive_codes::normalize_ins(raw))
+        .map(|value| additive_codes::ins_key(&value));
+    let cns = item
+        .cns_codes
+        .iter()
+        .filter_map(|raw| additive_codes::normalize_cns(raw))
+        .map(|value| additive_codes::cns_key(&value));
+    let mut keys: Vec<String> = ins.chain(cns).collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+fn build_hit(
+    item: &RuleItem,
+    token: &str,
+    key_match: &KeyMatch,
+    preference: &PreferenceBlend,
+) -> shared::RuleHit {
+    let mut risk_level = item.risk_level.clone();
+    if should_raise_risk(preference, &item.groups) {
+        risk_level = bump_risk(&risk_level);
+    }
+    shared::RuleHit {
+        rule_id: Some(item.id.clone()),
+        name: item.name.clone(),
+        category: item.category.clone(),
+        risk_level,
+        description: item.description.clone(),
+        group_tags: item.groups.clone(),
+        evidence: item.evidence.clone(),
+        source: item.source.clone(),
+        matched_text: Some(token.to_string()),
+        match_type: Some(key_match.kind.as_key().to_string()),
+        match_score: Some(key_match.score),
+        position: None,
+        declared_percentage: None,
+        score_impact: None,
+        allergens: item.allergens.clone(),
     }
-    if text_len < 6 {
-        reasons.push("OCR 文本过短，可信度降低".to_string());
+}
+
+/// Postgres channel notified by a trigger whenever the rules table changes
+pub const RULES_CHANGED_CHANNEL: &str = "rules_changed";
+
+/// Reload the shared rule engine whenever the rules table changes.
+///
+/// Notifications sent while the listener is disconnected are lost, so the rules are also
+/// reloaded every time the listener (re)connects.
+pub async fn watch_rule_changes(pool: PgPool, rules: Arc<RwLock<RuleEngine>>) {
+    const RETRY_DELAY: Duration = Duration::from_secs(5);
+    loop {
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(listener) => listener,
+            Err(err) => {
+                warn!("failed to connect rules listener: {}", err);
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+        if let Err(err) = listener.listen(RULES_CHANGED_CHANNEL).await {
+            warn!("failed to listen for rule changes: {}", err);
+            tokio::time::sleep(RETRY_DELAY).await;
+            continue;
+        }
+
+        reload_rules(&pool, &rules).await;
+        loop {
+            match listener.try_recv().await {
+                Ok(Some(_)) => reload_rules(&pool, &rules).await,
+                Ok(None) => {
+                    warn!("rules listener connection lost, reconnecting");
+                    break;
+                }
+                Err(err) => {
+                    warn!("rules listener failed: {}", err);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    break;
+                }
+            }
+        }
     }
+}
 
-    shared::ConfidenceInfo {
-        level: level.to_string(),
-        reasons,
-        factors,
+async fn reload_rules(pool: &PgPool, rules: &RwLock<RuleEngine>) {
+    match RuleEngine::try_load_from_db(pool).await {
+        Ok(engine) => {
+            let count = engine.items.len();
+            let version = engine.version;
+            *rules.write().await = engine;
+            info!(rules = count, version, "rules reloaded");
+        }
+        Err(err) => warn!("failed to reload rules from db: {}", err),
     }
 }
 
-fn split_ingredients(text: &str) -> Vec<String> {
-    let cleaned = text
-        .replace("配料表", "")
-        .replace("配料", "")
-        .replace('：', ":");
-    cleaned
-        .split(|c| matches!(c, ',' | '，' | '、' | ';' | '；' | '\n' | '/' | '|'))
-        .map(|item| normalize_token(item))
-        .filter(|item| !item.is_empty())
-        .collect()
+/// Enabled rules from the rules table
+pub async fn load_enabled_items(pool: &PgPool) -> anyhow::Result<Vec<RuleItem>> {
+    let rows = sqlx::query_as::<_, RuleRow>(
+        "SELECT id, name, aliases, category, risk_level, groups, description, evidence, source, \
+                e_numbers, ins_numbers, cns_codes, allergens \
+         FROM rules WHERE enabled = true ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| RuleItem {
+            id: row.id,
+            name: row.name,
+            aliases: row.aliases,
+            category: row.category,
+            risk_level: row.risk_level,
+            groups: row.groups,
+            description: row.description,
+            evidence: row.evidence,
+            source: row.source,
+            e_numbers: row.e_numbers,
+            ins_numbers: row.ins_numbers,
+            cns_codes: row.cns_codes,
+            allergens: row.allergens,
+        })
+        .collect();
+    Ok(items)
+}
+
+pub fn load_items_from_path(path: &str) -> anyhow::Result<Vec<RuleItem>> {
+    let content = std::fs::read_to_string(path)?;
+    let items = serde_json::from_str::<Vec<RuleItem>>(&content)?;
+    Ok(items)
 }
 
-fn normalize_token(value: &str) -> String {
+pub(crate) fn normalize_token(value: &str) -> String {
     value
         .trim()
         .to_lowercase()
@@ -254,16 +438,36 @@ fn normalize_token(value: &str) -> String {
         .replace('）', ")")
 }
 
-fn should_raise_risk(preference: PreferenceType, groups: &[String]) -> bool {
-    let tag = match preference {
-        PreferenceType::Allergy => "allergy",
-        PreferenceType::Kids => "kids",
-        PreferenceType::WeightLoss => "weight_loss",
-        PreferenceType::Health => "health",
-        PreferenceType::Fitness => "fitness",
-        PreferenceType::None => return false,
-    };
-    groups.iter().any(|group| group == tag)
+/// Equal after normalization, or one name contains the other
+pub(crate) fn names_match(a: &str, b: &str) -> bool {
+    let a = normalize_token(a);
+    let b = normalize_token(b);
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    a == b
+        || (a.chars().count() >= 2 && b.contains(&a))
+        || (b.chars().count() >= 2 && a.contains(&b))
+}
+
+/// Raise the risk when the focuses whose group the rule carries hold enough of the weight
+fn should_raise_risk(preference: &PreferenceBlend, groups: &[String]) -> bool {
+    let weight = preference.weight_where(|focus| {
+        rule_group(focus).is_some_and(|tag| groups.iter().any(|group| group == tag))
+    });
+    weight >= MIN_RISK_BUMP_WEIGHT
+}
+
+/// Rule group whose rules a focus raises
+fn rule_group(preference: PreferenceType) -> Option<&'static str> {
+    match preference {
+        PreferenceType::Allergy => Some("allergy"),
+        PreferenceType::Kids => Some("kids"),
+        PreferenceType::WeightLoss => Some("weight_loss"),
+        PreferenceType::Health => Some("health"),
+        PreferenceType::Fitness => Some("fitness"),
+        PreferenceType::None => None,
+    }
 }
 
 fn bump_risk(level: &str) -> String {
@@ -275,3 +479,128 @@ fn bump_risk(level: &str) -> String {
     }
     .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, name: &str, aliases: &[&str]) -> RuleItem {
+        RuleItem {
+            id: id.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            category: "additive".to_string(),
+            risk_level: "low".to_string(),
+            groups: vec!["kids".to_string()],
+            description: String::new(),
+            evidence: None,
+            source: None,
+            e_numbers: Vec::new(),
+            ins_numbers: Vec::new(),
+            cns_codes: Vec::new(),
+            allergens: Vec::new(),
+        }
+    }
+
+    fn engine() -> RuleEngine {
+        let mut sorbate = item("additive-potassium-sorbate", "山梨酸钾", &["山梨酸"]);
+        sorbate.e_numbers = vec!["E202".to_string(), "E200".to_string()];
+        sorbate.cns_codes = vec!["17.003".to_string(), "17.004".to_string()];
+        let mut benzoate = item("additive-sodium-benzoate", "苯甲酸钠", &[]);
+        benzoate.ins_numbers = vec!["211".to_string()];
+        RuleEngine::from_items(vec![sorbate, benzoate])
+    }
+
+    #[test]
+    fn matches_rules_inside_longer_tokens() {
+        let evaluation = engine().evaluate(
+            "配料：水、山梨酸钾粉、苯申酸钠",
+            &PreferenceBlend::default(),
+        );
+        let names: Vec<&str> = evaluation.hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["山梨酸钾", "苯甲酸钠"]);
+        assert_eq!(evaluation.hits[0].match_type.as_deref(), Some("substring"));
+        assert_eq!(evaluation.hits[1].match_type.as_deref(), Some("fuzzy"));
+    }
+
+    #[test]
+    fn keeps_best_match_per_rule() {
+        let evaluation =
+            engine().evaluate("山梨酸钾(防腐剂)，山梨酸钾", &PreferenceType::Kids.into());
+        assert_eq!(evaluation.hits.len(), 1);
+        assert_eq!(evaluation.hits[0].match_type.as_deref(), Some("exact"));
+        assert_eq!(evaluation.hits[0].risk_level, "medium");
+    }
+
+    #[test]
+    fn minor_focuses_do_not_raise_risk() {
+        let blend = PreferenceBlend::from_weights([
+            (PreferenceType::Health, 3.0),
+            (PreferenceType::Kids, 1.0),
+        ]);
+        let evaluation = engine().evaluate("山梨酸钾", &blend);
+        assert_eq!(evaluation.hits[0].risk_level, "medium");
+
+        let blend = PreferenceBlend::from_weights([
+            (PreferenceType::Health, 4.0),
+            (PreferenceType::Kids, 1.0),
+        ]);
+        let evaluation = engine().evaluate("山梨酸钾", &blend);
+        assert_eq!(evaluation.hits[0].risk_level, "low");
+    }
+
+    #[test]
+    fn matches_rules_written_only_as_codes() {
+        let evaluation = engine().evaluate("水、E211、INS 202", &PreferenceBlend::default());
+        let names: Vec<&str> = evaluation.hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["苯甲酸钠", "山梨酸钾"]);
+        assert!(evaluation
+            .hits
+            .iter()
+            .all(|hit| hit.match_type.as_deref() == Some("code")));
+
+        let evaluation = engine().evaluate("防腐剂（CNS 17.003）", &PreferenceBlend::default());
+        assert_eq!(evaluation.hits.len(), 1);
+        assert_eq!(evaluation.hits[0].name, "山梨酸钾");
+    }
+
+    #[test]
+    fn evaluates_every_node_of_compound_ingredients() {
+        let evaluation = engine().evaluate(
+            "复合调味料（食用盐、山梨酸钾）、苯甲酸钠(E211)",
+            &PreferenceBlend::default(),
+        );
+        let names: Vec<&str> = evaluation.hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["山梨酸钾", "苯甲酸钠"]);
+        assert_eq!(evaluation.hits[0].matched_text.as_deref(), Some("山梨酸钾"));
+        assert_eq!(evaluation.hits[0].match_type.as_deref(), Some("exact"));
+
+        let compound = &evaluation.ingredients[0];
+        assert!(compound.matched_rules.is_empty());
+        assert_eq!(compound.children[1].matched_rules, vec!["山梨酸钾"]);
+        assert_eq!(evaluation.ingredients[1].matched_rules, vec!["苯甲酸钠"]);
+        let positions: Vec<Option<u32>> = evaluation.hits.iter().map(|h| h.position).collect();
+        assert_eq!(positions, vec![Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn diet_rules_match_per_ingredient_without_becoming_hits() {
+        let mut sugar = item("diet-sugar", "白砂糖", &["葡萄糖浆"]);
+        sugar.category = DIET_CATEGORY.to_string();
+        sugar.groups = vec!["not_keto".to_string()];
+        let mut milk = item("allergen-milk", "牛奶", &["奶粉"]);
+        milk.groups.push("not_vegan".to_string());
+        let engine = RuleEngine::from_items(vec![sugar, milk]);
+
+        let evaluation = engine.evaluate("白砂糖、奶粉、葡萄糖浆", &PreferenceBlend::default());
+        let names: Vec<&str> = evaluation.hits.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["牛奶"]);
+        let matched: Vec<&str> = evaluation
+            .diet_hits
+            .iter()
+            .filter_map(|h| h.matched_text.as_deref())
+            .collect();
+        assert_eq!(matched, vec!["白砂糖", "奶粉", "葡萄糖浆"]);
+        assert_eq!(evaluation.ingredients[0].matched_rules, vec!["白砂糖"]);
+    }
+}
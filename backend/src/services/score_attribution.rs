@@ -0,0 +1,363 @@
+//! Attribution of the health score to dimensions and ingredients
+//!
+//! A score starts at 100. The weighted dimension scores bring it to the base score, rule
+//! conflicts move it by up to ±10 and position weighting deducts the rest. Each stage is
+//! recovered from the finished result and its points are split across the dimensions or
+//! ingredients that caused them, so the entries always add up to the final score.
+
+use shared::{AnalysisResult, DimensionAttribution, IngredientAttribution, ScoreAttribution};
+
+use crate::services::confidence::normalize_risk;
+use crate::services::reconcile::MAX_CONFLICT_ADJUSTMENT;
+use crate::services::scoring::POSITION_WEIGHTING_DIMENSION;
+
+const STARTING_SCORE: i32 = 100;
+
+/// Dimension holding the whole base score when no weighted dimensions are known
+pub const OVERALL_DIMENSION: &str = "overall";
+
+/// Dimension holding points lost to rounding and the 0-100 bounds
+pub const ADJUSTMENT_DIMENSION: &str = "adjustment";
+
+pub const RULE_CONFLICT_SOURCE: &str = "rule_conflict";
+pub const POSITION_WEIGHTING_SOURCE: &str = "position_weighting";
+
+/// Weighted mean of the scored dimensions, as the health score before rule adjustments.
+///
+/// `weights` pairs an index into `score_breakdown` with that dimension's weight.
+pub fn weighted_score(result: &AnalysisResult, weights: &[(usize, f32)]) -> Option<i32> {
+    let breakdown = result.score_breakdown.as_deref().unwrap_or_default();
+    let mut weighted_sum = 0.0;
+    let mut weight_total = 0.0;
+    for &(index, weight) in weights {
+        if let Some(item) = breakdown.get(index) {
+            weighted_sum += item.score.clamp(0, 100) as f32 * weight;
+            weight_total += weight;
+        }
+    }
+    (weight_total > 0.0).then(|| ((weighted_sum / weight_total).round() as i32).clamp(0, 100))
+}
+
+/// Attribute a finished result's `health_score`.
+///
+/// `weights` are the dimension weights the result was scored with (see
+/// [`weighted_score`]); without them the base score is kept as one `overall` entry.
+pub fn attribute(result: &AnalysisResult, weights: &[(usize, f32)]) -> ScoreAttribution {
+    let final_score = result.health_score;
+    let breakdown = result.score_breakdown.as_deref().unwrap_or_default();
+    let position_points = breakdown
+        .iter()
+        .filter(|item| item.dimension == POSITION_WEIGHTING_DIMENSION)
+        .map(|item| item.score.min(0))
+        .sum::<i32>();
+    let reconciled = final_score - position_points;
+    let base = weighted_score(result, weights).unwrap_or_else(|| {
+        let adjustment = result
+            .rule_conflicts
+            .iter()
+            .map(|conflict| conflict.score_adjustment)
+            .sum::<i32>()
+            .clamp(-MAX_CONFLICT_ADJUSTMENT, MAX_CONFLICT_ADJUSTMENT);
+        (reconciled - adjustment).clamp(0, 100)
+    });
+
+    let mut dimensions = dimension_points(result, weights, base);
+    let mut ingredients = conflict_points(result, reconciled - base);
+    ingredients.extend(position_weighting_points(result, position_points));
+    ingredients.retain(|item| item.points != 0);
+    ingredients.sort_by_key(|item| item.points);
+
+    let attributed = dimensions.iter().map(|item| item.points).sum::<i32>()
+        + ingredients.iter().map(|item| item.points).sum::<i32>();
+    let remainder = final_score - STARTING_SCORE - attributed;
+    if remainder != 0 {
+        dimensions.push(DimensionAttribution {
+            dimension: ADJUSTMENT_DIMENSION.to_string(),
+            score: None,
+            weight: 0.0,
+            points: remainder,
+            reason: Some("取整与分数上下限".to_string()),
+        });
+    }
+
+    ScoreAttribution {
+        starting_score: STARTING_SCORE,
+        final_score,
+        dimensions,
+        ingredients,
+    }
+}
+
+/// Each dimension costs its weighted distance from a perfect score
+fn dimension_points(
+    result: &AnalysisResult,
+    weights: &[(usize, f32)],
+    base: i32,
+) -> Vec<DimensionAttribution> {
+    let breakdown = result.score_breakdown.as_deref().unwrap_or_default();
+    let scored: Vec<_> = weights
+        .iter()
+        .filter_map(|&(index, weight)| breakdown.get(index).map(|item| (item, weight)))
+        .collect();
+    let weight_total = scored.iter().map(|(_, weight)| weight).sum::<f32>();
+    if scored.is_empty() || weight_total <= 0.0 {
+        let reason = if breakdown
+            .iter()
+            .any(|item| item.dimension != POSITION_WEIGHTING_DIMENSION)
+        {
+            "综合各维度得分（评分时的偏好权重未记录）"
+        } else {
+            "模型给出的综合评分"
+        };
+        return vec![DimensionAttribution {
+            dimension: OVERALL_DIMENSION.to_string(),
+            score: Some(base),
+            weight: 1.0,
+            points: base - STARTING_SCORE,
+            reason: Some(reason.to_string()),
+        }];
+    }
+
+    let losses: Vec<f32> = scored
+        .iter()
+        .map(|(item, weight)| weight * (100 - item.score.clamp(0, 100)) as f32)
+        .collect();
+    let points = apportion(base - STARTING_SCORE, &losses);
+    scored
+        .into_iter()
+        .zip(points)
+        .map(|((item, weight), points)| DimensionAttribution {
+            dimension: item.dimension.clone(),
+            score: Some(item.score.clamp(0, 100)),
+            weight: weight / weight_total,
+            points,
+            reason: item.reason.clone(),
+        })
+        .collect()
+}
+
+/// Split the applied conflict adjustment by each conflict's own adjustment
+fn conflict_points(result: &AnalysisResult, applied: i32) -> Vec<IngredientAttribution> {
+    let shares: Vec<f32> = result
+        .rule_conflicts
+        .iter()
+        .map(|conflict| conflict.score_adjustment as f32)
+        .collect();
+    result
+        .rule_conflicts
+        .iter()
+        .zip(apportion(applied, &shares))
+        .map(|(conflict, points)| IngredientAttribution {
+            ingredient: conflict.ingredient.clone(),
+            rule_id: conflict.rule_id.clone(),
+            rule_name: Some(conflict.rule_name.clone()),
+            source: RULE_CONFLICT_SOURCE.to_string(),
+            points,
+            reason: format!(
+                "规则库判定为{}，模型判定为{}",
+                risk_label(&conflict.rule_risk_level),
+                risk_label(&conflict.llm_risk_level)
+            ),
+        })
+        .collect()
+}
+
+/// Split the applied position deduction by each hit's `score_impact`
+fn position_weighting_points(result: &AnalysisResult, applied: i32) -> Vec<IngredientAttribution> {
+    let hits: Vec<_> = result
+        .rule_hits
+        .iter()
+        .filter_map(|hit| hit.score_impact.map(|impact| (hit, impact)))
+        .collect();
+    let shares: Vec<f32> = hits.iter().map(|(_, impact)| *impact as f32).collect();
+    hits.into_iter()
+        .zip(apportion(applied, &shares))
+        .map(|((hit, _), points)| {
+            let placement = match (hit.declared_percentage, hit.position) {
+                (Some(percentage), _) => format!("标注含量 {}%", percentage),
+                (None, Some(position)) => format!("配料表第 {} 位", position),
+                (None, None) => "配料表位置未知".to_string(),
+            };
+            IngredientAttribution {
+                ingredient: hit.matched_text.clone().unwrap_or_else(|| hit.name.clone()),
+                rule_id: hit.rule_id.clone(),
+                rule_name: Some(hit.name.clone()),
+                source: POSITION_WEIGHTING_SOURCE.to_string(),
+                points,
+                reason: format!("{}的{}配料", placement, risk_label(&hit.risk_level)),
+            }
+        })
+        .collect()
+}
+
+fn risk_label(risk_level: &str) -> &str {
+    match normalize_risk(risk_level) {
+        Some("high") => "高风险",
+        Some("medium") => "中风险",
+        Some("low") => "低风险",
+        _ => "风险未知",
+    }
+}
+
+/// Split `total` into integers proportional to `shares` by largest remainder.
+///
+/// The parts add up to `total` unless every share is zero, in which case all are zero.
+fn apportion(total: i32, shares: &[f32]) -> Vec<i32> {
+    let share_total = shares.iter().map(|share| *share as f64).sum::<f64>();
+    if total == 0 || share_total == 0.0 {
+        return vec![0; shares.len()];
+    }
+    let exact: Vec<f64> = shares
+        .iter()
+        .map(|share| total as f64 * *share as f64 / share_total)
+        .collect();
+    let mut parts: Vec<i32> = exact.iter().map(|value| value.floor() as i32).collect();
+    let remainder = (total - parts.iter().sum::<i32>()).clamp(0, parts.len() as i32) as usize;
+    let mut order: Vec<usize> = (0..parts.len()).collect();
+    order.sort_by(|a, b| {
+        let fraction = |index: usize| exact[index] - exact[index].floor();
+        fraction(*b).total_cmp(&fraction(*a))
+    });
+    for index in order.into_iter().take(remainder) {
+        parts[index] += 1;
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{RuleConflict, RuleHit, ScoreBreakdown};
+
+    fn dimension(key: &str, score: i32) -> ScoreBreakdown {
+        ScoreBreakdown {
+            dimension: key.to_string(),
+            score,
+            reason: None,
+        }
+    }
+
+    fn hit(name: &str, position: u32, impact: i32) -> RuleHit {
+        RuleHit {
+            rule_id: Some(format!("rule-{}", name)),
+            name: name.to_string(),
+            category: "additive".to_string(),
+            risk_level: "high".to_string(),
+            description: String::new(),
+            group_tags: Vec::new(),
+            evidence: None,
+            source: None,
+            matched_text: Some(name.to_string()),
+            match_type: None,
+            match_score: None,
+            position: Some(position),
+            declared_percentage: None,
+            score_impact: Some(impact),
+            allergens: Vec::new(),
+        }
+    }
+
+    fn result(health_score: i32, breakdown: Vec<ScoreBreakdown>) -> AnalysisResult {
+        AnalysisResult {
+            health_score,
+            summary: String::new(),
+            table: Vec::new(),
+            ingredients: Vec::new(),
+            warnings: Vec::new(),
+            recommendation: String::new(),
+            overall_assessment: None,
+            focus_summary: None,
+            focus_ingredients: None,
+            score_breakdown: Some(breakdown),
+            rule_hits: Vec::new(),
+            confidence: None,
+            ingredient_tree: Vec::new(),
+            rule_set_version: None,
+            rule_conflicts: Vec::new(),
+            diet_verdicts: Vec::new(),
+            score_attribution: None,
+        }
+    }
+
+    fn total(attribution: &ScoreAttribution) -> i32 {
+        attribution.starting_score
+            + attribution
+                .dimensions
+                .iter()
+                .map(|item| item.points)
+                .sum::<i32>()
+            + attribution
+                .ingredients
+                .iter()
+                .map(|item| item.points)
+                .sum::<i32>()
+    }
+
+    #[test]
+    fn points_add_up_to_the_final_score() {
+        // Base 0.5 * 60 + 0.3 * 90 + 0.2 * 75 = 72; a conflict adds 4, position deducts 12.
+        let mut result = result(
+            64,
+            vec![
+                dimension("additives_processing", 60),
+                dimension("sugar_fat", 90),
+                dimension("nutrition_value", 75),
+                dimension(POSITION_WEIGHTING_DIMENSION, -12),
+            ],
+        );
+        result.rule_conflicts = vec![RuleConflict {
+            ingredient: "山梨酸钾".to_string(),
+            rule_id: None,
+            rule_name: "山梨酸钾".to_string(),
+            llm_risk_level: "high".to_string(),
+            rule_risk_level: "medium".to_string(),
+            score_adjustment: 4,
+        }];
+        result.rule_hits = vec![hit("白砂糖", 1, 8), hit("阿斯巴甜", 3, 4)];
+
+        let attribution = attribute(&result, &[(0, 0.5), (1, 0.3), (2, 0.2)]);
+
+        assert_eq!(total(&attribution), 64);
+        let points: Vec<i32> = attribution
+            .dimensions
+            .iter()
+            .map(|item| item.points)
+            .collect();
+        assert_eq!(points, vec![-20, -3, -5]);
+        let ingredients: Vec<(&str, i32)> = attribution
+            .ingredients
+            .iter()
+            .map(|item| (item.ingredient.as_str(), item.points))
+            .collect();
+        assert_eq!(
+            ingredients,
+            vec![("白砂糖", -8), ("阿斯巴甜", -4), ("山梨酸钾", 4)]
+        );
+    }
+
+    #[test]
+    fn capped_deductions_are_split_by_impact() {
+        let mut result = result(60, vec![dimension(POSITION_WEIGHTING_DIMENSION, -20)]);
+        result.rule_hits = vec![hit("白砂糖", 1, 8), hit("糖浆", 2, 8), hit("色素", 3, 8)];
+
+        let attribution = attribute(&result, &[]);
+
+        assert_eq!(attribution.dimensions[0].dimension, OVERALL_DIMENSION);
+        assert_eq!(attribution.dimensions[0].points, -20);
+        let mut points: Vec<i32> = attribution
+            .ingredients
+            .iter()
+            .map(|item| item.points)
+            .collect();
+        points.sort();
+        assert_eq!(points, vec![-7, -7, -6]);
+        assert_eq!(total(&attribution), 60);
+    }
+
+    #[test]
+    fn apportion_keeps_the_total() {
+        assert_eq!(apportion(-10, &[1.0, 1.0, 1.0]).iter().sum::<i32>(), -10);
+        assert_eq!(apportion(4, &[3.0, -1.0]), vec![6, -2]);
+        assert_eq!(apportion(5, &[0.0, 0.0]), vec![0, 0]);
+    }
+}
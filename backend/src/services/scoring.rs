@@ -0,0 +1,184 @@
+//! Deterministic score adjustments applied after the LLM score breakdown
+
+use shared::{AnalysisResult, ScoreBreakdown};
+
+/// Upper bound on points removed by position weighting
+const MAX_POSITION_DEDUCTION: i32 = 20;
+
+/// Breakdown dimension recording the position-weighting deduction
+pub const POSITION_WEIGHTING_DIMENSION: &str = "position_weighting";
+
+/// Deduct points for risky rule hits, weighted by where the ingredient sits on the label.
+///
+/// Labels list ingredients by descending weight, so sugar as the first ingredient costs far
+/// more than sugar as the tenth. A declared percentage, when present, replaces the position
+/// estimate. Each deducting hit records its share in `score_impact`; low-risk hits deduct
+/// nothing. The applied total is appended to `score_breakdown` as a negative
+/// `position_weighting` entry, so the breakdown explains the final score.
+pub fn apply_position_weighting(mut result: AnalysisResult) -> AnalysisResult {
+    let mut total = 0;
+    let mut counted = 0;
+    for hit in &mut result.rule_hits {
+        if risk_level_is_low(&hit.risk_level) {
+            continue;
+        }
+        let weight = position_weight(hit.position, hit.declared_percentage);
+        let impact = (risk_points(&hit.risk_level) * weight).round() as i32;
+        if impact <= 0 {
+            continue;
+        }
+        hit.score_impact = Some(impact);
+        total += impact;
+        counted += 1;
+    }
+
+    let before = result.health_score;
+    result.health_score = (before - total.min(MAX_POSITION_DEDUCTION)).clamp(0, 100);
+    let applied = before - result.health_score;
+    if applied > 0 {
+        let capped = if total > MAX_POSITION_DEDUCTION {
+            format!("（上限 {} 分）", MAX_POSITION_DEDUCTION)
+        } else {
+            String::new()
+        };
+        result
+            .score_breakdown
+            .get_or_insert_with(Vec::new)
+            .push(ScoreBreakdown {
+                dimension: POSITION_WEIGHTING_DIMENSION.to_string(),
+                score: -applied,
+                reason: Some(format!(
+                    "{} 项风险配料按配料表位置加权扣 {} 分{}",
+                    counted, applied, capped
+                )),
+            });
+    }
+    result
+}
+
+fn risk_level_is_low(risk_level: &str) -> bool {
+    risk_level.trim().eq_ignore_ascii_case("low")
+}
+
+pub(crate) fn risk_points(risk_level: &str) -> f32 {
+    match risk_level.trim().to_lowercase().as_str() {
+        "high" => 8.0,
+        "medium" => 4.0,
+        "low" => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Share of the full risk penalty an ingredient carries: 1.0 for the first ingredient,
+/// decaying with position; a declared 10% counts as 1.0
+fn position_weight(position: Option<u32>, percentage: Option<f32>) -> f32 {
+    if let Some(percentage) = percentage {
+        return (percentage / 10.0).clamp(0.1, 2.0);
+    }
+    match position {
+        Some(position) if position > 0 => 1.0 / (1.0 + 0.35 * (position - 1) as f32),
+        _ => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::RuleHit;
+
+    fn hit(risk_level: &str, position: u32, percentage: Option<f32>) -> RuleHit {
+        RuleHit {
+            rule_id: None,
+            name: "白砂糖".to_string(),
+            category: "other".to_string(),
+            risk_level: risk_level.to_string(),
+            description: String::new(),
+            group_tags: Vec::new(),
+            evidence: None,
+            source: None,
+            matched_text: None,
+            match_type: None,
+            match_score: None,
+            position: Some(position),
+            declared_percentage: percentage,
+            score_impact: None,
+            allergens: Vec::new(),
+        }
+    }
+
+    fn result(hits: Vec<RuleHit>) -> AnalysisResult {
+        AnalysisResult {
+            health_score: 80,
+            summary: String::new(),
+            table: Vec::new(),
+            ingredients: Vec::new(),
+            warnings: Vec::new(),
+            recommendation: String::new(),
+            overall_assessment: None,
+            focus_summary: None,
+            focus_ingredients: None,
+            score_breakdown: None,
+            rule_hits: hits,
+            confidence: None,
+            ingredient_tree: Vec::new(),
+            rule_set_version: None,
+            rule_conflicts: Vec::new(),
+            diet_verdicts: Vec::new(),
+            score_attribution: None,
+        }
+    }
+
+    #[test]
+    fn first_ingredient_weighs_more_than_tenth() {
+        let first = apply_position_weighting(result(vec![hit("high", 1, None)]));
+        let tenth = apply_position_weighting(result(vec![hit("high", 10, None)]));
+        assert_eq!(first.rule_hits[0].score_impact, Some(8));
+        assert_eq!(tenth.rule_hits[0].score_impact, Some(2));
+        assert!(first.health_score < tenth.health_score);
+    }
+
+    #[test]
+    fn declared_percentage_overrides_position() {
+        let weighted = apply_position_weighting(result(vec![hit("medium", 8, Some(25.0))]));
+        assert_eq!(weighted.rule_hits[0].score_impact, Some(8));
+        assert_eq!(weighted.health_score, 72);
+    }
+
+    #[test]
+    fn breakdown_records_the_applied_deduction() {
+        let weighted =
+            apply_position_weighting(result(vec![hit("high", 1, None), hit("medium", 2, None)]));
+        let entry = weighted
+            .score_breakdown
+            .as_ref()
+            .and_then(|items| items.last())
+            .unwrap();
+        assert_eq!(entry.dimension, POSITION_WEIGHTING_DIMENSION);
+        assert_eq!(80 + entry.score, weighted.health_score);
+        let impacts: i32 = weighted
+            .rule_hits
+            .iter()
+            .filter_map(|hit| hit.score_impact)
+            .sum();
+        assert_eq!(-entry.score, impacts);
+    }
+
+    #[test]
+    fn low_risk_hits_do_not_deduct() {
+        let weighted = apply_position_weighting(result(vec![hit("low", 1, Some(80.0))]));
+        assert_eq!(weighted.rule_hits[0].score_impact, None);
+        assert_eq!(weighted.health_score, 80);
+        assert!(weighted.score_breakdown.is_none());
+    }
+
+    #[test]
+    fn total_deduction_is_capped() {
+        let hits = (1..=6)
+            .map(|position| hit("high", position, Some(30.0)))
+            .collect();
+        let weighted = apply_position_weighting(result(hits));
+        assert_eq!(weighted.health_score, 80 - MAX_POSITION_DEDUCTION);
+        let entry = weighted.score_breakdown.unwrap().pop().unwrap();
+        assert_eq!(entry.score, -MAX_POSITION_DEDUCTION);
+    }
+}
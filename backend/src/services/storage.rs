@@ -72,10 +72,15 @@ fn generate_filename(original: Option<&str>, extension: &str) -> String {
 }
 
 pub fn resolve_image_path(upload_dir: &str, image_url: &str) -> Result<PathBuf> {
+    let filename =
+        upload_file_name(image_url).ok_or_else(|| anyhow::anyhow!("invalid image url"))?;
+    Ok(Path::new(upload_dir).join(filename))
+}
+
+/// Extract the on-disk file name referenced by a public `/uploads/...` URL
+pub fn upload_file_name(image_url: &str) -> Option<&str> {
     let trimmed = image_url.trim_start_matches('/');
-    let path = Path::new(trimmed);
-    let filename = path
+    Path::new(trimmed)
         .file_name()
-        .ok_or_else(|| anyhow::anyhow!("invalid image url"))?;
-    Ok(Path::new(upload_dir).join(filename))
+        .and_then(|name| name.to_str())
 }
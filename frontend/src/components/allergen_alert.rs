@@ -0,0 +1,27 @@
+//! Blocker banner for detected allergens the user selected
+
+use leptos::prelude::*;
+use shared::Warning;
+
+#[component]
+pub fn AllergenAlertBanner(warnings: Vec<Warning>) -> impl IntoView {
+    let blockers: Vec<Warning> = warnings.into_iter().filter(|w| w.blocker).collect();
+    (!blockers.is_empty()).then(|| {
+        let items = blockers
+            .into_iter()
+            .map(|w| {
+                view! {
+                    <li>{w.message}</li>
+                }
+            })
+            .collect_view();
+        view! {
+            <div class="mb-4 rounded-2xl border-2 border-red-300 bg-red-50 p-4 shadow-lg" role="alert">
+                <h4 class="m-0 mb-2 text-base font-bold text-red-700">"⛔ 过敏原警报"</h4>
+                <ul class="m-0 pl-4 text-sm font-medium text-red-800 space-y-1">
+                    {items}
+                </ul>
+            </div>
+        }
+    })
+}
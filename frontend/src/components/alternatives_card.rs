@@ -0,0 +1,83 @@
+//! Healthier alternatives to a poorly scored product
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use leptos_router::hooks::use_navigate;
+use shared::{Alternative, AlternativeSource};
+
+use crate::services;
+
+/// Scores below this get alternatives suggested
+pub const ALTERNATIVES_BELOW_SCORE: i32 = 70;
+
+/// Fetches alternatives for a completed analysis; renders nothing when there are none
+#[component]
+pub fn AlternativesCard(analysis_id: uuid::Uuid) -> impl IntoView {
+    let items = RwSignal::new(Vec::<Alternative>::new());
+    let category = RwSignal::new(None::<String>);
+    let navigate = use_navigate();
+
+    spawn_local(async move {
+        // Suggestions are optional; a failed lookup just shows nothing.
+        if let Ok(response) = services::fetch_alternatives(analysis_id).await {
+            category.set(response.category);
+            items.set(response.items);
+        }
+    });
+
+    move || {
+        let list = items.get();
+        if list.is_empty() {
+            return None;
+        }
+        let title = match category.get() {
+            Some(label) => format!("🔄 更健康的{}选择", label),
+            None => "🔄 更健康的选择".to_string(),
+        };
+        let rows = list
+            .into_iter()
+            .map(|item| {
+                let navigate = navigate.clone();
+                let post_id = (item.source == AlternativeSource::Community).then_some(item.id);
+                let name = match &item.brand {
+                    Some(brand) => format!("{} {}", brand, item.name),
+                    None => item.name.clone(),
+                };
+                view! {
+                    <li
+                        class="py-2"
+                        class:cursor-pointer=post_id.is_some()
+                        on:click=move |_| {
+                            if let Some(id) = post_id {
+                                navigate(&format!("/community/{}", id), Default::default());
+                            }
+                        }
+                    >
+                        <div class="flex items-center justify-between gap-3">
+                            <span class="min-w-0 text-sm font-medium text-gray-800 truncate">{name}</span>
+                            <span class="shrink-0 text-sm font-semibold text-emerald-600">
+                                {format!("{} 分（+{}）", item.health_score, item.score_gain)}
+                            </span>
+                        </div>
+                        <ul class="mt-1 m-0 pl-4 text-xs text-gray-500">
+                            {item
+                                .reasons
+                                .into_iter()
+                                .skip(1)
+                                .map(|reason| view! { <li>{reason}</li> })
+                                .collect_view()}
+                        </ul>
+                    </li>
+                }
+            })
+            .collect_view();
+        Some(view! {
+            <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+                <h4 class="m-0 text-sm font-semibold text-gray-800">{title}</h4>
+                <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                    {rows}
+                </ul>
+            </div>
+        })
+    }
+}
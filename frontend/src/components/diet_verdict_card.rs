@@ -0,0 +1,58 @@
+//! Per-diet compatibility verdicts
+
+use leptos::prelude::*;
+use shared::{DietStatus, DietVerdict};
+
+fn status_badge(status: DietStatus) -> (&'static str, &'static str) {
+    match status {
+        DietStatus::Compatible => ("✅ 符合", "bg-emerald-50 text-emerald-700"),
+        DietStatus::Incompatible => ("❌ 不符合", "bg-red-50 text-red-700"),
+        DietStatus::Uncertain => ("❔ 待确认", "bg-amber-50 text-amber-700"),
+    }
+}
+
+fn verdict_detail(verdict: &DietVerdict) -> Option<String> {
+    let ingredients = verdict.ingredients.join("、");
+    match verdict.status {
+        DietStatus::Compatible => None,
+        DietStatus::Incompatible => Some(format!("含 {}", ingredients)),
+        DietStatus::Uncertain if ingredients.is_empty() => {
+            Some("规则库暂不可用，请自行核对配料表".to_string())
+        }
+        DietStatus::Uncertain => Some(format!("{} 的来源需确认", ingredients)),
+    }
+}
+
+#[component]
+pub fn DietVerdictCard(verdicts: Vec<DietVerdict>) -> impl IntoView {
+    (!verdicts.is_empty()).then(|| {
+        let items = verdicts
+            .into_iter()
+            .map(|verdict| {
+                let (label, class) = status_badge(verdict.status);
+                let detail = verdict_detail(&verdict);
+                view! {
+                    <li class="flex items-start justify-between gap-3 py-2">
+                        <div class="min-w-0">
+                            <div class="text-sm font-medium text-gray-800">{verdict.diet.label()}</div>
+                            {detail.map(|detail| view! {
+                                <div class="mt-1 text-xs text-gray-500">{detail}</div>
+                            })}
+                        </div>
+                        <span class=format!("shrink-0 px-2 py-0.5 rounded-full text-xs font-semibold {}", class)>
+                            {label}
+                        </span>
+                    </li>
+                }
+            })
+            .collect_view();
+        view! {
+            <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+                <h4 class="m-0 text-sm font-semibold text-gray-800">"🥗 饮食方式"</h4>
+                <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                    {items}
+                </ul>
+            </div>
+        }
+    })
+}
@@ -0,0 +1,193 @@
+//! Family member profiles: list, add and delete, plus the "scored for" note on results
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared::{Allergen, FamilyProfile, FamilyProfileInput};
+
+use crate::components::{get_preference_label, AllergenSelector, PREFERENCE_OPTIONS};
+use crate::services;
+use crate::stores::ToastLevel;
+use crate::utils::emit_toast;
+
+const INPUT_CLASS: &str = "w-full h-10 rounded-xl border border-emerald-100 bg-white-95 px-3 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500";
+
+/// Split a comma/space separated avoid-list typed by the user
+fn parse_avoid_list(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c == '，' || c == '、' || c.is_whitespace())
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[component]
+pub fn FamilyProfileManager() -> impl IntoView {
+    let profiles = RwSignal::new(Vec::<FamilyProfile>::new());
+    let name = RwSignal::new(String::new());
+    let preference = RwSignal::new(String::new());
+    let allergens = RwSignal::new(Vec::<Allergen>::new());
+    let avoid = RwSignal::new(String::new());
+    let saving = RwSignal::new(false);
+
+    spawn_local(async move {
+        match services::fetch_family_profiles().await {
+            Ok(response) => profiles.set(response.items),
+            Err(err) => emit_toast(ToastLevel::Error, "加载失败", &err),
+        }
+    });
+
+    let on_add = move |_| {
+        let member = name.get_untracked().trim().to_string();
+        if member.is_empty() {
+            emit_toast(ToastLevel::Warning, "请输入名称", "成员名称不能为空");
+            return;
+        }
+        if saving.get_untracked() {
+            return;
+        }
+        let input = FamilyProfileInput {
+            name: member,
+            preference: Some(preference.get_untracked()).filter(|value| !value.is_empty()),
+            allergens: allergens
+                .get_untracked()
+                .into_iter()
+                .map(|allergen| allergen.as_key().to_string())
+                .collect(),
+            avoid_ingredients: parse_avoid_list(&avoid.get_untracked()),
+        };
+        saving.set(true);
+        spawn_local(async move {
+            match services::create_family_profile(input).await {
+                Ok(created) => {
+                    profiles.update(|items| items.push(created));
+                    name.set(String::new());
+                    preference.set(String::new());
+                    allergens.set(Vec::new());
+                    avoid.set(String::new());
+                }
+                Err(err) => emit_toast(ToastLevel::Error, "添加失败", &err),
+            }
+            saving.set(false);
+        });
+    };
+
+    let on_delete = Callback::new(move |id: uuid::Uuid| {
+        spawn_local(async move {
+            match services::delete_family_profile(id).await {
+                Ok(()) => profiles.update(|items| items.retain(|item| item.id != id)),
+                Err(err) => emit_toast(ToastLevel::Error, "删除失败", &err),
+            }
+        });
+    });
+
+    view! {
+        <div class="mt-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm p-4">
+            <div class="text-sm font-semibold text-gray-800">"👪 家庭成员"</div>
+            <p class="mt-1 mb-3 text-xs text-gray-500">"为家人分别设置分析偏好、过敏原和规避配料，分析时可选择为谁评分。"</p>
+
+            <div class="space-y-2">
+                <input
+                    class=INPUT_CLASS
+                    type="text"
+                    placeholder="成员名称，如 宝宝、奶奶…"
+                    prop:value=move || name.get()
+                    on:input=move |ev| name.set(event_target_value(&ev))
+                />
+                <select
+                    class=INPUT_CLASS
+                    prop:value=move || preference.get()
+                    on:change=move |ev| preference.set(event_target_value(&ev))
+                >
+                    <option value="" prop:selected=move || preference.get().is_empty()>
+                        "分析偏好：跟随当前设置"
+                    </option>
+                    {PREFERENCE_OPTIONS
+                        .iter()
+                        .map(|option| {
+                            let value = option.value;
+                            view! {
+                                <option value=value prop:selected=move || preference.get() == value>
+                                    {option.label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <AllergenSelector
+                    value=Signal::derive(move || allergens.get())
+                    on_change=Callback::new(move |selected: Vec<Allergen>| allergens.set(selected))
+                />
+                <input
+                    class=INPUT_CLASS
+                    type="text"
+                    placeholder="规避配料，用逗号分隔，如 蜂蜜、咖啡因…"
+                    prop:value=move || avoid.get()
+                    on:input=move |ev| avoid.set(event_target_value(&ev))
+                />
+                <button
+                    class="w-full h-10 rounded-xl border-0 bg-gradient-to-br from-emerald-500 to-teal-500 text-white text-sm font-semibold shadow-md disabled:opacity-50"
+                    disabled=move || saving.get()
+                    on:click=on_add
+                >
+                    "添加成员"
+                </button>
+            </div>
+
+            <ul class="mt-3 m-0 p-0 list-none space-y-2">
+                <For
+                    each=move || profiles.get()
+                    key=|item| item.id
+                    children=move |item| {
+                        let id = item.id;
+                        let FamilyProfileInput { name, preference, allergens, avoid_ingredients } =
+                            item.profile;
+                        let mut details = Vec::new();
+                        if let Some(preference) = preference {
+                            details.push(get_preference_label(&preference).to_string());
+                        }
+                        let allergen_labels: Vec<&str> = allergens
+                            .iter()
+                            .filter_map(|key| Allergen::parse(key))
+                            .map(Allergen::label)
+                            .collect();
+                        if !allergen_labels.is_empty() {
+                            details.push(format!("过敏：{}", allergen_labels.join("、")));
+                        }
+                        if !avoid_ingredients.is_empty() {
+                            details.push(format!("规避：{}", avoid_ingredients.join("、")));
+                        }
+                        view! {
+                            <li class="flex items-center justify-between gap-2 rounded-xl bg-emerald-50 px-3 py-2">
+                                <div class="min-w-0">
+                                    <div class="text-sm font-medium text-gray-800 truncate">{name}</div>
+                                    {(!details.is_empty()).then(|| view! {
+                                        <div class="mt-1 text-xs text-gray-500 truncate">{details.join(" · ")}</div>
+                                    })}
+                                </div>
+                                <button
+                                    class="shrink-0 h-8 px-3 rounded-lg border border-red-100 bg-white text-xs text-red-600"
+                                    on:click=move |_| on_delete.run(id)
+                                >
+                                    "删除"
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
+
+/// Which family member an analysis was scored for; renders nothing for the account itself
+#[component]
+pub fn ScoredForNote(#[prop(into)] profile_name: Option<String>) -> impl IntoView {
+    profile_name.map(|name| {
+        view! {
+            <div class="mb-4 rounded-2xl border border-amber-100 bg-amber-50 px-4 py-2 text-sm text-amber-800">
+                {format!("👪 本次结果按「{}」的偏好、过敏原和规避配料评分", name)}
+            </div>
+        }
+    })
+}
@@ -0,0 +1,104 @@
+//! Favorite toggle, tags and note on a history record, with an inline editor
+
+use leptos::prelude::*;
+use shared::HistoryAnnotations;
+
+const INPUT_CLASS: &str = "w-full h-9 rounded-xl border border-emerald-100 bg-white-95 px-3 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500";
+
+/// Split a comma/space separated tag list typed by the user
+fn parse_tags(value: &str) -> Vec<String> {
+    value
+        .split(|c: char| c == ',' || c == '，' || c == '、' || c.is_whitespace())
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `on_favorite` receives the new flag; `on_save` the edited annotations, flag unchanged
+#[component]
+pub fn HistoryAnnotationsPanel(
+    annotations: HistoryAnnotations,
+    on_favorite: Callback<bool>,
+    on_save: Callback<HistoryAnnotations>,
+) -> impl IntoView {
+    let is_favorite = annotations.is_favorite;
+    let editing = RwSignal::new(false);
+    let tags_input = RwSignal::new(annotations.tags.join(", "));
+    let note_input = RwSignal::new(annotations.note.clone().unwrap_or_default());
+    let tags = annotations.tags.clone();
+    let note = annotations.note.clone();
+
+    let on_submit = move |_| {
+        on_save.run(HistoryAnnotations {
+            is_favorite,
+            tags: parse_tags(&tags_input.get_untracked()),
+            note: Some(note_input.get_untracked()),
+        });
+        editing.set(false);
+    };
+
+    view! {
+        <div class="mb-3">
+            <div class="flex flex-wrap items-center gap-2">
+                <button
+                    class="h-7 px-2 rounded-lg border-0 bg-transparent text-sm cursor-pointer hover:bg-amber-50"
+                    aria-label=if is_favorite { "取消收藏" } else { "收藏" }
+                    on:click=move |_| on_favorite.run(!is_favorite)
+                >
+                    {if is_favorite { "★" } else { "☆" }}
+                </button>
+                {tags
+                    .into_iter()
+                    .map(|tag| {
+                        view! {
+                            <span class="bg-emerald-50 text-emerald-700 border border-emerald-100 text-xs px-2 py-0.5 rounded-full">
+                                {format!("#{}", tag)}
+                            </span>
+                        }
+                    })
+                    .collect_view()}
+                <button
+                    class="h-7 px-2 rounded-lg border-0 bg-transparent text-xs text-gray-500 cursor-pointer hover:bg-gray-100"
+                    on:click=move |_| editing.update(|editing| *editing = !*editing)
+                >
+                    "标签/备注"
+                </button>
+            </div>
+            {note.map(|note| view! {
+                <p class="mt-1 mb-0 text-xs text-gray-600 whitespace-pre-wrap">{note}</p>
+            })}
+            <Show when=move || editing.get()>
+                <div class="mt-2 space-y-2">
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="标签，用逗号分隔，如：早餐, 孩子"
+                        prop:value=move || tags_input.get()
+                        on:input=move |ev| tags_input.set(event_target_value(&ev))
+                    />
+                    <textarea
+                        class="w-full min-h-16 rounded-xl border border-emerald-100 bg-white-95 px-3 py-2 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500"
+                        placeholder="备注"
+                        prop:value=move || note_input.get()
+                        on:input=move |ev| note_input.set(event_target_value(&ev))
+                    ></textarea>
+                    <div class="flex justify-end gap-2">
+                        <button
+                            class="h-8 px-3 rounded-lg border-0 bg-transparent text-sm text-gray-600 cursor-pointer"
+                            on:click=move |_| editing.set(false)
+                        >
+                            "取消"
+                        </button>
+                        <button
+                            class="h-8 px-3 rounded-lg border-0 bg-emerald-600 text-sm text-white cursor-pointer"
+                            on:click=on_submit
+                        >
+                            "保存"
+                        </button>
+                    </div>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -0,0 +1,188 @@
+//! Search bar and filters for the history page
+
+use leptos::prelude::*;
+
+use crate::components::PREFERENCE_OPTIONS;
+use crate::utils::history_search::HistorySearch;
+
+const INPUT_CLASS: &str = "w-full h-10 rounded-xl border border-emerald-100 bg-white-95 px-3 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500";
+
+const WARNING_TYPES: &[&str] = &["过敏原警报", "规则库提示", "添加剂"];
+
+fn parse_score(value: &str) -> Option<i32> {
+    value
+        .trim()
+        .parse::<i32>()
+        .ok()
+        .map(|score| score.clamp(0, 100))
+}
+
+/// `cloud` also offers the date and preference filters, which only the server can apply
+#[component]
+pub fn HistorySearchBar(
+    #[prop(into)] cloud: Signal<bool>,
+    on_search: Callback<HistorySearch>,
+) -> impl IntoView {
+    let draft = RwSignal::new(HistorySearch::default());
+    let show_filters = RwSignal::new(false);
+
+    let apply = move || on_search.run(draft.get_untracked());
+    let reset = move |_| {
+        draft.set(HistorySearch::default());
+        on_search.run(HistorySearch::default());
+    };
+
+    view! {
+        <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+            <form
+                class="flex items-center gap-2"
+                on:submit=move |ev| {
+                    ev.prevent_default();
+                    apply();
+                }
+            >
+                <input
+                    type="search"
+                    class=INPUT_CLASS
+                    placeholder="搜索配料、摘要或识别文字"
+                    prop:value=move || draft.get().query
+                    on:input=move |ev| draft.update(|search| search.query = event_target_value(&ev))
+                />
+                <button
+                    type="button"
+                    class="h-10 shrink-0 px-3 rounded-xl border border-emerald-100 bg-transparent text-sm text-emerald-700 cursor-pointer"
+                    on:click=move |_| show_filters.update(|show| *show = !*show)
+                >
+                    "筛选"
+                </button>
+                <button
+                    type="submit"
+                    class="h-10 shrink-0 px-3 rounded-xl border-0 bg-emerald-600 text-sm text-white cursor-pointer"
+                >
+                    "搜索"
+                </button>
+            </form>
+
+            <Show when=move || show_filters.get()>
+                <div class="mt-3 grid grid-cols-2 gap-2">
+                    <input
+                        type="number"
+                        min="0"
+                        max="100"
+                        class=INPUT_CLASS
+                        placeholder="最低分"
+                        prop:value=move || draft.get().min_score.map(|score| score.to_string()).unwrap_or_default()
+                        on:input=move |ev| draft.update(|search| search.min_score = parse_score(&event_target_value(&ev)))
+                    />
+                    <input
+                        type="number"
+                        min="0"
+                        max="100"
+                        class=INPUT_CLASS
+                        placeholder="最高分"
+                        prop:value=move || draft.get().max_score.map(|score| score.to_string()).unwrap_or_default()
+                        on:input=move |ev| draft.update(|search| search.max_score = parse_score(&event_target_value(&ev)))
+                    />
+                    <Show when=move || cloud.get()>
+                        <input
+                            type="date"
+                            class=INPUT_CLASS
+                            aria-label="开始日期"
+                            prop:value=move || draft.get().from
+                            on:change=move |ev| draft.update(|search| search.from = event_target_value(&ev))
+                        />
+                        <input
+                            type="date"
+                            class=INPUT_CLASS
+                            aria-label="结束日期"
+                            prop:value=move || draft.get().to
+                            on:change=move |ev| draft.update(|search| search.to = event_target_value(&ev))
+                        />
+                    </Show>
+                    <select
+                        class=INPUT_CLASS
+                        prop:value=move || draft.get().warning_type
+                        on:change=move |ev| draft.update(|search| search.warning_type = event_target_value(&ev))
+                    >
+                        <option value="" prop:selected=move || draft.get().warning_type.is_empty()>
+                            "警告类型：全部"
+                        </option>
+                        {WARNING_TYPES
+                            .iter()
+                            .map(|&warning_type| {
+                                view! {
+                                    <option
+                                        value=warning_type
+                                        prop:selected=move || draft.get().warning_type == warning_type
+                                    >
+                                        {warning_type}
+                                    </option>
+                                }
+                            })
+                            .collect_view()}
+                    </select>
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="包含配料"
+                        prop:value=move || draft.get().ingredient
+                        on:input=move |ev| draft.update(|search| search.ingredient = event_target_value(&ev))
+                    />
+                    <input
+                        type="text"
+                        class=INPUT_CLASS
+                        placeholder="标签"
+                        prop:value=move || draft.get().tag
+                        on:input=move |ev| draft.update(|search| search.tag = event_target_value(&ev))
+                    />
+                    <label class="flex items-center gap-2 text-sm text-gray-700">
+                        <input
+                            type="checkbox"
+                            prop:checked=move || draft.get().favorite
+                            on:change=move |ev| draft.update(|search| search.favorite = event_target_checked(&ev))
+                        />
+                        "只看收藏"
+                    </label>
+                    <Show when=move || cloud.get()>
+                        <select
+                            class=INPUT_CLASS
+                            prop:value=move || draft.get().preference
+                            on:change=move |ev| draft.update(|search| search.preference = event_target_value(&ev))
+                        >
+                            <option value="" prop:selected=move || draft.get().preference.is_empty()>
+                                "分析偏好：全部"
+                            </option>
+                            {PREFERENCE_OPTIONS
+                                .iter()
+                                .map(|option| {
+                                    let value = option.value;
+                                    view! {
+                                        <option value=value prop:selected=move || draft.get().preference == value>
+                                            {option.label}
+                                        </option>
+                                    }
+                                })
+                                .collect_view()}
+                        </select>
+                    </Show>
+                </div>
+                <div class="mt-3 flex justify-end gap-2">
+                    <button
+                        type="button"
+                        class="h-8 px-3 rounded-lg border-0 bg-transparent text-sm text-gray-600 cursor-pointer"
+                        on:click=reset
+                    >
+                        "清除"
+                    </button>
+                    <button
+                        type="button"
+                        class="h-8 px-3 rounded-lg border-0 bg-emerald-600 text-sm text-white cursor-pointer"
+                        on:click=move |_| apply()
+                    >
+                        "应用筛选"
+                    </button>
+                </div>
+            </Show>
+        </div>
+    }
+}
@@ -1,11 +1,17 @@
 //! Reusable components
 
+mod allergen_alert;
+mod alternatives_card;
 mod community_share_button;
 mod confirm_modal;
+mod diet_verdict_card;
 mod error_display;
 mod example_images;
 mod export_preview_modal;
+mod family_profile_manager;
 mod health_score_card;
+mod history_annotations;
+mod history_search_bar;
 mod icons;
 mod image_preview;
 mod ingredient_card;
@@ -14,17 +20,24 @@ mod ingredient_table;
 mod loading_spinner;
 mod preference_selector;
 mod risk_badge;
+mod rule_overlay_manager;
 mod share_button;
 mod summary_card;
 mod tab_icons;
 mod toast;
 mod usage_tips;
 
+pub use allergen_alert::AllergenAlertBanner;
+pub use alternatives_card::{AlternativesCard, ALTERNATIVES_BELOW_SCORE};
 pub use community_share_button::CommunityShareButton;
 pub use confirm_modal::ConfirmModal;
+pub use diet_verdict_card::DietVerdictCard;
 pub use error_display::ErrorDisplay;
 pub use export_preview_modal::ExportPreviewModal;
+pub use family_profile_manager::{FamilyProfileManager, ScoredForNote};
 pub use health_score_card::HealthScoreCard;
+pub use history_annotations::HistoryAnnotationsPanel;
+pub use history_search_bar::HistorySearchBar;
 pub use icons::{
     IconArrowLeft, IconCamera, IconCheckBadge, IconFileText, IconSparkles, IconUpload,
 };
@@ -33,10 +46,11 @@ pub use ingredient_card::IngredientCard;
 pub use ingredient_card_list::IngredientCardList;
 pub use ingredient_table::{IngredientRow, IngredientTable};
 pub use preference_selector::{
-    get_preference_description, get_preference_icon, get_preference_label, PreferenceCard,
-    PreferenceSelector, PREFERENCE_OPTIONS,
+    get_preference_description, get_preference_icon, get_preference_label, AllergenSelector,
+    DietSelector, FocusSelector, PreferenceCard, PreferenceSelector, PREFERENCE_OPTIONS,
 };
 pub use risk_badge::RiskBadge;
+pub use rule_overlay_manager::RuleOverlayManager;
 pub use share_button::{ShareButton, ShareExportProps};
 pub use summary_card::SummaryCard;
 pub use tab_icons::IconCommunity;
@@ -1,4 +1,5 @@
 use leptos::prelude::*;
+use shared::{Allergen, DietRegime};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct PreferenceOption {
@@ -180,3 +181,127 @@ pub fn PreferenceCard(
         </div>
     }
 }
+
+#[component]
+pub fn AllergenSelector(
+    #[prop(into)] value: Signal<Vec<Allergen>>,
+    #[prop(into)] on_change: Callback<Vec<Allergen>>,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-wrap gap-2">
+            {Allergen::ALL
+                .iter()
+                .map(|allergen| {
+                    let allergen = *allergen;
+                    let is_selected = move || value.get().contains(&allergen);
+                    view! {
+                        <button
+                            class=move || {
+                                if is_selected() {
+                                    "px-3 py-1.5 rounded-full border border-red-300 bg-red-50 text-xs font-semibold text-red-700 transition-all"
+                                } else {
+                                    "px-3 py-1.5 rounded-full border border-emerald-100 bg-white-95 text-xs text-gray-700 transition-all hover:border-emerald-200"
+                                }
+                            }
+                            on:click=move |_| {
+                                let mut selected = value.get();
+                                if let Some(index) = selected.iter().position(|item| *item == allergen) {
+                                    selected.remove(index);
+                                } else {
+                                    selected.push(allergen);
+                                }
+                                on_change.run(selected);
+                            }
+                        >
+                            {allergen.label()}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+#[component]
+pub fn DietSelector(
+    #[prop(into)] value: Signal<Vec<DietRegime>>,
+    #[prop(into)] on_change: Callback<Vec<DietRegime>>,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-wrap gap-2">
+            {DietRegime::ALL
+                .iter()
+                .map(|diet| {
+                    let diet = *diet;
+                    let is_selected = move || value.get().contains(&diet);
+                    view! {
+                        <button
+                            class=move || {
+                                if is_selected() {
+                                    "px-3 py-1.5 rounded-full border border-emerald-300 bg-emerald-50 text-xs font-semibold text-emerald-700 transition-all"
+                                } else {
+                                    "px-3 py-1.5 rounded-full border border-emerald-100 bg-white-95 text-xs text-gray-700 transition-all hover:border-emerald-200"
+                                }
+                            }
+                            on:click=move |_| {
+                                let mut selected = value.get();
+                                if let Some(index) = selected.iter().position(|item| *item == diet) {
+                                    selected.remove(index);
+                                } else {
+                                    selected.push(diet);
+                                }
+                                on_change.run(selected);
+                            }
+                        >
+                            {diet.label()}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
+
+/// Extra focuses blended with the main preference; the main one and `normal` are not offered
+#[component]
+pub fn FocusSelector(
+    #[prop(into)] value: Signal<Vec<String>>,
+    #[prop(into)] primary: Signal<String>,
+    #[prop(into)] on_change: Callback<Vec<String>>,
+) -> impl IntoView {
+    view! {
+        <div class="flex flex-wrap gap-2">
+            {PREFERENCE_OPTIONS
+                .iter()
+                .filter(|opt| opt.value != "normal")
+                .map(|opt| {
+                    let opt_value = opt.value;
+                    let is_selected = move || value.get().iter().any(|item| item == opt_value);
+                    view! {
+                        <button
+                            class=move || {
+                                if is_selected() {
+                                    "px-3 py-1.5 rounded-full border border-emerald-300 bg-emerald-50 text-xs font-semibold text-emerald-700 transition-all"
+                                } else {
+                                    "px-3 py-1.5 rounded-full border border-emerald-100 bg-white-95 text-xs text-gray-700 transition-all hover:border-emerald-200"
+                                }
+                            }
+                            style:display=move || if primary.get() == opt_value { "none" } else { "" }
+                            on:click=move |_| {
+                                let mut selected = value.get();
+                                if let Some(index) = selected.iter().position(|item| item == opt_value) {
+                                    selected.remove(index);
+                                } else {
+                                    selected.push(opt_value.to_string());
+                                }
+                                on_change.run(selected);
+                            }
+                        >
+                            {opt.icon} " " {opt.label}
+                        </button>
+                    }
+                })
+                .collect_view()}
+        </div>
+    }
+}
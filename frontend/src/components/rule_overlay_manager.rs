@@ -0,0 +1,163 @@
+//! Personal rule overlay list with add and delete
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared::{RuleOverlay, RuleOverlayInput};
+
+use crate::components::RiskBadge;
+use crate::services;
+use crate::stores::ToastLevel;
+use crate::utils::emit_toast;
+
+/// Select values: a risk level, or `trust` to ignore the ingredient's rule hits
+const OVERLAY_ACTIONS: [(&str, &str); 4] = [
+    ("high", "规避（高风险）"),
+    ("medium", "关注（中风险）"),
+    ("low", "放心（低风险）"),
+    ("trust", "信任（忽略规则提示）"),
+];
+
+const INPUT_CLASS: &str = "w-full h-10 rounded-xl border border-emerald-100 bg-white-95 px-3 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500";
+
+#[component]
+pub fn RuleOverlayManager() -> impl IntoView {
+    let overlays = RwSignal::new(Vec::<RuleOverlay>::new());
+    let ingredient = RwSignal::new(String::new());
+    let action = RwSignal::new("high".to_string());
+    let note = RwSignal::new(String::new());
+    let saving = RwSignal::new(false);
+
+    spawn_local(async move {
+        match services::fetch_rule_overlays().await {
+            Ok(response) => overlays.set(response.items),
+            Err(err) => emit_toast(ToastLevel::Error, "加载失败", &err),
+        }
+    });
+
+    let on_add = move |_| {
+        let name = ingredient.get_untracked().trim().to_string();
+        if name.is_empty() {
+            emit_toast(ToastLevel::Warning, "请输入配料", "配料名称不能为空");
+            return;
+        }
+        if saving.get_untracked() {
+            return;
+        }
+        let trusted = action.get_untracked() == "trust";
+        let input = RuleOverlayInput {
+            ingredient: name,
+            rule_id: None,
+            risk_level: if trusted {
+                String::new()
+            } else {
+                action.get_untracked()
+            },
+            note: Some(note.get_untracked().trim().to_string()).filter(|n| !n.is_empty()),
+            trusted,
+        };
+        saving.set(true);
+        spawn_local(async move {
+            match services::create_rule_overlay(input).await {
+                Ok(created) => {
+                    overlays.update(|items| items.push(created));
+                    ingredient.set(String::new());
+                    note.set(String::new());
+                }
+                Err(err) => emit_toast(ToastLevel::Error, "添加失败", &err),
+            }
+            saving.set(false);
+        });
+    };
+
+    let on_delete = Callback::new(move |id: uuid::Uuid| {
+        spawn_local(async move {
+            match services::delete_rule_overlay(id).await {
+                Ok(()) => overlays.update(|items| items.retain(|item| item.id != id)),
+                Err(err) => emit_toast(ToastLevel::Error, "删除失败", &err),
+            }
+        });
+    });
+
+    view! {
+        <div class="mt-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm p-4">
+            <div class="text-sm font-semibold text-gray-800">"📝 我的配料规则"</div>
+            <p class="mt-1 mb-3 text-xs text-gray-500">"为配料设置个人风险等级，或信任某个配料以忽略规则提示。"</p>
+
+            <div class="space-y-2">
+                <input
+                    class=INPUT_CLASS
+                    type="text"
+                    placeholder="配料名称，如 阿斯巴甜…"
+                    prop:value=move || ingredient.get()
+                    on:input=move |ev| ingredient.set(event_target_value(&ev))
+                />
+                <select
+                    class=INPUT_CLASS
+                    prop:value=move || action.get()
+                    on:change=move |ev| action.set(event_target_value(&ev))
+                >
+                    {OVERLAY_ACTIONS
+                        .iter()
+                        .map(|(value, label)| {
+                            let value = *value;
+                            view! {
+                                <option value=value prop:selected=move || action.get() == value>
+                                    {*label}
+                                </option>
+                            }
+                        })
+                        .collect_view()}
+                </select>
+                <input
+                    class=INPUT_CLASS
+                    type="text"
+                    placeholder="备注（可选）…"
+                    prop:value=move || note.get()
+                    on:input=move |ev| note.set(event_target_value(&ev))
+                />
+                <button
+                    class="w-full h-10 rounded-xl border-0 bg-gradient-to-br from-emerald-500 to-teal-500 text-white text-sm font-semibold shadow-md disabled:opacity-50"
+                    disabled=move || saving.get()
+                    on:click=on_add
+                >
+                    "添加规则"
+                </button>
+            </div>
+
+            <ul class="mt-3 m-0 p-0 list-none space-y-2">
+                <For
+                    each=move || overlays.get()
+                    key=|item| item.id
+                    children=move |item| {
+                        let id = item.id;
+                        let RuleOverlayInput { ingredient, risk_level, note, trusted, .. } = item.overlay;
+                        view! {
+                            <li class="flex items-center justify-between gap-2 rounded-xl bg-emerald-50 px-3 py-2">
+                                <div class="min-w-0">
+                                    <div class="flex items-center gap-2 text-sm font-medium text-gray-800">
+                                        <span class="truncate">{ingredient}</span>
+                                        {if trusted {
+                                            view! {
+                                                <span class="inline-flex items-center px-2 py-1 rounded-full text-xs font-semibold bg-sky-50 text-sky-700 border border-sky-100">"信任"</span>
+                                            }
+                                            .into_any()
+                                        } else {
+                                            view! { <RiskBadge level=risk_level /> }.into_any()
+                                        }}
+                                    </div>
+                                    {note.map(|note| view! { <div class="mt-1 text-xs text-gray-500 truncate">{note}</div> })}
+                                </div>
+                                <button
+                                    class="shrink-0 h-8 px-3 rounded-lg border border-red-100 bg-white text-xs text-red-600"
+                                    on:click=move |_| on_delete.run(id)
+                                >
+                                    "删除"
+                                </button>
+                            </li>
+                        }
+                    }
+                />
+            </ul>
+        </div>
+    }
+}
@@ -17,7 +17,8 @@ use leptos_router::path;
 use crate::components::{MainLayout, ToastHost};
 use crate::pages::{
     AnalyzingPage, CapturePage, CommunityDetailPage, CommunityPage, ConfirmPage, DetailPage,
-    HistoryPage, LoginPage, OcrPage, OnboardingPage, ProfilePage, RegisterPage, SummaryPage,
+    HistoryPage, InsightsPage, LoginPage, OcrPage, OnboardingPage, ProfilePage, RegisterPage,
+    ScoreExplanationPage, SummaryPage,
 };
 use crate::stores::{
     AnalysisSource, AppState, LoadingState, ResultPageState, TabRoute, ToastLevel,
@@ -198,6 +199,16 @@ pub fn App() -> impl IntoView {
                             <DetailPage />
                         </MainLayout>
                     } />
+                    <Route path=path!("/score") view=move || view! {
+                        <MainLayout>
+                            <ScoreExplanationPage />
+                        </MainLayout>
+                    } />
+                    <Route path=path!("/history/insights") view=move || view! {
+                        <MainLayout>
+                            <InsightsPage />
+                        </MainLayout>
+                    } />
                 </Routes>
             </main>
         </Router>
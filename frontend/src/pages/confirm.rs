@@ -3,11 +3,13 @@ use leptos::task::spawn_local;
 use leptos_router::hooks::use_navigate;
 use wasm_bindgen::JsCast;
 
-use crate::components::{get_preference_description, IconArrowLeft};
+use crate::components::{get_preference_description, get_preference_label, IconArrowLeft};
 use crate::services;
 use crate::stores::{AppState, LoadingState, ToastLevel};
 use crate::utils::emit_toast;
-use crate::utils::preference::{load_preference, save_preference};
+use crate::utils::preference::{
+    blend_focuses, load_allergens, load_diets, load_extra_focuses, load_preference, save_preference,
+};
 
 #[component]
 pub fn ConfirmPage() -> impl IntoView {
@@ -31,10 +33,22 @@ pub fn ConfirmPage() -> impl IntoView {
         .unwrap_or_else(|| "normal".to_string());
     let preference = create_signal(initial_preference).0;
 
+    // Family member to score for; `None` scores for the account itself
+    let profiles = RwSignal::new(Vec::<shared::FamilyProfile>::new());
+    let profile_id = RwSignal::new(None::<uuid::Uuid>);
+    if state.auth_user.get_untracked().is_some() {
+        spawn_local(async move {
+            if let Ok(response) = services::fetch_family_profiles().await {
+                profiles.set(response.items);
+            }
+        });
+    }
+
     let on_confirm = move |_| {
         let text = edited_text.get();
         let analysis_id = state_for_confirm.analysis_id.get();
         let current_preference = preference.get();
+        let current_profile = profile_id.get();
 
         if let Some(id) = analysis_id {
             let state = state_for_confirm.clone();
@@ -49,9 +63,34 @@ pub fn ConfirmPage() -> impl IntoView {
                 save_preference(&current_preference);
             }
 
+            // A family profile brings its own allergens; the locally saved ones are the user's
+            let allergens = if current_profile.is_some() {
+                Vec::new()
+            } else {
+                load_allergens()
+                    .into_iter()
+                    .map(|allergen| allergen.as_key().to_string())
+                    .collect()
+            };
+            let focuses = blend_focuses(&current_preference, &load_extra_focuses());
+            let diets = load_diets()
+                .into_iter()
+                .map(|diet| diet.as_key().to_string())
+                .collect();
+
             spawn_local(async move {
                 state.error_message.set(None);
-                match services::confirm_and_analyze(id, text, Some(current_preference)).await {
+                match services::confirm_and_analyze(
+                    id,
+                    text,
+                    Some(current_preference),
+                    focuses,
+                    allergens,
+                    diets,
+                    current_profile,
+                )
+                .await
+                {
                     Ok(response) => {
                         state.analysis_result.set(Some(response));
                         state.confirmed_text.set(Some(edited_text.get()));
@@ -116,12 +155,70 @@ pub fn ConfirmPage() -> impl IntoView {
                     </p>
                 </div>
 
+                <Show when=move || !profiles.get().is_empty()>
+                    <div class="mt-3 px-1">
+                        <label class="block mb-1 text-xs font-medium text-gray-700">"为谁分析"</label>
+                        <select
+                            class="w-full h-10 rounded-xl border border-emerald-100 bg-white-95 px-3 text-sm text-gray-800 shadow-sm focus:outline-none focus:border-emerald-500"
+                            on:change=move |ev| {
+                                profile_id.set(uuid::Uuid::parse_str(&event_target_value(&ev)).ok());
+                            }
+                        >
+                            <option value="" prop:selected=move || profile_id.get().is_none()>"我自己"</option>
+                            {move || {
+                                profiles
+                                    .get()
+                                    .into_iter()
+                                    .map(|profile| {
+                                        let id = profile.id;
+                                        view! {
+                                            <option
+                                                value=id.to_string()
+                                                prop:selected=move || profile_id.get() == Some(id)
+                                            >
+                                                {profile.profile.name}
+                                            </option>
+                                        }
+                                    })
+                                    .collect_view()
+                            }}
+                        </select>
+                    </div>
+                </Show>
+
                 <div class="mt-3 px-1">
                     <p class="m-0 text-xs text-gray-600">
-                        {move || format!(
-                            "💡 当前分析更注重：{}。如需修改请前往「我的」页面",
-                            get_preference_description(&preference.get()),
-                        )}
+                        {move || {
+                            let profile_preference = profile_id.get().and_then(|id| {
+                                profiles
+                                    .get()
+                                    .into_iter()
+                                    .find(|profile| profile.id == id)
+                                    .and_then(|profile| profile.profile.preference)
+                            });
+                            // A profile's own preference replaces the blended focuses
+                            let extras = if profile_preference.is_some() {
+                                String::new()
+                            } else {
+                                let labels: Vec<&str> = load_extra_focuses()
+                                    .iter()
+                                    .filter(|focus| **focus != preference.get())
+                                    .map(|focus| get_preference_label(focus))
+                                    .collect();
+                                if labels.is_empty() {
+                                    String::new()
+                                } else {
+                                    format!("，同时关注{}", labels.join("、"))
+                                }
+                            };
+                            format!(
+                                "💡 当前分析更注重：{}{}。如需修改请前往「我的」页面",
+                                get_preference_description(
+                                    &profile_preference.unwrap_or_else(|| preference.get()),
+                                ),
+                                extras,
+                            )
+                        }}
                     </p>
                 </div>
 
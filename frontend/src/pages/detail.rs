@@ -3,7 +3,7 @@
 use leptos::prelude::*;
 use wasm_bindgen::JsCast;
 
-use crate::components::{IconArrowLeft, IngredientCardList, IngredientRow};
+use crate::components::{IconArrowLeft, IngredientCardList, IngredientRow, ScoredForNote};
 use crate::stores::AppState;
 
 fn risk_label(level: &str) -> String {
@@ -102,6 +102,13 @@ pub fn DetailPage() -> impl IntoView {
             </div>
 
             <div class="page-scrollable-content px-5 py-5">
+                {move || {
+                    let profile_name = state
+                        .analysis_result
+                        .get()
+                        .and_then(|response| response.profile_name);
+                    view! { <ScoredForNote profile_name=profile_name /> }
+                }}
                 <Show
                     when=move || !table_rows().is_empty()
                     fallback=move || view! { <p class="text-sm text-gray-600 text-center m-0 py-6">"暂无配料数据"</p> }
@@ -4,12 +4,13 @@ use leptos::task::spawn_local;
 use leptos_router::hooks::{use_location, use_navigate};
 use wasm_bindgen::{JsCast, JsValue};
 
-use crate::components::ConfirmModal;
+use crate::components::{ConfirmModal, HistoryAnnotationsPanel, HistorySearchBar};
 use crate::services;
 use crate::stores::{AnalysisSource, AppState, ToastLevel};
+use crate::utils::history_search::HistorySearch;
 use crate::utils::navigation::build_full_path;
 use crate::utils::{emit_toast, local_history};
-use shared::{AnalysisResponse, AnalysisStatus, LlmStatus, OcrStatus};
+use shared::{AnalysisResponse, AnalysisStatus, HistoryAnnotations, LlmStatus, OcrStatus};
 
 fn format_datetime(date: &Date) -> String {
     let options = Object::new();
@@ -110,6 +111,11 @@ fn local_to_response(item: &local_history::LocalHistoryItem) -> Option<AnalysisR
         ocr_completed_at: None,
         result: Some(item.result.clone()),
         error_message: None,
+        gtin: None,
+        product_id: None,
+        canonical_result: None,
+        profile_id: None,
+        profile_name: None,
         created_at: created_at.clone(),
         updated_at: created_at,
     })
@@ -125,7 +131,8 @@ pub fn HistoryPage() -> impl IntoView {
     let total = RwSignal::new(0_i64);
     let items = RwSignal::new(Vec::<shared::HistoryItem>::new());
     let local_items = RwSignal::new(Vec::<local_history::LocalHistoryItem>::new());
-    let last_load_key = RwSignal::new(None::<(uuid::Uuid, i64)>);
+    let search = RwSignal::new(HistorySearch::default());
+    let last_load_key = RwSignal::new(None::<(uuid::Uuid, i64, HistorySearch)>);
     let viewing_id = RwSignal::new(None::<uuid::Uuid>);
     let deleting_id = RwSignal::new(None::<uuid::Uuid>);
     let deleting_local_id = RwSignal::new(None::<String>);
@@ -156,7 +163,7 @@ pub fn HistoryPage() -> impl IntoView {
         }
     });
 
-    let load_page = Callback::new(move |page_number: i64| {
+    let load_page = Callback::new(move |(page_number, filter): (i64, HistorySearch)| {
         if loading.get_untracked() {
             return;
         }
@@ -164,7 +171,7 @@ pub fn HistoryPage() -> impl IntoView {
         let items = items.clone();
         let total = total.clone();
         spawn_local(async move {
-            match services::fetch_user_history(page_number, 20).await {
+            match services::fetch_user_history(page_number, 20, &filter).await {
                 Ok(response) => {
                     items.set(response.items);
                     total.set(response.total);
@@ -181,12 +188,13 @@ pub fn HistoryPage() -> impl IntoView {
     create_effect(move |_| {
         if let Some(user) = state.auth_user.get() {
             let current_page = page.get();
-            let key = (user.id, current_page);
+            let filter = search.get();
+            let key = (user.id, current_page, filter.clone());
             if last_load_key.get_untracked().as_ref() == Some(&key) {
                 return;
             }
             last_load_key.set(Some(key));
-            load_page.run(current_page);
+            load_page.run((current_page, filter));
         }
     });
 
@@ -197,6 +205,37 @@ pub fn HistoryPage() -> impl IntoView {
         }
     });
 
+    let filtered_local = Memo::new(move |_| {
+        let filter = search.get();
+        local_items
+            .get()
+            .into_iter()
+            .filter(|item| filter.matches_local(item))
+            .collect::<Vec<_>>()
+    });
+
+    let on_search = Callback::new(move |filter: HistorySearch| {
+        search.set(filter);
+        page.set(1);
+    });
+
+    let save_local_annotations = move |id: String, annotations: HistoryAnnotations| {
+        match local_history::update_local_annotations(&id, annotations) {
+            Ok(()) => local_items.set(local_history::load_local_history()),
+            Err(err) => emit_toast(ToastLevel::Error, "保存失败", &err),
+        }
+    };
+
+    let apply_cloud_annotations = move |response: shared::HistoryAnnotationsResponse| {
+        items.update(|items| {
+            if let Some(item) = items.iter_mut().find(|item| item.id == response.id) {
+                item.is_favorite = response.annotations.is_favorite;
+                item.tags = response.annotations.tags;
+                item.note = response.annotations.note;
+            }
+        });
+    };
+
     let on_delete = move |id: uuid::Uuid| {
         pending_delete_id.set(Some(id));
         pending_delete_local_id.set(None);
@@ -314,6 +353,12 @@ pub fn HistoryPage() -> impl IntoView {
             />
 
             <div class="page-scrollable-content pb-20">
+                <div class="px-5 pt-4">
+                    <HistorySearchBar
+                        cloud=Signal::derive(move || state.auth_user.get().is_some())
+                        on_search=on_search
+                    />
+                </div>
 
                 <Show when=move || state.auth_user.get().is_some() fallback=move || {
                 view! {
@@ -343,13 +388,29 @@ pub fn HistoryPage() -> impl IntoView {
                                 </a>
                             }>
                                 <div class="space-y-3">
-                                    {move || local_items.get().into_iter().map(|item| {
+                                    <Show when=move || filtered_local.get().is_empty()>
+                                        <p class="text-sm text-gray-500 text-center m-0 py-4">"没有符合条件的记录"</p>
+                                    </Show>
+                                    {move || filtered_local.get().into_iter().map(|item| {
                                         let id = item.id.clone();
                                         let id_value = StoredValue::new(id.clone());
                                         let summary = item.summary.clone();
                                         let score = item.health_score;
                                         let timestamp = format_timestamp(item.timestamp);
                                         let item_clone = item.clone();
+                                        let annotations = item.annotations();
+                                        let on_favorite = Callback::new({
+                                            let annotations = annotations.clone();
+                                            move |is_favorite| {
+                                                save_local_annotations(
+                                                    id_value.get_value(),
+                                                    HistoryAnnotations { is_favorite, ..annotations.clone() },
+                                                )
+                                            }
+                                        });
+                                        let on_save_annotations = Callback::new(move |annotations| {
+                                            save_local_annotations(id_value.get_value(), annotations)
+                                        });
                                         let image_path = StoredValue::new(item.image_path.clone());
                                         let image_url = image_path
                                             .get_value()
@@ -385,6 +446,12 @@ pub fn HistoryPage() -> impl IntoView {
                                                     </p>
                                                 </div>
 
+                                                <HistoryAnnotationsPanel
+                                                    annotations=annotations
+                                                    on_favorite=on_favorite
+                                                    on_save=on_save_annotations
+                                                />
+
                                                 <div class="flex items-center justify-between pt-3 border-t border-gray-100">
                                                     <span class="text-sm text-gray-600">
                                                         "健康评分："
@@ -445,13 +512,29 @@ pub fn HistoryPage() -> impl IntoView {
                             </div>
                         }>
                             <div class="space-y-3 mb-6">
-                                {move || local_items.get().into_iter().map(|item| {
+                                <Show when=move || filtered_local.get().is_empty()>
+                                    <p class="text-xs text-gray-400 text-center m-0 py-3">"没有符合条件的本地记录"</p>
+                                </Show>
+                                {move || filtered_local.get().into_iter().map(|item| {
                                     let id = item.id.clone();
                                     let id_value = StoredValue::new(id.clone());
                                     let summary = item.summary.clone();
                                     let score = item.health_score;
                                     let timestamp = format_timestamp(item.timestamp);
                                     let item_clone = item.clone();
+                                    let annotations = item.annotations();
+                                    let on_favorite = Callback::new({
+                                        let annotations = annotations.clone();
+                                        move |is_favorite| {
+                                            save_local_annotations(
+                                                id_value.get_value(),
+                                                HistoryAnnotations { is_favorite, ..annotations.clone() },
+                                            )
+                                        }
+                                    });
+                                    let on_save_annotations = Callback::new(move |annotations| {
+                                        save_local_annotations(id_value.get_value(), annotations)
+                                    });
                                     let image_path = StoredValue::new(item.image_path.clone());
                                     let image_url = image_path
                                         .get_value()
@@ -480,6 +563,12 @@ pub fn HistoryPage() -> impl IntoView {
                                                 </p>
                                             </div>
 
+                                            <HistoryAnnotationsPanel
+                                                annotations=annotations
+                                                on_favorite=on_favorite
+                                                on_save=on_save_annotations
+                                            />
+
                                             <div class="flex items-center justify-between pt-3 border-t border-gray-100">
                                                 <span class="text-sm text-gray-600">
                                                     "健康评分："
@@ -521,7 +610,15 @@ pub fn HistoryPage() -> impl IntoView {
 
                     <div class="px-5 mb-6">
                         <div class="flex items-center justify-between mb-3 px-1">
-                            <h2 class="text-base font-bold text-gray-900 m-0">"云端历史"</h2>
+                            <div class="flex items-center gap-3">
+                                <h2 class="text-base font-bold text-gray-900 m-0">"云端历史"</h2>
+                                <a
+                                    href="/history/insights"
+                                    class="text-xs text-emerald-600 font-medium no-underline hover:text-emerald-700"
+                                >
+                                    "📈 健康洞察"
+                                </a>
+                            </div>
                             <span class="text-xs text-emerald-600 flex items-center gap-1">
                                 <div class="w-2 h-2 bg-emerald-500 rounded-full animate-pulse"></div>
                                 "已同步"
@@ -547,7 +644,7 @@ pub fn HistoryPage() -> impl IntoView {
                                         </div>
                                     </div>
                                     <div class="flex items-center justify-center pt-3 border-t border-emerald-50">
-                                        <span class="text-sm font-medium text-emerald-600">"云端暂无数据，去分析一条？"</span>
+                                        <span class="text-sm font-medium text-emerald-600">{move || if search.get().is_empty() { "云端暂无数据，去分析一条？" } else { "没有符合条件的记录" }}</span>
                                     </div>
                                 </a>
                             }>
@@ -555,7 +652,29 @@ pub fn HistoryPage() -> impl IntoView {
                                     {move || items.get().into_iter().map(|item| {
                                         let id = item.id;
                                         let item_clone = item.clone();
+                                        let annotations = HistoryAnnotations {
+                                            is_favorite: item.is_favorite,
+                                            tags: item.tags.clone(),
+                                            note: item.note.clone(),
+                                        };
+                                        let on_favorite = Callback::new(move |is_favorite| {
+                                            spawn_local(async move {
+                                                match services::set_history_favorite(id, is_favorite).await {
+                                                    Ok(response) => apply_cloud_annotations(response),
+                                                    Err(err) => emit_toast(ToastLevel::Error, "保存失败", &err),
+                                                }
+                                            });
+                                        });
+                                        let on_save_annotations = Callback::new(move |annotations: HistoryAnnotations| {
+                                            spawn_local(async move {
+                                                match services::update_history_notes(id, annotations.tags, annotations.note).await {
+                                                    Ok(response) => apply_cloud_annotations(response),
+                                                    Err(err) => emit_toast(ToastLevel::Error, "保存失败", &err),
+                                                }
+                                            });
+                                        });
                                         let summary = item.summary.clone().unwrap_or_default();
+                                        let profile_name = item.profile_name.clone();
                                         let formatted_time = format_iso_datetime(&item.created_at);
                                         let image_url = StoredValue::new(item.image_url.clone());
                                         let resolved_image_url =
@@ -564,9 +683,16 @@ pub fn HistoryPage() -> impl IntoView {
                                         view! {
                                             <div class="p-4 shadow-lg border-0 bg-white-95 backdrop-blur-sm rounded-2xl transition-all duration-300">
                                                 <div class="flex items-center justify-between mb-3">
-                                                    <span class="bg-blue-500 text-white border-0 text-xs px-2.5 py-0.5 rounded-full font-medium">
-                                                        "云端记录"
-                                                    </span>
+                                                    <div class="flex items-center gap-2">
+                                                        <span class="bg-blue-500 text-white border-0 text-xs px-2.5 py-0.5 rounded-full font-medium">
+                                                            "云端记录"
+                                                        </span>
+                                                        {profile_name.map(|name| view! {
+                                                            <span class="bg-amber-50 text-amber-700 border border-amber-100 text-xs px-2.5 py-0.5 rounded-full font-medium">
+                                                                {format!("为{}评分", name)}
+                                                            </span>
+                                                        })}
+                                                    </div>
                                                     <span class="text-xs text-gray-500">{formatted_time}</span>
                                                 </div>
 
@@ -591,6 +717,12 @@ pub fn HistoryPage() -> impl IntoView {
                                                     </p>
                                                 </div>
 
+                                                <HistoryAnnotationsPanel
+                                                    annotations=annotations
+                                                    on_favorite=on_favorite
+                                                    on_save=on_save_annotations
+                                                />
+
                                                 <div class="flex items-center justify-between pt-3 border-t border-gray-100">
                                                     <span class="text-sm text-gray-600">
                                                         "健康评分："
@@ -0,0 +1,245 @@
+//! Insights page - trends and recurring ingredients across the user's history
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared::{IngredientCount, InsightsResponse, MonthComparison, WeeklyInsight};
+
+use crate::components::IconArrowLeft;
+use crate::services;
+use crate::stores::{AppState, ToastLevel};
+use crate::utils::emit_toast;
+
+const INSIGHT_WEEKS: i32 = 12;
+
+fn score_text(score: Option<f64>) -> String {
+    score
+        .map(|score| format!("{:.1}", score))
+        .unwrap_or_else(|| "--".to_string())
+}
+
+fn trend_text(trend: Option<f64>) -> String {
+    match trend {
+        Some(trend) if trend >= 0.05 => format!("每周平均上升 {:.1} 分", trend),
+        Some(trend) if trend <= -0.05 => format!("每周平均下降 {:.1} 分", -trend),
+        Some(_) => "近期评分基本持平".to_string(),
+        None => "记录太少，暂无趋势".to_string(),
+    }
+}
+
+fn change_view(comparison: &MonthComparison) -> impl IntoView {
+    let (text, class) = match comparison.change {
+        Some(change) if change >= 0.05 => (
+            format!("较上月提升 {:.1} 分", change),
+            "text-sm font-semibold text-emerald-600",
+        ),
+        Some(change) if change <= -0.05 => (
+            format!("较上月下降 {:.1} 分", -change),
+            "text-sm font-semibold text-red-600",
+        ),
+        Some(_) => ("与上月持平".to_string(), "text-sm text-gray-600"),
+        None if comparison.previous_analyses == 0 => {
+            ("上月暂无记录".to_string(), "text-sm text-gray-500")
+        }
+        None => ("本月暂无记录".to_string(), "text-sm text-gray-500"),
+    };
+    view! { <span class=class>{text}</span> }
+}
+
+fn week_bars(weeks: Vec<WeeklyInsight>) -> impl IntoView {
+    weeks
+        .into_iter()
+        .map(|week| {
+            let height = week
+                .average_score
+                .map(|score| score.clamp(0.0, 100.0).round() as i32)
+                .unwrap_or(0);
+            let label = week.week_start.get(5..).unwrap_or_default().to_string();
+            let title = match week.average_score {
+                Some(score) => format!("{} 起 {} 次，平均 {:.1} 分", week.week_start, week.analyses, score),
+                None => format!("{} 起无记录", week.week_start),
+            };
+            view! {
+                <div class="flex-1 min-w-0 flex flex-col items-center gap-1" title=title>
+                    <div class="w-full h-24 flex items-end rounded bg-emerald-50">
+                        <div class="w-full rounded bg-emerald-500" style=format!("height: {}%", height)></div>
+                    </div>
+                    <span class="text-[10px] text-gray-400">{label}</span>
+                </div>
+            }
+        })
+        .collect_view()
+}
+
+fn ingredient_rows(items: Vec<IngredientCount>) -> impl IntoView {
+    items
+        .into_iter()
+        .map(|item| {
+            view! {
+                <li class="flex items-center justify-between gap-3 py-2">
+                    <span class="text-sm text-gray-800">{item.name}</span>
+                    <span class="shrink-0 text-xs text-gray-500">{format!("{} 次", item.analyses)}</span>
+                </li>
+            }
+        })
+        .collect_view()
+}
+
+fn ingredient_card(title: &'static str, items: Vec<IngredientCount>) -> impl IntoView {
+    view! {
+        <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+            <h4 class="m-0 text-sm font-semibold text-gray-800">{title}</h4>
+            {if items.is_empty() {
+                view! { <p class="m-0 py-2 text-xs text-gray-500">"暂无记录"</p> }.into_any()
+            } else {
+                view! {
+                    <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                        {ingredient_rows(items)}
+                    </ul>
+                }
+                .into_any()
+            }}
+        </div>
+    }
+}
+
+fn insights_view(insights: InsightsResponse) -> impl IntoView {
+    let share = insights
+        .allergen_warning_share
+        .map(|share| format!("{:.0}%", share * 100.0))
+        .unwrap_or_else(|| "--".to_string());
+    let comparison = insights.month_comparison;
+
+    view! {
+        <div class="mb-4 grid grid-cols-3 gap-2">
+            <div class="rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-3 py-3 text-center">
+                <div class="text-xs text-gray-500">"分析次数"</div>
+                <div class="mt-1 text-xl font-bold text-gray-800">{insights.total_analyses}</div>
+            </div>
+            <div class="rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-3 py-3 text-center">
+                <div class="text-xs text-gray-500">"平均评分"</div>
+                <div class="mt-1 text-xl font-bold text-emerald-600">{score_text(insights.average_score)}</div>
+            </div>
+            <div class="rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-3 py-3 text-center">
+                <div class="text-xs text-gray-500">"触发过敏原警报"</div>
+                <div class="mt-1 text-xl font-bold text-amber-600">{share}</div>
+            </div>
+        </div>
+
+        <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+            <div class="flex items-center justify-between">
+                <h4 class="m-0 text-sm font-semibold text-gray-800">
+                    {format!("📅 {} 月度对比", comparison.current_month)}
+                </h4>
+                {change_view(&comparison)}
+            </div>
+            <div class="mt-2 grid grid-cols-2 gap-2 text-sm text-gray-700">
+                <div>
+                    {format!(
+                        "本月 {} 次，平均 {}",
+                        comparison.current_analyses,
+                        score_text(comparison.current_average),
+                    )}
+                </div>
+                <div>
+                    {format!(
+                        "上月 {} 次，平均 {}",
+                        comparison.previous_analyses,
+                        score_text(comparison.previous_average),
+                    )}
+                </div>
+            </div>
+        </div>
+
+        <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+            <div class="flex items-center justify-between">
+                <h4 class="m-0 text-sm font-semibold text-gray-800">"📈 每周平均评分"</h4>
+                <span class="text-xs text-gray-500">{trend_text(insights.weekly_trend)}</span>
+            </div>
+            <div class="mt-3 flex items-end gap-1">{week_bars(insights.weeks)}</div>
+        </div>
+
+        {ingredient_card("⚠️ 常见高风险配料", insights.high_risk_ingredients)}
+        {ingredient_card("🧪 常见添加剂", insights.additives)}
+    }
+}
+
+#[component]
+pub fn InsightsPage() -> impl IntoView {
+    let state = use_context::<AppState>().expect("AppState not found");
+    let insights = RwSignal::new(None::<InsightsResponse>);
+    let loading = RwSignal::new(false);
+
+    create_effect(move |_| {
+        if state.auth_user.get().is_none() {
+            insights.set(None);
+            return;
+        }
+        loading.set(true);
+        spawn_local(async move {
+            match services::fetch_insights(INSIGHT_WEEKS).await {
+                Ok(response) => insights.set(Some(response)),
+                Err(err) => emit_toast(ToastLevel::Error, "加载失败", &err),
+            }
+            loading.set(false);
+        });
+    });
+
+    let on_back = move |_| {
+        // Navigate back using browser history
+        if let Some(window) = web_sys::window() {
+            if let Some(history) = window.history().ok() {
+                let _ = history.back();
+            }
+        }
+    };
+
+    view! {
+        <section class="page figma">
+            <div class="flex items-center px-4 py-3 bg-white-80 backdrop-blur-xl sticky top-0 z-10 shadow-sm">
+                <button
+                    class="mr-3 -ml-2 w-10 h-10 rounded-full border-0 bg-transparent flex items-center justify-center text-gray-700 hover:text-gray-900 hover:bg-gray-100 transition-colors"
+                    on:click=on_back
+                    aria-label="返回上一页"
+                >
+                    <IconArrowLeft />
+                </button>
+                <h2 class="m-0 text-base font-semibold text-gray-800">"健康洞察"</h2>
+            </div>
+
+            <div class="page-scrollable-content px-5 py-5">
+                {move || {
+                    if state.auth_user.get().is_none() {
+                        return view! {
+                            <a
+                                href="/profile"
+                                class="block text-center text-sm text-emerald-600 font-medium no-underline py-6"
+                            >
+                                "登录后可查看基于云端历史的健康洞察"
+                            </a>
+                        }
+                        .into_any();
+                    }
+                    match insights.get() {
+                        Some(insights) if insights.total_analyses > 0 => {
+                            insights_view(insights).into_any()
+                        }
+                        Some(_) => view! {
+                            <p class="text-sm text-gray-600 text-center m-0 py-6">
+                                "还没有完成的分析，去分析一条吧"
+                            </p>
+                        }
+                        .into_any(),
+                        None if loading.get() => view! {
+                            <p class="text-sm text-gray-600 text-center m-0 py-6">"加载中…"</p>
+                        }
+                        .into_any(),
+                        None => view! {
+                            <p class="text-sm text-gray-600 text-center m-0 py-6">"暂无数据"</p>
+                        }
+                        .into_any(),
+                    }
+                }}
+            </div>
+        </section>
+    }
+}
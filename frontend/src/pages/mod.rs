@@ -7,11 +7,13 @@ mod community_detail;
 mod confirm;
 mod detail;
 mod history;
+mod insights;
 mod login;
 mod ocr;
 mod onboarding;
 mod profile;
 mod register;
+mod score_explanation;
 mod summary;
 
 pub use analyzing::AnalyzingPage;
@@ -21,9 +23,11 @@ pub use community_detail::CommunityDetailPage;
 pub use confirm::ConfirmPage;
 pub use detail::DetailPage;
 pub use history::HistoryPage;
+pub use insights::InsightsPage;
 pub use login::LoginPage;
 pub use ocr::OcrPage;
 pub use onboarding::OnboardingPage;
 pub use profile::ProfilePage;
 pub use register::RegisterPage;
+pub use score_explanation::ScoreExplanationPage;
 pub use summary::SummaryPage;
@@ -3,12 +3,19 @@ use leptos::task::spawn_local;
 use leptos_router::hooks::use_navigate;
 use serde_json::json;
 
-use crate::components::{get_preference_label, ConfirmModal, PreferenceCard};
+use crate::components::{
+    get_preference_label, AllergenSelector, ConfirmModal, DietSelector, FocusSelector,
+    PreferenceCard,
+};
 use crate::services;
 use crate::stores::{AppState, ToastLevel};
 use crate::utils::emit_toast;
 use crate::utils::local_storage;
-use crate::utils::preference::{merge_preferences, save_preference};
+use crate::utils::preference::{
+    load_allergens, load_diets, load_extra_focuses, merge_allergens, merge_diets,
+    merge_extra_focuses, merge_preferences, save_allergens, save_diets, save_extra_focuses,
+    save_preference,
+};
 
 #[component]
 pub fn OnboardingPage() -> impl IntoView {
@@ -21,6 +28,9 @@ pub fn OnboardingPage() -> impl IntoView {
         .unwrap_or_else(|| "normal".to_string());
 
     let preference = RwSignal::new(initial_preference);
+    let allergens = RwSignal::new(load_allergens());
+    let diets = RwSignal::new(load_diets());
+    let extra_focuses = RwSignal::new(load_extra_focuses());
     let show_confirm = RwSignal::new(false);
 
     let on_save_click = move |_| {
@@ -30,7 +40,17 @@ pub fn OnboardingPage() -> impl IntoView {
     let on_confirm_save = move |_| {
         show_confirm.set(false);
         let pref_value = preference.get();
+        let selected_allergens = allergens.get();
+        let selected_diets = diets.get();
+        let selected_focuses: Vec<String> = extra_focuses
+            .get()
+            .into_iter()
+            .filter(|focus| *focus != pref_value)
+            .collect();
         save_preference(&pref_value);
+        save_extra_focuses(&selected_focuses);
+        save_allergens(&selected_allergens);
+        save_diets(&selected_diets);
         state.analysis_preference.set(Some(pref_value.clone()));
         local_storage::set_has_seen_onboarding(true);
         state.has_seen_onboarding.set(true);
@@ -43,6 +63,9 @@ pub fn OnboardingPage() -> impl IntoView {
                     .map(|prefs| prefs.preferences)
                     .unwrap_or_else(|_| json!({}));
                 let merged = merge_preferences(base, Some(val_clone.as_str()), Some(true));
+                let merged = merge_allergens(merged, &selected_allergens);
+                let merged = merge_diets(merged, &selected_diets);
+                let merged = merge_extra_focuses(merged, &selected_focuses);
                 match services::update_preferences(merged).await {
                     Ok(_) => {
                         emit_toast(ToastLevel::Success, "已保存", "人群设置已保存");
@@ -98,6 +121,40 @@ pub fn OnboardingPage() -> impl IntoView {
                         })
                     />
 
+                    <div class="mt-6 px-1">
+                        <div class="text-sm font-semibold text-gray-800">"同时关注（可多选）"</div>
+                        <p class="mt-1 mb-3 text-xs text-gray-500">"与上方人群定位一起评分，上方选择的权重更高"</p>
+                        <FocusSelector
+                            value=Signal::derive(move || extra_focuses.get())
+                            primary=Signal::derive(move || preference.get())
+                            on_change=Callback::new(move |value: Vec<String>| {
+                                extra_focuses.set(value);
+                            })
+                        />
+                    </div>
+
+                    <div class="mt-6 px-1">
+                        <div class="text-sm font-semibold text-gray-800">"我的过敏原（可多选）"</div>
+                        <p class="mt-1 mb-3 text-xs text-gray-500">"检测到所选过敏原时，结果页会显示醒目警报"</p>
+                        <AllergenSelector
+                            value=Signal::derive(move || allergens.get())
+                            on_change=Callback::new(move |value: Vec<shared::Allergen>| {
+                                allergens.set(value);
+                            })
+                        />
+                    </div>
+
+                    <div class="mt-6 px-1">
+                        <div class="text-sm font-semibold text-gray-800">"我的饮食方式（可多选）"</div>
+                        <p class="mt-1 mb-3 text-xs text-gray-500">"结果页会逐项判断产品是否符合所选饮食方式"</p>
+                        <DietSelector
+                            value=Signal::derive(move || diets.get())
+                            on_change=Callback::new(move |value: Vec<shared::DietRegime>| {
+                                diets.set(value);
+                            })
+                        />
+                    </div>
+
                     <div class="flex items-center gap-4 mt-8 px-2">
                         <button class="flex-1 py-3 px-4 bg-gray-100 hover:bg-gray-200 text-gray-700 text-sm font-semibold rounded-2xl transition-all cursor-pointer border-0" on:click=on_skip>
                             "先体验，后设置"
@@ -2,6 +2,7 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos_router::hooks::use_navigate;
 
+use crate::components::{FamilyProfileManager, RuleOverlayManager};
 use crate::services;
 use crate::stores::{AppState, ToastLevel};
 use crate::utils::emit_toast;
@@ -122,6 +123,9 @@ pub fn ProfilePage() -> impl IntoView {
                                         <span class="text-red-300">"›"</span>
                                     </button>
                                 </div>
+
+                                <FamilyProfileManager />
+                                <RuleOverlayManager />
                             </div>
                         }
                     })
@@ -2,7 +2,6 @@ use leptos::prelude::*;
 use leptos::task::spawn_local;
 use leptos_router::hooks::use_navigate;
 use serde_json::json;
-use uuid::Uuid;
 
 use crate::services;
 use crate::stores::{AppState, ToastLevel};
@@ -58,12 +57,9 @@ pub fn RegisterPage() -> impl IntoView {
                     })
                     .unwrap_or(false);
                 if should_migrate {
-                    let ids: Vec<Uuid> = local_items
-                        .iter()
-                        .filter_map(|item| Uuid::parse_str(&item.id).ok())
-                        .collect();
-                    if !ids.is_empty() {
-                        match services::migrate_local_history(ids).await {
+                    let request = local_history::migrate_request(&local_items);
+                    if !request.ids.is_empty() {
+                        match services::migrate_local_history(&request).await {
                             Ok(resp) => {
                                 if let Err(err) = local_history::clear_local_history() {
                                     emit_toast(ToastLevel::Warning, "清理本地记录失败", &err);
@@ -0,0 +1,222 @@
+//! Score explanation page - where the health score points went
+
+use leptos::prelude::*;
+use leptos::task::spawn_local;
+use shared::{
+    DimensionAttribution, IngredientAttribution, ScoreBreakdown, ScoreExplanationResponse,
+};
+
+use crate::components::IconArrowLeft;
+use crate::services;
+use crate::stores::{AppState, ToastLevel};
+use crate::utils::emit_toast;
+
+fn dimension_label(dimension: &str) -> String {
+    match dimension {
+        "additives_processing" => "添加剂与加工".to_string(),
+        "sugar_fat" => "糖与脂肪".to_string(),
+        "nutrition_value" => "营养价值".to_string(),
+        "sensitive" => "敏感成分".to_string(),
+        "formula_complexity" => "配方复杂度".to_string(),
+        "position_weighting" => "配料位置加权".to_string(),
+        "overall" => "综合评分".to_string(),
+        "adjustment" => "取整调整".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn source_label(source: &str) -> &'static str {
+    match source {
+        "rule_conflict" => "规则库校正",
+        "position_weighting" => "位置加权",
+        _ => "其他",
+    }
+}
+
+fn points_text(points: i32) -> String {
+    if points > 0 {
+        format!("+{} 分", points)
+    } else {
+        format!("{} 分", points)
+    }
+}
+
+fn points_class(points: i32) -> &'static str {
+    if points < 0 {
+        "shrink-0 text-sm font-semibold text-red-600"
+    } else if points > 0 {
+        "shrink-0 text-sm font-semibold text-emerald-600"
+    } else {
+        "shrink-0 text-sm font-semibold text-gray-400"
+    }
+}
+
+fn dimension_rows(items: Vec<ScoreBreakdown>) -> impl IntoView {
+    items
+        .into_iter()
+        .filter(|item| item.dimension != "position_weighting")
+        .map(|item| {
+            let score = item.score.clamp(0, 100);
+            view! {
+                <li class="py-2">
+                    <div class="flex items-center justify-between text-sm">
+                        <span class="text-gray-800">{dimension_label(&item.dimension)}</span>
+                        <span class="font-semibold text-gray-700">{score}</span>
+                    </div>
+                    <div class="mt-1 h-2 rounded-full bg-emerald-50 overflow-hidden">
+                        <div class="h-2 rounded-full bg-emerald-500" style=format!("width: {}%", score)></div>
+                    </div>
+                    {item.reason.map(|reason| view! {
+                        <div class="mt-1 text-xs text-gray-500">{reason}</div>
+                    })}
+                </li>
+            }
+        })
+        .collect_view()
+}
+
+fn attribution_rows(items: Vec<DimensionAttribution>) -> impl IntoView {
+    items
+        .into_iter()
+        .map(|item| {
+            let weight = (item.weight > 0.0 && item.weight < 1.0)
+                .then(|| format!("权重 {}%", (item.weight * 100.0).round() as i32));
+            view! {
+                <li class="flex items-start justify-between gap-3 py-2">
+                    <div class="min-w-0">
+                        <div class="text-sm font-medium text-gray-800">{dimension_label(&item.dimension)}</div>
+                        {weight.map(|weight| view! {
+                            <div class="mt-1 text-xs text-gray-500">{weight}</div>
+                        })}
+                    </div>
+                    <span class=points_class(item.points)>{points_text(item.points)}</span>
+                </li>
+            }
+        })
+        .collect_view()
+}
+
+fn ingredient_rows(items: Vec<IngredientAttribution>) -> impl IntoView {
+    items
+        .into_iter()
+        .map(|item| {
+            view! {
+                <li class="flex items-start justify-between gap-3 py-2">
+                    <div class="min-w-0">
+                        <div class="text-sm font-medium text-gray-800">{item.ingredient}</div>
+                        <div class="mt-1 text-xs text-gray-500">
+                            {format!("{} · {}", source_label(&item.source), item.reason)}
+                        </div>
+                    </div>
+                    <span class=points_class(item.points)>{points_text(item.points)}</span>
+                </li>
+            }
+        })
+        .collect_view()
+}
+
+#[component]
+pub fn ScoreExplanationPage() -> impl IntoView {
+    let state = use_context::<AppState>().expect("AppState not found");
+    let explanation = RwSignal::new(None::<ScoreExplanationResponse>);
+
+    // Results analysed before attributions were stored are explained by the backend.
+    if let Some(response) = state.analysis_result.get_untracked() {
+        let id = response.id;
+        if let Some(result) = response.result {
+            match result.score_attribution {
+                Some(attribution) => explanation.set(Some(ScoreExplanationResponse {
+                    id,
+                    health_score: result.health_score,
+                    score_breakdown: result.score_breakdown.unwrap_or_default(),
+                    attribution,
+                    reconstructed: false,
+                })),
+                None => spawn_local(async move {
+                    match services::fetch_score_explanation(id).await {
+                        Ok(response) => explanation.set(Some(response)),
+                        Err(err) => emit_toast(ToastLevel::Error, "加载失败", &err),
+                    }
+                }),
+            }
+        }
+    }
+
+    let on_back = move |_| {
+        // Navigate back using browser history
+        if let Some(window) = web_sys::window() {
+            if let Some(history) = window.history().ok() {
+                let _ = history.back();
+            }
+        }
+    };
+
+    view! {
+        <section class="page figma">
+            <div class="flex items-center px-4 py-3 bg-white-80 backdrop-blur-xl sticky top-0 z-10 shadow-sm">
+                <button
+                    class="mr-3 -ml-2 w-10 h-10 rounded-full border-0 bg-transparent flex items-center justify-center text-gray-700 hover:text-gray-900 hover:bg-gray-100 transition-colors"
+                    on:click=on_back
+                    aria-label="返回上一页"
+                >
+                    <IconArrowLeft />
+                </button>
+                <h2 class="m-0 text-base font-semibold text-gray-800">"为什么是这个分数"</h2>
+            </div>
+
+            <div class="page-scrollable-content px-5 py-5">
+                {move || match explanation.get() {
+                    None => view! {
+                        <p class="text-sm text-gray-600 text-center m-0 py-6">"暂无评分说明"</p>
+                    }
+                    .into_any(),
+                    Some(explanation) => {
+                        let attribution = explanation.attribution;
+                        view! {
+                            <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3 text-center">
+                                <div class="text-xs text-gray-500">
+                                    {format!("起始 {} 分，逐项扣减后", attribution.starting_score)}
+                                </div>
+                                <div class="mt-1 text-3xl font-bold text-emerald-600">
+                                    {attribution.final_score}
+                                </div>
+                            </div>
+
+                            {(!explanation.score_breakdown.is_empty()).then(|| view! {
+                                <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+                                    <h4 class="m-0 text-sm font-semibold text-gray-800">"📊 各维度得分"</h4>
+                                    <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                                        {dimension_rows(explanation.score_breakdown)}
+                                    </ul>
+                                </div>
+                            })}
+
+                            <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+                                <h4 class="m-0 text-sm font-semibold text-gray-800">"🧮 维度扣分"</h4>
+                                <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                                    {attribution_rows(attribution.dimensions)}
+                                </ul>
+                            </div>
+
+                            {(!attribution.ingredients.is_empty()).then(|| view! {
+                                <div class="mb-4 rounded-2xl border border-emerald-100 bg-white-95 shadow-sm px-4 py-3">
+                                    <h4 class="m-0 text-sm font-semibold text-gray-800">"🧪 配料增减分"</h4>
+                                    <ul class="m-0 p-0 list-none divide-y divide-emerald-50">
+                                        {ingredient_rows(attribution.ingredients)}
+                                    </ul>
+                                </div>
+                            })}
+
+                            {explanation.reconstructed.then(|| view! {
+                                <p class="m-0 text-xs text-gray-500">
+                                    "该记录生成时尚未保存评分明细，维度部分按综合评分展示。"
+                                </p>
+                            })}
+                        }
+                        .into_any()
+                    }
+                }}
+            </div>
+        </section>
+    }
+}
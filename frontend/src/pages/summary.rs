@@ -8,8 +8,9 @@ use std::time::Duration;
 use wasm_bindgen::JsCast;
 
 use crate::components::{
-    get_preference_label, CommunityShareButton, HealthScoreCard, IconArrowLeft, ShareButton,
-    ShareExportProps, SummaryCard,
+    get_preference_label, AllergenAlertBanner, AlternativesCard, CommunityShareButton,
+    DietVerdictCard, HealthScoreCard, IconArrowLeft, ScoredForNote, ShareButton, ShareExportProps,
+    SummaryCard, ALTERNATIVES_BELOW_SCORE,
 };
 use crate::services;
 use crate::stores::{AnalysisSource, AppState, ToastLevel};
@@ -148,6 +149,9 @@ pub fn SummaryPage() -> impl IntoView {
             summary,
             result,
             image_path: state.selected_image_path.get(),
+            is_favorite: false,
+            tags: Vec::new(),
+            note: None,
         };
 
         if let Err(err) = local_history::add_local_history(item) {
@@ -161,6 +165,11 @@ pub fn SummaryPage() -> impl IntoView {
         navigate_detail("/detail", Default::default());
     };
 
+    let navigate_score = navigate.clone();
+    let on_view_score = move |_| {
+        navigate_score("/score", Default::default());
+    };
+
     let on_back = move |_| {
         // Navigate back using browser history
         if let Some(window) = web_sys::window() {
@@ -184,6 +193,18 @@ pub fn SummaryPage() -> impl IntoView {
             </div>
 
             <div class="page-scrollable-content px-5 py-5">
+                {move || {
+                    let profile_name = state.analysis_result.get().and_then(|r| r.profile_name);
+                    view! { <ScoredForNote profile_name=profile_name /> }
+                }}
+
+                // Selected allergens detected
+                {move || {
+                    state.analysis_result.get()
+                        .and_then(|r| r.result)
+                        .map(|result| view! { <AllergenAlertBanner warnings=result.warnings.clone() /> })
+                }}
+
                 // Health score card
                 <Show when=move || {
                     state.analysis_result.get()
@@ -224,6 +245,22 @@ pub fn SummaryPage() -> impl IntoView {
                     }}
                 </Show>
 
+                // Diet compatibility
+                {move || {
+                    state.analysis_result.get()
+                        .and_then(|r| r.result)
+                        .map(|result| view! { <DietVerdictCard verdicts=result.diet_verdicts /> })
+                }}
+
+                // Healthier alternatives for poor scores
+                {move || {
+                    state.analysis_result.get()
+                        .filter(|response| response.result.as_ref().is_some_and(|result| {
+                            result.health_score < ALTERNATIVES_BELOW_SCORE
+                        }))
+                        .map(|response| view! { <AlternativesCard analysis_id=response.id /> })
+                }}
+
                 // Action buttons
                 <div class="flex flex-col gap-3 pb-5">
                     <button
@@ -233,6 +270,13 @@ pub fn SummaryPage() -> impl IntoView {
                         <span class="text-xl leading-none">"📋"</span>
                         <span class="text-base font-semibold leading-none">"查看详细配料表"</span>
                     </button>
+                    <button
+                        class="w-full h-12 px-5 rounded-2xl border border-emerald-200 bg-white text-emerald-700 font-semibold shadow-sm transition-all flex items-center justify-center gap-2"
+                        on:click=on_view_score
+                    >
+                        <span class="text-xl leading-none">"🧮"</span>
+                        <span class="text-base font-semibold leading-none">"为什么是这个分数"</span>
+                    </button>
 
                     {move || {
                         state.analysis_result.get()
@@ -8,6 +8,7 @@ use crate::stores::ToastLevel;
 use crate::utils::auth_storage;
 use crate::utils::emit_toast;
 use crate::utils::error_messages::{map_api_error, map_client_error};
+use crate::utils::history_search::HistorySearch;
 use shared::{
     CommunityCreatePayload, CommunityDeleteRequest, CommunityPostCreated, CommunityPostDetail,
     CommunityPostListResponse,
@@ -168,10 +169,18 @@ pub async fn confirm_and_analyze(
     id: uuid::Uuid,
     confirmed_text: String,
     preference: Option<String>,
+    preferences: Vec<shared::PreferenceWeight>,
+    allergens: Vec<String>,
+    diets: Vec<String>,
+    profile_id: Option<uuid::Uuid>,
 ) -> Result<shared::AnalysisResponse, String> {
     let payload = shared::ConfirmRequest {
         confirmed_text,
         preference,
+        preferences,
+        allergens,
+        diets,
+        profile_id,
     };
     let body =
         serde_json::to_string(&payload).map_err(|_| map_client_error("serialize_request"))?;
@@ -254,6 +263,46 @@ pub async fn fetch_analysis(id: uuid::Uuid) -> Result<shared::AnalysisResponse,
     serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
 }
 
+pub async fn fetch_alternatives(id: uuid::Uuid) -> Result<shared::AlternativesResponse, String> {
+    let mut init = RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let request = Request::new_with_str_and_init(
+        &format!("{}/api/v1/analysis/{}/alternatives", API_BASE, id),
+        &init,
+    )
+    .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn fetch_score_explanation(
+    id: uuid::Uuid,
+) -> Result<shared::ScoreExplanationResponse, String> {
+    let mut init = RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let request = Request::new_with_str_and_init(
+        &format!("{}/api/v1/analysis/{}/explanation", API_BASE, id),
+        &init,
+    )
+    .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
 async fn send_request(request: Request) -> Result<Response, String> {
     let window = web_sys::window().ok_or_else(|| map_client_error("missing_window"))?;
     let response_value = JsFuture::from(window.fetch_with_request(&request))
@@ -483,7 +532,7 @@ pub async fn update_preferences(
     serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
 }
 
-pub async fn fetch_user_history(page: i64, limit: i64) -> Result<shared::HistoryResponse, String> {
+pub async fn fetch_rule_overlays() -> Result<shared::RuleOverlayListResponse, String> {
     let mut init = RequestInit::new();
     init.set_method("GET");
     init.set_mode(RequestMode::Cors);
@@ -491,15 +540,164 @@ pub async fn fetch_user_history(page: i64, limit: i64) -> Result<shared::History
     apply_auth_header(&headers)?;
     init.set_headers(&headers);
 
+    let request =
+        Request::new_with_str_and_init(&format!("{}/api/v1/users/rule-overlays", API_BASE), &init)
+            .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn create_rule_overlay(
+    overlay: shared::RuleOverlayInput,
+) -> Result<shared::RuleOverlay, String> {
+    let body =
+        serde_json::to_string(&overlay).map_err(|_| map_client_error("serialize_request"))?;
+
+    let mut init = RequestInit::new();
+    init.set_method("POST");
+    init.set_mode(RequestMode::Cors);
+
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|_| map_client_error("content_type"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+
+    let request =
+        Request::new_with_str_and_init(&format!("{}/api/v1/users/rule-overlays", API_BASE), &init)
+            .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn delete_rule_overlay(id: uuid::Uuid) -> Result<(), String> {
+    let mut init = RequestInit::new();
+    init.set_method("DELETE");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
     let request = Request::new_with_str_and_init(
-        &format!(
-            "{}/api/v1/users/history?page={}&limit={}",
-            API_BASE, page, limit
-        ),
+        &format!("{}/api/v1/users/rule-overlays/{}", API_BASE, id),
         &init,
     )
     .map_err(|_| map_client_error("build_request"))?;
 
+    let _ = send_request(request).await?;
+    Ok(())
+}
+
+pub async fn fetch_family_profiles() -> Result<shared::FamilyProfileListResponse, String> {
+    let mut init = RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let request =
+        Request::new_with_str_and_init(&format!("{}/api/v1/users/profiles", API_BASE), &init)
+            .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn create_family_profile(
+    profile: shared::FamilyProfileInput,
+) -> Result<shared::FamilyProfile, String> {
+    let body =
+        serde_json::to_string(&profile).map_err(|_| map_client_error("serialize_request"))?;
+
+    let mut init = RequestInit::new();
+    init.set_method("POST");
+    init.set_mode(RequestMode::Cors);
+
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|_| map_client_error("content_type"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+
+    let request =
+        Request::new_with_str_and_init(&format!("{}/api/v1/users/profiles", API_BASE), &init)
+            .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn delete_family_profile(id: uuid::Uuid) -> Result<(), String> {
+    let mut init = RequestInit::new();
+    init.set_method("DELETE");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let request = Request::new_with_str_and_init(
+        &format!("{}/api/v1/users/profiles/{}", API_BASE, id),
+        &init,
+    )
+    .map_err(|_| map_client_error("build_request"))?;
+
+    let _ = send_request(request).await?;
+    Ok(())
+}
+
+pub async fn fetch_insights(weeks: i32) -> Result<shared::InsightsResponse, String> {
+    let mut init = RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let request = Request::new_with_str_and_init(
+        &format!("{}/api/v1/users/insights?weeks={}", API_BASE, weeks),
+        &init,
+    )
+    .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
+pub async fn fetch_user_history(
+    page: i64,
+    limit: i64,
+    search: &HistorySearch,
+) -> Result<shared::HistoryResponse, String> {
+    let mut init = RequestInit::new();
+    init.set_method("GET");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+
+    let mut url = format!(
+        "{}/api/v1/users/history?page={}&limit={}",
+        API_BASE, page, limit
+    );
+    for (key, value) in search.query_pairs() {
+        let value = String::from(js_sys::encode_uri_component(&value));
+        url.push_str(&format!("&{}={}", key, value));
+    }
+    let request = Request::new_with_str_and_init(&url, &init)
+        .map_err(|_| map_client_error("build_request"))?;
+
     let response = send_request(request).await?;
     let body = read_response_text(&response).await?;
     serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
@@ -521,6 +719,51 @@ pub async fn delete_history(id: uuid::Uuid) -> Result<(), String> {
     Ok(())
 }
 
+pub async fn set_history_favorite(
+    id: uuid::Uuid,
+    is_favorite: bool,
+) -> Result<shared::HistoryAnnotationsResponse, String> {
+    let payload = shared::FavoriteRequest { is_favorite };
+    put_history_annotations(&format!("{}/favorite", id), &payload).await
+}
+
+pub async fn update_history_notes(
+    id: uuid::Uuid,
+    tags: Vec<String>,
+    note: Option<String>,
+) -> Result<shared::HistoryAnnotationsResponse, String> {
+    let payload = shared::HistoryNotesRequest { tags, note };
+    put_history_annotations(&format!("{}/notes", id), &payload).await
+}
+
+async fn put_history_annotations<T: serde::Serialize>(
+    path: &str,
+    payload: &T,
+) -> Result<shared::HistoryAnnotationsResponse, String> {
+    let body = serde_json::to_string(payload).map_err(|_| map_client_error("serialize_request"))?;
+
+    let mut init = RequestInit::new();
+    init.set_method("PUT");
+    init.set_mode(RequestMode::Cors);
+    let headers = Headers::new().map_err(|_| map_client_error("build_headers"))?;
+    headers
+        .set("Content-Type", "application/json")
+        .map_err(|_| map_client_error("content_type"))?;
+    apply_auth_header(&headers)?;
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(&body));
+
+    let request = Request::new_with_str_and_init(
+        &format!("{}/api/v1/users/history/{}", API_BASE, path),
+        &init,
+    )
+    .map_err(|_| map_client_error("build_request"))?;
+
+    let response = send_request(request).await?;
+    let body = read_response_text(&response).await?;
+    serde_json::from_str(&body).map_err(|_| map_client_error("invalid_response"))
+}
+
 pub async fn delete_history_batch(ids: Vec<uuid::Uuid>) -> Result<(), String> {
     let payload = shared::BatchDeleteRequest { ids };
     let body =
@@ -546,11 +789,9 @@ pub async fn delete_history_batch(ids: Vec<uuid::Uuid>) -> Result<(), String> {
 }
 
 pub async fn migrate_local_history(
-    ids: Vec<uuid::Uuid>,
+    payload: &shared::LocalHistoryMigrateRequest,
 ) -> Result<shared::LocalHistoryMigrateResponse, String> {
-    let payload = shared::LocalHistoryMigrateRequest { ids };
-    let body =
-        serde_json::to_string(&payload).map_err(|_| map_client_error("serialize_request"))?;
+    let body = serde_json::to_string(payload).map_err(|_| map_client_error("serialize_request"))?;
 
     let mut init = RequestInit::new();
     init.set_method("POST");
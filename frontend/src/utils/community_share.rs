@@ -89,6 +89,11 @@ mod tests {
             score_breakdown: None,
             rule_hits: vec![],
             confidence: None,
+            ingredient_tree: vec![],
+            rule_set_version: None,
+            rule_conflicts: vec![],
+            diet_verdicts: vec![],
+            score_attribution: None,
         };
         let summary = build_summary_text(&result);
         assert_eq!(summary, "focus");
@@ -127,6 +132,11 @@ mod tests {
             score_breakdown: None,
             rule_hits: vec![],
             confidence: None,
+            ingredient_tree: vec![],
+            rule_set_version: None,
+            rule_conflicts: vec![],
+            diet_verdicts: vec![],
+            score_attribution: None,
         };
         let analysis_id = Uuid::new_v4();
         let payload = build_create_payload(
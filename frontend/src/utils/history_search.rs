@@ -0,0 +1,184 @@
+//! History search: query parameters for the cloud history and matching of local records
+
+use crate::utils::local_history::LocalHistoryItem;
+
+/// Search words and filters typed on the history page; empty fields do not filter
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HistorySearch {
+    pub query: String,
+    pub min_score: Option<i32>,
+    pub max_score: Option<i32>,
+    /// `YYYY-MM-DD`
+    pub from: String,
+    /// `YYYY-MM-DD`, inclusive
+    pub to: String,
+    pub preference: String,
+    pub warning_type: String,
+    pub ingredient: String,
+    pub favorite: bool,
+    pub tag: String,
+}
+
+impl HistorySearch {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Query parameters for `GET /users/history`, unencoded
+    pub fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+        let texts = [
+            ("q", &self.query),
+            ("from", &self.from),
+            ("to", &self.to),
+            ("preference", &self.preference),
+            ("warning_type", &self.warning_type),
+            ("ingredient", &self.ingredient),
+            ("tag", &self.tag),
+        ];
+        for (key, value) in texts {
+            let value = value.trim();
+            if !value.is_empty() {
+                pairs.push((key, value.to_string()));
+            }
+        }
+        if let Some(score) = self.min_score {
+            pairs.push(("min_score", score.to_string()));
+        }
+        if let Some(score) = self.max_score {
+            pairs.push(("max_score", score.to_string()));
+        }
+        if self.favorite {
+            pairs.push(("favorite", "true".to_string()));
+        }
+        pairs
+    }
+
+    /// Whether a local record matches. Local records keep no analysis date string or
+    /// preference, so those two filters are not checked.
+    pub fn matches_local(&self, item: &LocalHistoryItem) -> bool {
+        let names: Vec<String> = item
+            .result
+            .ingredients
+            .iter()
+            .map(|ingredient| ingredient.name.to_lowercase())
+            .collect();
+        let haystack = format!("{} {}", item.summary, names.join(" ")).to_lowercase();
+        let words_match = self
+            .query
+            .split_whitespace()
+            .all(|word| haystack.contains(&word.to_lowercase()));
+        let ingredient = self.ingredient.trim().to_lowercase();
+        let warning_type = self.warning_type.trim();
+        let tag = self.tag.trim().trim_start_matches('#').to_lowercase();
+
+        words_match
+            && self.min_score.is_none_or(|min| item.health_score >= min)
+            && self.max_score.is_none_or(|max| item.health_score <= max)
+            && (ingredient.is_empty() || names.iter().any(|name| name.contains(&ingredient)))
+            && (warning_type.is_empty()
+                || item
+                    .result
+                    .warnings
+                    .iter()
+                    .any(|warning| warning.warning_type == warning_type))
+            && (!self.favorite || item.is_favorite)
+            && (tag.is_empty() || item.tags.iter().any(|known| known.to_lowercase() == tag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared::{AnalysisResult, IngredientInfo};
+
+    fn item(summary: &str, score: i32, ingredients: &[&str]) -> LocalHistoryItem {
+        LocalHistoryItem {
+            id: "local".to_string(),
+            timestamp: 0,
+            health_score: score,
+            summary: summary.to_string(),
+            result: AnalysisResult {
+                health_score: score,
+                summary: summary.to_string(),
+                table: vec![],
+                ingredients: ingredients
+                    .iter()
+                    .map(|name| IngredientInfo {
+                        name: name.to_string(),
+                        category: String::new(),
+                        risk_level: "low".to_string(),
+                        description: None,
+                    })
+                    .collect(),
+                warnings: vec![],
+                recommendation: String::new(),
+                overall_assessment: None,
+                focus_summary: None,
+                focus_ingredients: None,
+                score_breakdown: None,
+                rule_hits: vec![],
+                confidence: None,
+                ingredient_tree: vec![],
+                rule_set_version: None,
+                rule_conflicts: vec![],
+                diet_verdicts: vec![],
+                score_attribution: None,
+            },
+            image_path: None,
+            is_favorite: false,
+            tags: vec![],
+            note: None,
+        }
+    }
+
+    #[test]
+    fn query_pairs_skip_empty_fields() {
+        let search = HistorySearch {
+            query: " 甜味剂 ".to_string(),
+            min_score: Some(60),
+            ..HistorySearch::default()
+        };
+        assert_eq!(
+            search.query_pairs(),
+            vec![("q", "甜味剂".to_string()), ("min_score", "60".to_string())]
+        );
+        assert!(HistorySearch::default().query_pairs().is_empty());
+    }
+
+    #[test]
+    fn local_items_match_words_score_and_ingredient() {
+        let drink = item("含甜味剂的饮料", 40, &["水", "阿斯巴甜"]);
+        let search = HistorySearch {
+            query: "饮料 阿斯巴甜".to_string(),
+            max_score: Some(50),
+            ..HistorySearch::default()
+        };
+        assert!(search.matches_local(&drink));
+
+        let search = HistorySearch {
+            ingredient: "牛乳".to_string(),
+            ..HistorySearch::default()
+        };
+        assert!(!search.matches_local(&drink));
+    }
+
+    #[test]
+    fn local_items_match_favorite_and_tag() {
+        let mut drink = item("饮料", 40, &["水"]);
+        drink.tags = vec!["Kids".to_string()];
+        let search = HistorySearch {
+            tag: "#kids".to_string(),
+            ..HistorySearch::default()
+        };
+        assert!(search.matches_local(&drink));
+
+        let search = HistorySearch {
+            favorite: true,
+            ..HistorySearch::default()
+        };
+        assert!(!search.matches_local(&drink));
+        drink.is_favorite = true;
+        assert!(search.matches_local(&drink));
+    }
+}
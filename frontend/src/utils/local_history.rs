@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
-use shared::AnalysisResult;
+use shared::{
+    AnalysisResult, HistoryAnnotations, LocalHistoryAnnotations, LocalHistoryMigrateRequest,
+};
+use uuid::Uuid;
 use web_sys::window;
 
 const LOCAL_HISTORY_KEY: &str = "smart-ingredients-history";
@@ -14,6 +17,28 @@ pub struct LocalHistoryItem {
     pub result: AnalysisResult,
     #[serde(default)]
     pub image_path: Option<String>,
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl LocalHistoryItem {
+    pub fn annotations(&self) -> HistoryAnnotations {
+        HistoryAnnotations {
+            is_favorite: self.is_favorite,
+            tags: self.tags.clone(),
+            note: self.note.clone(),
+        }
+    }
+
+    fn set_annotations(&mut self, annotations: HistoryAnnotations) {
+        self.is_favorite = annotations.is_favorite;
+        self.tags = annotations.tags;
+        self.note = annotations.note;
+    }
 }
 
 pub fn load_local_history() -> Vec<LocalHistoryItem> {
@@ -37,8 +62,11 @@ pub fn save_local_history(items: &[LocalHistoryItem]) -> Result<(), String> {
     Ok(())
 }
 
-pub fn add_local_history(item: LocalHistoryItem) -> Result<(), String> {
+pub fn add_local_history(mut item: LocalHistoryItem) -> Result<(), String> {
     let mut items = load_local_history();
+    if let Some(existing) = items.iter().find(|existing| existing.id == item.id) {
+        item.set_annotations(existing.annotations());
+    }
     items.retain(|existing| existing.id != item.id);
     items.insert(0, item);
     if items.len() > LOCAL_HISTORY_LIMIT {
@@ -57,6 +85,37 @@ pub fn delete_local_history(id: &str) -> Result<(), String> {
     save_local_history(&items)
 }
 
+/// Store the favorite flag, tags and note of a local record, normalized
+pub fn update_local_annotations(id: &str, annotations: HistoryAnnotations) -> Result<(), String> {
+    let annotations = annotations.normalized()?;
+    let mut items = load_local_history();
+    let Some(item) = items.iter_mut().find(|item| item.id == id) else {
+        return Err("本地记录不存在".to_string());
+    };
+    item.set_annotations(annotations);
+    save_local_history(&items)
+}
+
+/// Migration payload carrying the local favorites, tags and notes along
+pub fn migrate_request(items: &[LocalHistoryItem]) -> LocalHistoryMigrateRequest {
+    let mut ids = Vec::new();
+    let mut annotations = Vec::new();
+    for item in items {
+        let Ok(id) = Uuid::parse_str(&item.id) else {
+            continue;
+        };
+        ids.push(id);
+        let local = item.annotations();
+        if local != HistoryAnnotations::default() {
+            annotations.push(LocalHistoryAnnotations {
+                id,
+                annotations: local,
+            });
+        }
+    }
+    LocalHistoryMigrateRequest { ids, annotations }
+}
+
 pub fn clear_local_history() -> Result<(), String> {
     save_local_history(&[])
 }
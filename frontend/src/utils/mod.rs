@@ -6,6 +6,7 @@ pub mod community_share_storage;
 pub mod community_ui;
 pub mod error_messages;
 pub mod export_image;
+pub mod history_search;
 pub mod local_history;
 pub mod local_storage;
 pub mod navigation;
@@ -1,8 +1,18 @@
 //! Preference storage helpers
 
 use serde_json::{Map, Value};
+use shared::{
+    Allergen, DietRegime, PreferenceWeight, ALLERGENS_PREFERENCE_KEY, DIETS_PREFERENCE_KEY,
+};
 
 const PREFERENCE_KEY: &str = "analysis_preference";
+const ALLERGENS_KEY: &str = "analysis_allergens";
+const DIETS_KEY: &str = "analysis_diets";
+const EXTRA_FOCUSES_KEY: &str = "analysis_extra_focuses";
+/// Field of the preferences document holding the extra focuses
+const EXTRA_FOCUSES_PREFERENCE_KEY: &str = "extra_selections";
+/// Relative weight of the main preference when extra focuses are blended in
+const PRIMARY_FOCUS_WEIGHT: f32 = 2.0;
 pub fn load_preference() -> Option<String> {
     let window = web_sys::window()?;
     let storage = window.local_storage().ok().flatten()?;
@@ -25,6 +35,138 @@ pub fn save_preference(value: &str) {
     let _ = storage.set_item(PREFERENCE_KEY, value);
 }
 
+pub fn load_allergens() -> Vec<Allergen> {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return Vec::new();
+    };
+    let Some(value) = storage.get_item(ALLERGENS_KEY).ok().flatten() else {
+        return Vec::new();
+    };
+    serde_json::from_str::<Vec<String>>(&value)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|key| Allergen::parse(key))
+        .collect()
+}
+
+pub fn save_allergens(allergens: &[Allergen]) {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return;
+    };
+    let _ = storage.set_item(ALLERGENS_KEY, &allergen_keys(allergens).to_string());
+}
+
+/// Replace the allergen selection in a preferences document, keeping other fields
+pub fn merge_allergens(base: Value, allergens: &[Allergen]) -> Value {
+    let mut map = match base {
+        Value::Object(value) => value,
+        _ => Map::new(),
+    };
+    map.insert(
+        ALLERGENS_PREFERENCE_KEY.to_string(),
+        allergen_keys(allergens),
+    );
+    Value::Object(map)
+}
+
+fn allergen_keys(allergens: &[Allergen]) -> Value {
+    Value::Array(
+        allergens
+            .iter()
+            .map(|allergen| Value::String(allergen.as_key().to_string()))
+            .collect(),
+    )
+}
+
+pub fn load_diets() -> Vec<DietRegime> {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return Vec::new();
+    };
+    let Some(value) = storage.get_item(DIETS_KEY).ok().flatten() else {
+        return Vec::new();
+    };
+    serde_json::from_str::<Vec<String>>(&value)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|key| DietRegime::parse(key))
+        .collect()
+}
+
+pub fn save_diets(diets: &[DietRegime]) {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return;
+    };
+    let _ = storage.set_item(DIETS_KEY, &diet_keys(diets).to_string());
+}
+
+/// Replace the diet selection in a preferences document, keeping other fields
+pub fn merge_diets(base: Value, diets: &[DietRegime]) -> Value {
+    let mut map = match base {
+        Value::Object(value) => value,
+        _ => Map::new(),
+    };
+    map.insert(DIETS_PREFERENCE_KEY.to_string(), diet_keys(diets));
+    Value::Object(map)
+}
+
+fn diet_keys(diets: &[DietRegime]) -> Value {
+    Value::Array(
+        diets
+            .iter()
+            .map(|diet| Value::String(diet.as_key().to_string()))
+            .collect(),
+    )
+}
+
+pub fn load_extra_focuses() -> Vec<String> {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return Vec::new();
+    };
+    let Some(value) = storage.get_item(EXTRA_FOCUSES_KEY).ok().flatten() else {
+        return Vec::new();
+    };
+    serde_json::from_str::<Vec<String>>(&value).unwrap_or_default()
+}
+
+pub fn save_extra_focuses(focuses: &[String]) {
+    let Some(storage) = web_sys::window().and_then(|w| w.local_storage().ok().flatten()) else {
+        return;
+    };
+    let value = serde_json::to_string(focuses).unwrap_or_else(|_| "[]".to_string());
+    let _ = storage.set_item(EXTRA_FOCUSES_KEY, &value);
+}
+
+/// Replace the extra focuses in a preferences document, keeping other fields
+pub fn merge_extra_focuses(base: Value, focuses: &[String]) -> Value {
+    let mut map = match base {
+        Value::Object(value) => value,
+        _ => Map::new(),
+    };
+    map.insert(
+        EXTRA_FOCUSES_PREFERENCE_KEY.to_string(),
+        Value::Array(focuses.iter().cloned().map(Value::String).collect()),
+    );
+    Value::Object(map)
+}
+
+/// Weighted focuses for a confirm request: empty without extras, so the single
+/// `preference` applies; otherwise the main preference counts double
+pub fn blend_focuses(primary: &str, extras: &[String]) -> Vec<PreferenceWeight> {
+    let extras: Vec<&String> = extras.iter().filter(|focus| *focus != primary).collect();
+    if extras.is_empty() {
+        return Vec::new();
+    }
+    std::iter::once(PreferenceWeight {
+        preference: primary.to_string(),
+        weight: Some(PRIMARY_FOCUS_WEIGHT),
+    })
+    .chain(extras.into_iter().map(|focus| PreferenceWeight {
+        preference: focus.clone(),
+        weight: None,
+    }))
+    .collect()
+}
+
 pub fn merge_preferences(
     base: Value,
     selection: Option<&str>,
@@ -76,6 +218,38 @@ mod tests {
         assert!(merged.get("has_seen_onboarding").is_none());
     }
 
+    #[test]
+    fn merge_allergens_replaces_selection_only() {
+        let base = json!({"selection": "allergy", "allergens": ["milk"]});
+        let merged = merge_allergens(base, &[Allergen::Peanut, Allergen::TreeNuts]);
+        assert_eq!(
+            merged.get("allergens"),
+            Some(&json!(["peanut", "tree_nuts"]))
+        );
+        assert_eq!(
+            merged.get("selection").and_then(|v| v.as_str()),
+            Some("allergy")
+        );
+    }
+
+    #[test]
+    fn merge_diets_replaces_selection_only() {
+        let base = json!({"allergens": ["milk"], "diets": ["keto"]});
+        let merged = merge_diets(base, &[DietRegime::Vegan, DietRegime::GlutenFree]);
+        assert_eq!(merged.get("diets"), Some(&json!(["vegan", "gluten_free"])));
+        assert_eq!(merged.get("allergens"), Some(&json!(["milk"])));
+    }
+
+    #[test]
+    fn blend_focuses_weights_the_main_preference() {
+        assert!(blend_focuses("kids", &["kids".to_string()]).is_empty());
+        let blended = blend_focuses("kids", &["allergy".to_string()]);
+        assert_eq!(blended.len(), 2);
+        assert_eq!(blended[0].preference, "kids");
+        assert_eq!(blended[0].weight, Some(PRIMARY_FOCUS_WEIGHT));
+        assert_eq!(blended[1].weight, None);
+    }
+
     #[test]
     fn merge_preferences_keeps_existing_selection_when_none() {
         let base = json!({"selection": "elderly"});
@@ -1,6 +1,6 @@
 //! Analysis request and response types
 
-use crate::{AnalysisStatus, LlmStatus, OcrStatus};
+use crate::{AnalysisStatus, DietVerdict, LlmStatus, OcrStatus};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -32,6 +32,29 @@ pub struct ConfirmRequest {
     /// Optional analysis preference
     #[serde(default)]
     pub preference: Option<String>,
+    /// Several analysis focuses blended by weight; when present, `preference` is ignored
+    #[serde(default)]
+    pub preferences: Vec<PreferenceWeight>,
+    /// Allergen keys to alert on, merged with the signed-in user's saved allergens
+    #[serde(default)]
+    pub allergens: Vec<String>,
+    /// Diet keys to check the product against, merged with the signed-in user's saved diets
+    #[serde(default)]
+    pub diets: Vec<String>,
+    /// Family profile to score for; its preference, allergens and avoid-list replace
+    /// the account's own
+    #[serde(default)]
+    pub profile_id: Option<Uuid>,
+}
+
+/// One focus of a blended analysis preference
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreferenceWeight {
+    /// Preference key, as in `ConfirmRequest::preference`
+    pub preference: String,
+    /// Relative weight; defaults to 1
+    #[serde(default)]
+    pub weight: Option<f32>,
 }
 
 /// Analysis result from LLM
@@ -71,11 +94,48 @@ pub struct AnalysisResult {
     /// Confidence level and reasons
     #[serde(default)]
     pub confidence: Option<ConfidenceInfo>,
+    /// Parsed ingredient list with compound ingredients nested
+    #[serde(default)]
+    pub ingredient_tree: Vec<IngredientNode>,
+    /// Rule set version that produced `rule_hits`
+    #[serde(default)]
+    pub rule_set_version: Option<i64>,
+    /// Ingredients where a rule overrode the LLM's risk level
+    #[serde(default)]
+    pub rule_conflicts: Vec<RuleConflict>,
+    /// Compatibility with each diet the user selected
+    #[serde(default)]
+    pub diet_verdicts: Vec<DietVerdict>,
+    /// Where the points between 100 and `health_score` went
+    #[serde(default)]
+    pub score_attribution: Option<ScoreAttribution>,
+}
+
+/// Disagreement between a curated rule and the LLM about one ingredient; the rule wins
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleConflict {
+    /// Ingredient name as listed by the LLM
+    pub ingredient: String,
+    /// ID of the overriding rule
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Name of the overriding rule
+    pub rule_name: String,
+    /// Risk level the LLM assigned
+    pub llm_risk_level: String,
+    /// Risk level the rule assigned, now shown for the ingredient
+    pub rule_risk_level: String,
+    /// Health score points added (positive) or removed (negative) to follow the rule
+    #[serde(default)]
+    pub score_adjustment: i32,
 }
 
 /// Rule hit info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RuleHit {
+    /// ID of the matched rule
+    #[serde(default)]
+    pub rule_id: Option<String>,
     pub name: String,
     pub category: String,
     pub risk_level: String,
@@ -86,6 +146,27 @@ pub struct RuleHit {
     pub evidence: Option<String>,
     #[serde(default)]
     pub source: Option<String>,
+    /// Label text that matched the rule
+    #[serde(default)]
+    pub matched_text: Option<String>,
+    /// How the text matched (exact, substring, fuzzy)
+    #[serde(default)]
+    pub match_type: Option<String>,
+    /// Match quality between 0 and 1
+    #[serde(default)]
+    pub match_score: Option<f32>,
+    /// 1-based position of the top-level ingredient in the label list
+    #[serde(default)]
+    pub position: Option<u32>,
+    /// Percentage declared on the label for the matched ingredient
+    #[serde(default)]
+    pub declared_percentage: Option<f32>,
+    /// Health score points deducted for this hit by position weighting
+    #[serde(default)]
+    pub score_impact: Option<i32>,
+    /// Allergen keys the matched rule detects
+    #[serde(default)]
+    pub allergens: Vec<String>,
 }
 
 /// Confidence factor
@@ -121,6 +202,28 @@ pub struct IngredientInfo {
     pub description: Option<String>,
 }
 
+/// Node in the parsed ingredient list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngredientNode {
+    /// Ingredient name as written on the label
+    pub name: String,
+    /// Bracketed notes such as function class or additive code
+    #[serde(default)]
+    pub annotations: Vec<String>,
+    /// 1-based position among its siblings; labels list ingredients by descending weight
+    #[serde(default)]
+    pub position: u32,
+    /// Declared percentage, e.g. 12 for `白砂糖12%`
+    #[serde(default)]
+    pub percentage: Option<f32>,
+    /// Names of rules matched on this node
+    #[serde(default)]
+    pub matched_rules: Vec<String>,
+    /// Sub-ingredients of a compound ingredient
+    #[serde(default)]
+    pub children: Vec<IngredientNode>,
+}
+
 /// Ingredient row for table rendering
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TableRow {
@@ -149,6 +252,9 @@ pub struct Warning {
     pub ingredients: Vec<String>,
     /// Warning message
     pub message: String,
+    /// Detected allergen the user asked to avoid; shown as a prominent alert
+    #[serde(default)]
+    pub blocker: bool,
 }
 
 /// Scoring breakdown item
@@ -156,13 +262,75 @@ pub struct Warning {
 pub struct ScoreBreakdown {
     /// Dimension key (e.g., additives_processing, sugar_fat)
     pub dimension: String,
-    /// Dimension score (0-100)
+    /// Dimension score (0-100); the `position_weighting` entry instead holds the
+    /// (negative) points deducted after the dimension scores were combined
     pub score: i32,
     /// Reason for the score
     #[serde(default)]
     pub reason: Option<String>,
 }
 
+/// Deterministic split of the final health score into point costs.
+///
+/// `starting_score` plus the points of every dimension and ingredient equals `final_score`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreAttribution {
+    pub starting_score: i32,
+    pub final_score: i32,
+    /// Points each weighted dimension cost, plus any rounding remainder
+    #[serde(default)]
+    pub dimensions: Vec<DimensionAttribution>,
+    /// Points moved by individual ingredients, most costly first
+    #[serde(default)]
+    pub ingredients: Vec<IngredientAttribution>,
+}
+
+/// Points one score dimension cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DimensionAttribution {
+    /// Dimension key as in `ScoreBreakdown`, or `overall` / `adjustment`
+    pub dimension: String,
+    /// Dimension score (0-100), when the dimension was scored
+    #[serde(default)]
+    pub score: Option<i32>,
+    /// Share of the dimension in the weighted score (0-1)
+    #[serde(default)]
+    pub weight: f32,
+    /// Points gained (positive) or lost (negative)
+    pub points: i32,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Points one ingredient moved after the dimension scores were combined
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IngredientAttribution {
+    pub ingredient: String,
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    #[serde(default)]
+    pub rule_name: Option<String>,
+    /// `rule_conflict` or `position_weighting`
+    pub source: String,
+    /// Points gained (positive) or lost (negative)
+    pub points: i32,
+    pub reason: String,
+}
+
+/// Response of the score explanation endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreExplanationResponse {
+    pub id: Uuid,
+    pub health_score: i32,
+    #[serde(default)]
+    pub score_breakdown: Vec<ScoreBreakdown>,
+    pub attribution: ScoreAttribution,
+    /// The result predates stored attributions and was attributed afterwards, without the
+    /// preference weights it was scored with
+    #[serde(default)]
+    pub reconstructed: bool,
+}
+
 /// Full analysis response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnalysisResponse {
@@ -184,6 +352,22 @@ pub struct AnalysisResponse {
     pub result: Option<AnalysisResult>,
     /// Error message (if failed)
     pub error_message: Option<String>,
+    /// GTIN decoded from the product barcode, if one was found
+    #[serde(default)]
+    pub gtin: Option<String>,
+    /// Catalog product linked to this analysis
+    #[serde(default)]
+    pub product_id: Option<Uuid>,
+    /// Canonical result of the linked product, so a known product can be shown without
+    /// waiting for this analysis
+    #[serde(default)]
+    pub canonical_result: Option<AnalysisResult>,
+    /// Family profile the analysis was scored for
+    #[serde(default)]
+    pub profile_id: Option<Uuid>,
+    /// Name of that profile when the analysis ran
+    #[serde(default)]
+    pub profile_name: Option<String>,
     /// Creation timestamp
     pub created_at: String,
     /// Update timestamp
@@ -201,10 +385,19 @@ pub struct HistoryItem {
     pub health_score: Option<i32>,
     /// Summary text
     pub summary: Option<String>,
+    /// Name of the family profile the analysis was scored for
+    #[serde(default)]
+    pub profile_name: Option<String>,
     /// Creation timestamp
     pub created_at: String,
     /// Whether marked as favorite
     pub is_favorite: bool,
+    /// User-defined tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free-text note
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 /// History list response
@@ -39,4 +39,75 @@ pub struct UserProfile {
     pub login_id: String,
     pub created_at: String,
     pub analysis_count: i64,
+    #[serde(default)]
+    pub role: UserRole,
+    #[serde(default)]
+    pub permissions: Vec<Permission>,
+}
+
+/// User role; each role includes the permissions of the roles below it
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    /// Regular user
+    #[default]
+    User,
+    /// Community moderator
+    Moderator,
+    /// Administrator
+    Admin,
+}
+
+/// Action gated by role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Create, edit and delete rules in the rule library
+    ManageRules,
+    /// Remove community posts written by others
+    ModerateCommunity,
+    /// Change other users' roles
+    ManageUsers,
+    /// Rename shared catalog products and choose their canonical analysis
+    CurateCatalog,
+}
+
+impl UserRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Moderator => "moderator",
+            Self::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "user" => Some(Self::User),
+            "moderator" => Some(Self::Moderator),
+            "admin" => Some(Self::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn permissions(self) -> &'static [Permission] {
+        match self {
+            Self::User => &[],
+            Self::Moderator => &[Permission::ModerateCommunity, Permission::CurateCatalog],
+            Self::Admin => &[
+                Permission::ManageRules,
+                Permission::ModerateCommunity,
+                Permission::ManageUsers,
+                Permission::CurateCatalog,
+            ],
+        }
+    }
+}
+
+/// Request to change a user's role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateUserRoleRequest {
+    pub role: UserRole,
 }
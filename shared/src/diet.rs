@@ -0,0 +1,154 @@
+//! Dietary regime types
+
+use serde::{Deserialize, Serialize};
+
+/// Diet a user can ask to have products checked against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DietRegime {
+    Vegan,
+    Vegetarian,
+    Halal,
+    GlutenFree,
+    Keto,
+}
+
+/// Key in the user preferences JSON holding the selected diet keys
+pub const DIETS_PREFERENCE_KEY: &str = "diets";
+
+impl DietRegime {
+    pub const ALL: [DietRegime; 5] = [
+        Self::Vegan,
+        Self::Vegetarian,
+        Self::Halal,
+        Self::GlutenFree,
+        Self::Keto,
+    ];
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Self::Vegan => "vegan",
+            Self::Vegetarian => "vegetarian",
+            Self::Halal => "halal",
+            Self::GlutenFree => "gluten_free",
+            Self::Keto => "keto",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Vegan => "纯素",
+            Self::Vegetarian => "素食",
+            Self::Halal => "清真",
+            Self::GlutenFree => "无麸质",
+            Self::Keto => "生酮",
+        }
+    }
+
+    /// Parse a diet key; `-` and `_` are interchangeable
+    pub fn parse(value: &str) -> Option<Self> {
+        let key = value.trim().to_lowercase().replace('-', "_");
+        Self::ALL.into_iter().find(|diet| diet.as_key() == key)
+    }
+
+    /// Rule group tag marking an ingredient as ruling this diet out, e.g. `not_vegan`
+    pub fn incompatible_tag(self) -> String {
+        format!("not_{}", self.as_key())
+    }
+
+    /// Rule group tag marking an ingredient as possibly ruling this diet out
+    pub fn uncertain_tag(self) -> String {
+        format!("maybe_not_{}", self.as_key())
+    }
+
+    /// Stricter diets inherit the tags of the diets they contain: whatever is not
+    /// vegetarian is not vegan either
+    fn implied_by(self) -> &'static [DietRegime] {
+        match self {
+            Self::Vegan => &[Self::Vegan, Self::Vegetarian],
+            Self::Vegetarian => &[Self::Vegetarian],
+            Self::Halal => &[Self::Halal],
+            Self::GlutenFree => &[Self::GlutenFree],
+            Self::Keto => &[Self::Keto],
+        }
+    }
+
+    /// Whether a rule's group tags rule this diet out
+    pub fn excluded_by(self, tags: &[String]) -> bool {
+        self.implied_by()
+            .iter()
+            .any(|diet| tags.contains(&diet.incompatible_tag()))
+    }
+
+    /// Whether a rule's group tags make this diet uncertain
+    pub fn questioned_by(self, tags: &[String]) -> bool {
+        self.implied_by()
+            .iter()
+            .any(|diet| tags.contains(&diet.uncertain_tag()))
+    }
+
+    /// Whether a rule group tag is one of the diet tags
+    pub fn is_diet_tag(tag: &str) -> bool {
+        Self::ALL
+            .into_iter()
+            .any(|diet| tag == diet.incompatible_tag() || tag == diet.uncertain_tag())
+    }
+
+    /// Diets selected in a user preferences document; unknown keys are skipped
+    pub fn from_preferences(preferences: &serde_json::Value) -> Vec<Self> {
+        let mut selected = Vec::new();
+        let keys = preferences
+            .get(DIETS_PREFERENCE_KEY)
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str());
+        for diet in keys.filter_map(Self::parse) {
+            if !selected.contains(&diet) {
+                selected.push(diet);
+            }
+        }
+        selected
+    }
+}
+
+/// Outcome of checking a product against one diet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DietStatus {
+    /// No detected ingredient rules the diet out
+    Compatible,
+    /// At least one detected ingredient rules the diet out
+    Incompatible,
+    /// Some ingredients may rule the diet out, or the ingredients could not be checked
+    Uncertain,
+}
+
+/// Verdict for one diet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DietVerdict {
+    pub diet: DietRegime,
+    pub status: DietStatus,
+    /// Label text of the offending ingredients, or of the doubtful ones when uncertain
+    #[serde(default)]
+    pub ingredients: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vegan_inherits_vegetarian_tags() {
+        let tags = vec!["not_vegetarian".to_string()];
+        assert!(DietRegime::Vegan.excluded_by(&tags));
+        assert!(DietRegime::Vegetarian.excluded_by(&tags));
+        assert!(!DietRegime::Halal.excluded_by(&tags));
+        assert!(!DietRegime::Vegetarian.excluded_by(&["not_vegan".to_string()]));
+        assert!(DietRegime::is_diet_tag("maybe_not_gluten_free"));
+        assert_eq!(
+            DietRegime::parse("gluten-free"),
+            Some(DietRegime::GlutenFree)
+        );
+    }
+}
@@ -46,3 +46,115 @@ pub struct Ingredient {
     /// Description
     pub description: Option<String>,
 }
+
+/// Allergen a user can ask to be alerted about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Allergen {
+    Peanut,
+    Milk,
+    Egg,
+    /// Gluten-containing cereals such as wheat
+    Gluten,
+    Soy,
+    /// Crustaceans and shellfish
+    Shellfish,
+    Fish,
+    Sesame,
+    TreeNuts,
+    /// Sulfur dioxide and sulfites
+    Sulfites,
+}
+
+/// Key in the user preferences JSON holding the selected allergen keys
+pub const ALLERGENS_PREFERENCE_KEY: &str = "allergens";
+
+impl Allergen {
+    pub const ALL: [Allergen; 10] = [
+        Self::Peanut,
+        Self::Milk,
+        Self::Egg,
+        Self::Gluten,
+        Self::Soy,
+        Self::Shellfish,
+        Self::Fish,
+        Self::Sesame,
+        Self::TreeNuts,
+        Self::Sulfites,
+    ];
+
+    pub fn as_key(self) -> &'static str {
+        match self {
+            Self::Peanut => "peanut",
+            Self::Milk => "milk",
+            Self::Egg => "egg",
+            Self::Gluten => "gluten",
+            Self::Soy => "soy",
+            Self::Shellfish => "shellfish",
+            Self::Fish => "fish",
+            Self::Sesame => "sesame",
+            Self::TreeNuts => "tree_nuts",
+            Self::Sulfites => "sulfites",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Peanut => "花生",
+            Self::Milk => "牛奶/乳制品",
+            Self::Egg => "鸡蛋",
+            Self::Gluten => "麸质/小麦",
+            Self::Soy => "大豆",
+            Self::Shellfish => "甲壳类/贝类",
+            Self::Fish => "鱼类",
+            Self::Sesame => "芝麻",
+            Self::TreeNuts => "坚果",
+            Self::Sulfites => "亚硫酸盐",
+        }
+    }
+
+    /// Parse an allergen key; `wheat` is accepted for gluten
+    pub fn parse(value: &str) -> Option<Self> {
+        let key = value.trim().to_lowercase().replace('-', "_");
+        if key == "wheat" {
+            return Some(Self::Gluten);
+        }
+        Self::ALL
+            .into_iter()
+            .find(|allergen| allergen.as_key() == key)
+    }
+
+    /// Allergens selected in a user preferences document; unknown keys are skipped
+    pub fn from_preferences(preferences: &serde_json::Value) -> Vec<Self> {
+        let mut selected = Vec::new();
+        let keys = preferences
+            .get(ALLERGENS_PREFERENCE_KEY)
+            .and_then(|value| value.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|value| value.as_str());
+        for allergen in keys.filter_map(Self::parse) {
+            if !selected.contains(&allergen) {
+                selected.push(allergen);
+            }
+        }
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn allergens_from_preferences_skips_unknown_and_duplicates() {
+        let preferences =
+            json!({"allergens": ["peanut", "Wheat", "tree-nuts", "durian", "peanut"]});
+        assert_eq!(
+            Allergen::from_preferences(&preferences),
+            vec![Allergen::Peanut, Allergen::Gluten, Allergen::TreeNuts]
+        );
+        assert!(Allergen::from_preferences(&json!({"selection": "allergy"})).is_empty());
+    }
+}
@@ -8,15 +8,21 @@ use serde::{Deserialize, Serialize};
 mod analysis;
 mod auth;
 mod community;
+mod diet;
 mod error;
 mod ingredient;
+mod product;
+mod rule;
 mod user;
 
 pub use analysis::*;
 pub use auth::*;
 pub use community::*;
+pub use diet::*;
 pub use error::*;
 pub use ingredient::*;
+pub use product::*;
+pub use rule::*;
 pub use user::*;
 
 /// OCR status tracking
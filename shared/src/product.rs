@@ -0,0 +1,121 @@
+//! Product catalog types
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::AnalysisResult;
+
+/// Product with its canonical analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Product {
+    /// Product ID
+    pub id: Uuid,
+    /// GTIN (EAN-8, GTIN-13 or GTIN-14)
+    pub gtin: String,
+    /// Brand name
+    pub brand: Option<String>,
+    /// Product name
+    pub name: String,
+    /// Product category
+    pub category: Option<String>,
+    /// Canonical ingredient text
+    pub ingredient_text: Option<String>,
+    /// Latest canonical analysis ID
+    pub canonical_analysis_id: Option<Uuid>,
+    /// Health score of the canonical analysis
+    pub health_score: Option<i32>,
+    /// Canonical analysis result
+    pub canonical_result: Option<AnalysisResult>,
+    /// Creation timestamp
+    pub created_at: String,
+    /// Update timestamp
+    pub updated_at: String,
+}
+
+/// Product search list item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductListItem {
+    /// Product ID
+    pub id: Uuid,
+    /// GTIN
+    pub gtin: String,
+    /// Brand name
+    pub brand: Option<String>,
+    /// Product name
+    pub name: String,
+    /// Product category
+    pub category: Option<String>,
+    /// Health score of the canonical analysis
+    pub health_score: Option<i32>,
+}
+
+/// Product search response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProductListResponse {
+    /// Total number of matches
+    pub total: i64,
+    /// Current page number
+    pub page: i64,
+    /// Items per page
+    pub limit: i64,
+    /// Matching products
+    pub items: Vec<ProductListItem>,
+}
+
+/// Request to attach a completed analysis to a product
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachAnalysisRequest {
+    /// Analysis to use as the canonical result
+    pub analysis_id: Uuid,
+    /// GTIN; defaults to the barcode decoded from the analysis photo
+    #[serde(default)]
+    pub gtin: Option<String>,
+    /// Product name
+    pub name: String,
+    /// Brand name
+    #[serde(default)]
+    pub brand: Option<String>,
+    /// Product category
+    #[serde(default)]
+    pub category: Option<String>,
+}
+
+/// Where a suggested alternative was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlternativeSource {
+    /// Catalog product, scored by its canonical analysis
+    Product,
+    /// Community post
+    Community,
+}
+
+/// Product suggested instead of an analysed one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alternative {
+    pub source: AlternativeSource,
+    /// Product ID or community post ID, depending on `source`
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub brand: Option<String>,
+    pub health_score: i32,
+    /// Points above the analysed product
+    pub score_gain: i32,
+    /// Why the alternative is better, most important first
+    #[serde(default)]
+    pub reasons: Vec<String>,
+}
+
+/// Alternatives response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlternativesResponse {
+    pub analysis_id: Uuid,
+    /// Inferred category of the analysed product; `None` when it could not be inferred,
+    /// in which case `items` is empty
+    #[serde(default)]
+    pub category: Option<String>,
+    /// Best alternative first
+    #[serde(default)]
+    pub items: Vec<Alternative>,
+}
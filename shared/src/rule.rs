@@ -0,0 +1,175 @@
+//! Rule library administration types
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Editable fields of a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleInput {
+    /// Display name, also matched against ingredient text
+    pub name: String,
+    /// Alternative names matched against ingredient text
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Category (allergen, additive, other)
+    pub category: String,
+    /// Risk level (low, medium, high)
+    pub risk_level: String,
+    /// Preference groups whose users see a raised risk
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Description shown with rule hits
+    pub description: String,
+    /// Supporting evidence
+    #[serde(default)]
+    pub evidence: Option<String>,
+    /// Evidence source
+    #[serde(default)]
+    pub source: Option<String>,
+    /// E-numbers, e.g. `E211`
+    #[serde(default)]
+    pub e_numbers: Vec<String>,
+    /// INS numbers, e.g. `211`
+    #[serde(default)]
+    pub ins_numbers: Vec<String>,
+    /// GB 2760 CNS codes, e.g. `17.003`
+    #[serde(default)]
+    pub cns_codes: Vec<String>,
+    /// Allergen keys this rule detects, e.g. `peanut`
+    #[serde(default)]
+    pub allergens: Vec<String>,
+}
+
+/// Request to create a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRuleRequest {
+    /// Stable rule ID, e.g. `additive-sodium-benzoate`
+    pub id: String,
+    #[serde(flatten)]
+    pub rule: RuleInput,
+}
+
+/// Request to add or remove a single alias
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleAliasRequest {
+    pub alias: String,
+}
+
+/// Rule as stored in the rule library
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    /// Rule ID
+    pub id: String,
+    #[serde(flatten)]
+    pub rule: RuleInput,
+    /// Whether the rule engine uses this rule
+    pub enabled: bool,
+    /// Creation timestamp
+    pub created_at: String,
+    /// Update timestamp
+    pub updated_at: String,
+}
+
+/// Rule list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleListResponse {
+    /// Total number of rules
+    pub total: i64,
+    /// Rules ordered by ID
+    pub items: Vec<RuleDefinition>,
+}
+
+/// Audit record of a single rule change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleAuditEntry {
+    /// Audit record ID
+    pub id: Uuid,
+    /// Changed rule ID
+    pub rule_id: String,
+    /// Action (create, update, enable, disable, add_alias, remove_alias, delete)
+    pub action: String,
+    /// User who made the change
+    pub actor_id: Option<Uuid>,
+    /// Rule before the change
+    pub before: Option<serde_json::Value>,
+    /// Rule after the change
+    pub after: Option<serde_json::Value>,
+    /// Change timestamp
+    pub created_at: String,
+}
+
+/// Rule audit list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleAuditResponse {
+    /// Total number of records
+    pub total: i64,
+    /// Current page number
+    pub page: i64,
+    /// Items per page
+    pub limit: i64,
+    /// Audit records, newest first
+    pub items: Vec<RuleAuditEntry>,
+}
+
+/// Snapshot of the enabled rule set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSetVersion {
+    /// Monotonic version number
+    pub version: i64,
+    /// SHA-256 of the canonical rule set content
+    pub content_hash: String,
+    /// Number of enabled rules
+    pub rule_count: i32,
+    /// Snapshot timestamp
+    pub created_at: String,
+}
+
+/// Rule set version list response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSetVersionListResponse {
+    /// Total number of versions
+    pub total: i64,
+    /// Current page number
+    pub page: i64,
+    /// Items per page
+    pub limit: i64,
+    /// Versions, newest first
+    pub items: Vec<RuleSetVersion>,
+}
+
+/// Rule identified by ID and name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuleSummary {
+    pub id: String,
+    pub name: String,
+}
+
+/// Rule present in both versions with different content
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RuleChange {
+    /// Rule ID
+    pub id: String,
+    /// Rule name in the compared version
+    pub name: String,
+    /// Changed fields other than aliases, e.g. `risk_level`
+    pub changed_fields: Vec<String>,
+    /// Aliases only in the compared version
+    pub added_aliases: Vec<String>,
+    /// Aliases only in the base version
+    pub removed_aliases: Vec<String>,
+}
+
+/// Differences between two rule set versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSetDiff {
+    /// Base version
+    pub from_version: i64,
+    /// Compared version
+    pub to_version: i64,
+    /// Rules only in the compared version
+    pub added: Vec<RuleSummary>,
+    /// Rules only in the base version
+    pub removed: Vec<RuleSummary>,
+    /// Rules whose content changed
+    pub changed: Vec<RuleChange>,
+}
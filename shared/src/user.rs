@@ -22,6 +22,90 @@ pub struct BatchDeleteRequest {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalHistoryMigrateRequest {
     pub ids: Vec<Uuid>,
+    /// Favorites, tags and notes kept locally, applied to the migrated analyses
+    #[serde(default)]
+    pub annotations: Vec<LocalHistoryAnnotations>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalHistoryAnnotations {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub annotations: HistoryAnnotations,
+}
+
+pub const MAX_HISTORY_TAGS: usize = 10;
+pub const MAX_HISTORY_TAG_LENGTH: usize = 20;
+pub const MAX_HISTORY_NOTE_LENGTH: usize = 500;
+
+/// Favorite flag, tags and note a user keeps on one of their analyses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryAnnotations {
+    #[serde(default)]
+    pub is_favorite: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+impl HistoryAnnotations {
+    /// Trim tags (dropping a leading `#`) and the note, drop empty and repeated tags,
+    /// and check the limits
+    pub fn normalized(self) -> Result<Self, String> {
+        let mut tags: Vec<String> = Vec::new();
+        for tag in &self.tags {
+            let tag = tag.trim().trim_start_matches('#').trim();
+            if tag.is_empty() || tags.iter().any(|known| known.eq_ignore_ascii_case(tag)) {
+                continue;
+            }
+            if tag.chars().count() > MAX_HISTORY_TAG_LENGTH {
+                return Err(format!("标签长度不能超过 {}", MAX_HISTORY_TAG_LENGTH));
+            }
+            tags.push(tag.to_string());
+        }
+        if tags.len() > MAX_HISTORY_TAGS {
+            return Err(format!("标签数量不能超过 {}", MAX_HISTORY_TAGS));
+        }
+        let note = self
+            .note
+            .as_deref()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+            .map(str::to_string);
+        if note
+            .as_ref()
+            .is_some_and(|note| note.chars().count() > MAX_HISTORY_NOTE_LENGTH)
+        {
+            return Err(format!("备注长度不能超过 {}", MAX_HISTORY_NOTE_LENGTH));
+        }
+        Ok(Self {
+            is_favorite: self.is_favorite,
+            tags,
+            note,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavoriteRequest {
+    pub is_favorite: bool,
+}
+
+/// Replaces the tags and note; the favorite flag is left as is
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryNotesRequest {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryAnnotationsResponse {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub annotations: HistoryAnnotations,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,3 +125,148 @@ pub struct HistoryPruneResponse {
     pub deleted: i64,
     pub total_after: i64,
 }
+
+/// Editable fields of a personal rule overlay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleOverlayInput {
+    /// Ingredient name; overrides the global rule with this name or alias, if any
+    #[serde(default)]
+    pub ingredient: String,
+    /// Global rule to override; the ingredient defaults to the rule's name
+    #[serde(default)]
+    pub rule_id: Option<String>,
+    /// Risk level applied for this user (low, medium, high); ignored when trusted
+    #[serde(default)]
+    pub risk_level: String,
+    /// Trust the ingredient: its rule hits are dropped for this user
+    #[serde(default)]
+    pub trusted: bool,
+    /// Personal note shown with rule hits
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+/// Personal rule overlay as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleOverlay {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub overlay: RuleOverlayInput,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleOverlayListResponse {
+    pub items: Vec<RuleOverlay>,
+}
+
+/// Editable fields of a family member profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FamilyProfileInput {
+    /// Display name, e.g. "宝宝" or "奶奶"
+    #[serde(default)]
+    pub name: String,
+    /// Analysis preference key; `None` uses the preference chosen at confirm time
+    #[serde(default)]
+    pub preference: Option<String>,
+    /// Allergen keys to alert on for this member
+    #[serde(default)]
+    pub allergens: Vec<String>,
+    /// Ingredient names this member avoids; each is treated as a high-risk rule
+    #[serde(default)]
+    pub avoid_ingredients: Vec<String>,
+}
+
+/// Family member profile as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FamilyProfile {
+    pub id: Uuid,
+    #[serde(flatten)]
+    pub profile: FamilyProfileInput,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FamilyProfileListResponse {
+    pub items: Vec<FamilyProfile>,
+}
+
+/// Average health score of the analyses created in one week
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyInsight {
+    /// Monday of the week, `YYYY-MM-DD` (UTC)
+    pub week_start: String,
+    pub analyses: i64,
+    /// `None` for a week without analyses
+    pub average_score: Option<f64>,
+}
+
+/// How many analyses mention an ingredient
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IngredientCount {
+    pub name: String,
+    pub analyses: i64,
+}
+
+/// This month so far against the whole previous month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthComparison {
+    /// `YYYY-MM` (UTC)
+    pub current_month: String,
+    pub current_analyses: i64,
+    pub current_average: Option<f64>,
+    pub previous_analyses: i64,
+    pub previous_average: Option<f64>,
+    /// Current minus previous average; positive is an improvement
+    pub change: Option<f64>,
+}
+
+/// Aggregates over a user's completed analyses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InsightsResponse {
+    pub total_analyses: i64,
+    pub average_score: Option<f64>,
+    /// Oldest week first, including weeks without analyses
+    pub weeks: Vec<WeeklyInsight>,
+    /// Least-squares slope of the weekly averages, in points per week
+    pub weekly_trend: Option<f64>,
+    pub high_risk_ingredients: Vec<IngredientCount>,
+    pub additives: Vec<IngredientCount>,
+    /// Analyses with at least one allergen warning
+    pub allergen_warning_analyses: i64,
+    /// `allergen_warning_analyses / total_analyses`, 0-1
+    pub allergen_warning_share: Option<f64>,
+    pub month_comparison: MonthComparison,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotations_normalize_tags_and_note() {
+        let annotations = HistoryAnnotations {
+            is_favorite: true,
+            tags: vec![
+                " #早餐 ".to_string(),
+                "早餐".to_string(),
+                "Kids".to_string(),
+                "kids".to_string(),
+                "  ".to_string(),
+            ],
+            note: Some("   ".to_string()),
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(annotations.tags, vec!["早餐", "Kids"]);
+        assert_eq!(annotations.note, None);
+
+        let too_long = HistoryAnnotations {
+            tags: vec!["长".repeat(MAX_HISTORY_TAG_LENGTH + 1)],
+            ..HistoryAnnotations::default()
+        };
+        assert!(too_long.normalized().is_err());
+    }
+}